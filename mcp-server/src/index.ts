@@ -9,11 +9,15 @@ import {
 } from '@modelcontextprotocol/sdk/types.js';
 import { exec } from 'child_process';
 import { promisify } from 'util';
-import { readFile } from 'fs/promises';
-import { join } from 'path';
 
 const execAsync = promisify(exec);
 
+// Quote a flag value for the shell and for VQL, which joins its arguments back together:
+// single quotes keep the shell from interpreting it, double quotes keep multi-word values whole
+function quoteFlagValue(value: string): string {
+  return `'"${value.replace(/"/g, '').replace(/'/g, "'\\''")}"'`;
+}
+
 class VQLMCPServer {
   private server: Server;
   private vqlMode: boolean = true; // VQL mode is on by default
@@ -49,26 +53,6 @@ class VQLMCPServer {
     }
   }
 
-  private async readVQLStorage(): Promise<any> {
-    try {
-      // Find VQL directory by looking for VQL/vql_storage.json
-      const { stdout } = await execAsync('find . -name "vql_storage.json" -path "*/VQL/*" | head -1');
-      const storagePath = stdout.trim();
-      
-      if (!storagePath) {
-        throw new Error('VQL storage file not found. Run vql -su first.');
-      }
-
-      const content = await readFile(storagePath, 'utf-8');
-      return JSON.parse(content);
-    } catch (error: any) {
-      throw new McpError(
-        ErrorCode.InternalError,
-        `Failed to read VQL storage: ${error.message}`
-      );
-    }
-  }
-
   private setupHandlers() {
     interface ToolArgs {
       directory?: string;
@@ -83,9 +67,13 @@ class VQLMCPServer {
       asset?: string;
       principle?: string;
       review?: string;
+      author?: string;
+      model?: string;
+      confidence?: number;
+      findings?: object[];
       principles?: string[];
       isExemplar?: boolean;
-      level?: 'H' | 'M' | 'L';
+      level?: string;
       referenceAsset?: string;
       referenceAssets?: string[];
     }
@@ -151,7 +139,7 @@ class VQLMCPServer {
             properties: {
               short: {
                 type: 'string',
-                description: 'Single character identifier for the principle',
+                description: 'Short name for the principle (letters, digits and underscores, e.g. "a" or "arch")',
               },
               long: {
                 type: 'string',
@@ -265,7 +253,7 @@ class VQLMCPServer {
               },
               path: {
                 type: 'string',
-                description: 'File path to the asset',
+                description: 'File path, directory or glob pattern (e.g. "src/user/*.service.ts") for the asset',
               },
             },
             required: ['shortName', 'entity', 'assetType', 'path'],
@@ -291,6 +279,23 @@ class VQLMCPServer {
                 type: 'string',
                 description: 'Review content',
               },
+              author: {
+                type: 'string',
+                description: 'Person the review is attributed to (optional)',
+              },
+              model: {
+                type: 'string',
+                description: 'AI model or session that wrote the review (optional)',
+              },
+              confidence: {
+                type: 'number',
+                description: 'Confidence in the rating, from 0 to 1 (optional)',
+              },
+              findings: {
+                type: 'array',
+                description: 'Specific problems found (optional), each {file, lines, severity, area, description, fix}',
+                items: { type: 'object' },
+              },
             },
             required: ['asset', 'principle', 'review'],
           },
@@ -349,6 +354,25 @@ class VQLMCPServer {
           },
         },
 
+        {
+          name: 'get_review_history',
+          description: 'Get every stored review for an asset, oldest first, to see how ratings changed',
+          inputSchema: {
+            type: 'object',
+            properties: {
+              asset: {
+                type: 'string',
+                description: 'Asset identifier',
+              },
+              principle: {
+                type: 'string',
+                description: 'Optional principle identifier (defaults to all principles)',
+              },
+            },
+            required: ['asset'],
+          },
+        },
+
         // Compliance Management
         {
           name: 'set_exemplar',
@@ -384,8 +408,7 @@ class VQLMCPServer {
               },
               level: {
                 type: 'string',
-                enum: ['H', 'M', 'L'],
-                description: 'Compliance level: H (High), M (Medium), L (Low)',
+                description: 'Compliance level on the principle\'s rating scale: H (High), M (Medium), L (Low) by default; list scales with vql -scale',
               },
             },
             required: ['asset', 'principle', 'level'],
@@ -662,7 +685,12 @@ class VQLMCPServer {
           // Review Management
           case 'store_review':
             const storeReviewOutput = await this.executeVQLCommand(
-              `vql -st ${typedArgs.asset} ${typedArgs.principle} "${typedArgs.review}"`
+              `vql -st ${typedArgs.asset} ${typedArgs.principle} -source mcp` +
+              (typedArgs.author ? ` -author ${quoteFlagValue(typedArgs.author)}` : '') +
+              (typedArgs.model ? ` -model ${quoteFlagValue(typedArgs.model)}` : '') +
+              (typedArgs.confidence !== undefined ? ` -confidence ${typedArgs.confidence}` : '') +
+              (typedArgs.findings ? ` -findings '${JSON.stringify(typedArgs.findings).replace(/'/g, "'\\''")}'` : '') +
+              ` "${typedArgs.review}"`
             );
             return { content: [{ type: 'text', text: storeReviewOutput }] };
 
@@ -670,54 +698,37 @@ class VQLMCPServer {
             if (!typedArgs.asset) {
               throw new McpError(ErrorCode.InvalidParams, 'Asset parameter is required');
             }
-            const storage = await this.readVQLStorage();
-            const asset = storage.asset_references?.[typedArgs.asset];
-            if (!asset) {
-              return { content: [{ type: 'text', text: `Asset '${typedArgs.asset}' not found` }] };
-            }
-            const reviews = asset.principle_reviews || {};
-            const formattedReviews = Object.entries(reviews)
-              .map(([principle, review]: [string, any]) => 
-                `Principle ${principle}: ${review.review}\nCompliance: ${review.compliance || 'Not set'}\n`
-              )
-              .join('\n');
-            return { content: [{ type: 'text', text: formattedReviews || 'No reviews found' }] };
+            // Go through the CLI so every storage backend (JSON, sharded, SQLite) is supported
+            const allReviewsOutput = await this.executeVQLCommand(`vql "${typedArgs.asset}?"`);
+            return { content: [{ type: 'text', text: allReviewsOutput }] };
 
           case 'get_review':
             if (!typedArgs.asset || !typedArgs.principle) {
               throw new McpError(ErrorCode.InvalidParams, 'Asset and principle parameters are required');
             }
-            const storageForReview = await this.readVQLStorage();
-            const assetForReview = storageForReview.asset_references?.[typedArgs.asset];
-            if (!assetForReview) {
-              return { content: [{ type: 'text', text: `Asset '${typedArgs.asset}' not found` }] };
-            }
-            const review = assetForReview.principle_reviews?.[typedArgs.principle];
-            if (!review) {
-              return { content: [{ type: 'text', text: `No review found for asset '${typedArgs.asset}' and principle '${typedArgs.principle}'` }] };
-            }
-            return { content: [{ type: 'text', text: `${review.review}\nCompliance: ${review.compliance || 'Not set'}` }] };
+            const reviewOutput = await this.executeVQLCommand(
+              `vql "${typedArgs.asset}?(${typedArgs.principle})"`
+            );
+            return { content: [{ type: 'text', text: reviewOutput }] };
 
           case 'get_multiple_reviews':
             if (!typedArgs.asset || !typedArgs.principles) {
               throw new McpError(ErrorCode.InvalidParams, 'Asset and principles parameters are required');
             }
-            const storageForMultiple = await this.readVQLStorage();
-            const assetForMultiple = storageForMultiple.asset_references?.[typedArgs.asset];
-            if (!assetForMultiple) {
-              return { content: [{ type: 'text', text: `Asset '${typedArgs.asset}' not found` }] };
+            const multipleReviewsOutput = await this.executeVQLCommand(
+              `vql "${typedArgs.asset}?(${typedArgs.principles.join(',')})"`
+            );
+            return { content: [{ type: 'text', text: multipleReviewsOutput }] };
+
+          case 'get_review_history':
+            if (!typedArgs.asset) {
+              throw new McpError(ErrorCode.InvalidParams, 'Asset parameter is required');
             }
-            const multipleReviews = (typedArgs.principles || [])
-              .map((principle: string) => {
-                const review = assetForMultiple.principle_reviews?.[principle];
-                if (review) {
-                  return `Principle ${principle}: ${review.review}\nCompliance: ${review.compliance || 'Not set'}`;
-                }
-                return null;
-              })
-              .filter(Boolean)
-              .join('\n\n');
-            return { content: [{ type: 'text', text: multipleReviews || 'No reviews found for specified principles' }] };
+            const historyQuery = typedArgs.principle
+              ? `${typedArgs.asset}?history(${typedArgs.principle})`
+              : `${typedArgs.asset}?history`;
+            const historyOutput = await this.executeVQLCommand(`vql "${historyQuery}"`);
+            return { content: [{ type: 'text', text: historyOutput }] };
 
           // Compliance Management
           case 'set_exemplar':
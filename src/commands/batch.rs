@@ -0,0 +1,138 @@
+use std::fs;
+use std::io::Read;
+use std::sync::Arc;
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+
+use crate::commands::json_commands::process_command;
+use crate::models::backend::{self, MemoryBackend};
+use crate::models::json_storage::find_vql_storage;
+
+/// Commands that work on the VQL directory itself and cannot be staged in memory
+const UNBATCHABLE_COMMANDS: [&str; 7] = ["batch", "layout", "undo", "redo", "journal", "su", "setup"];
+
+/// A script line that failed
+struct LineError {
+    line: usize,
+    command: String,
+    error: anyhow::Error,
+}
+
+/// Run a script of VQL commands (CLI or LLM syntax, one per line) as a single change
+///
+/// Reads from `source`, or stdin when it is missing or "-". Every command runs against
+/// one in-memory copy of storage; if any line fails, nothing is saved.
+pub fn run_batch(source: Option<&str>) -> Result<()> {
+    let (script_name, script) = read_script(source)?;
+
+    // Lock the real storage for the whole script so nothing changes underneath it
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let original = serde_json::to_string(&storage)
+        .context("Failed to serialize VQL storage to JSON")?;
+    let staging = Arc::new(MemoryBackend::staging(&vql_dir, &original));
+
+    let previous = backend::set_thread_backend(Some(staging.clone()));
+    let (executed, errors) = run_lines(&script);
+    backend::set_thread_backend(previous);
+
+    if !errors.is_empty() {
+        println!("{} {} of {} command(s) failed in {}:",
+            "ERROR:".red().bold(),
+            errors.len(),
+            executed,
+            script_name);
+        for failure in &errors {
+            println!("  line {}: {}", failure.line, failure.command.blue());
+            println!("    {:#}", failure.error);
+        }
+        return Err(anyhow!("Batch aborted - no changes were saved"));
+    }
+
+    let staged = staging.document().unwrap_or_else(|| original.clone());
+    if staged == original {
+        println!("{} Ran {} command(s) from {}; storage unchanged",
+            "INFO:".blue().bold(),
+            executed,
+            script_name);
+        return Ok(());
+    }
+
+    let document: serde_json::Value = serde_json::from_str(&staged)
+        .context("Failed to parse staged VQL storage")?;
+
+    storage.apply_document(document, format!("batch {} ({} commands)", script_name, executed))?;
+    storage.save(&vql_dir)?;
+
+    println!("{} Applied {} command(s) from {}",
+        "SUCCESS:".green().bold(),
+        executed,
+        script_name);
+
+    Ok(())
+}
+
+/// Read the script from a file, or from stdin
+fn read_script(source: Option<&str>) -> Result<(String, String)> {
+    match source.map(str::trim).filter(|s| !s.is_empty() && *s != "-") {
+        Some(path) => {
+            let path = path.trim_matches('"');
+            let script = fs::read_to_string(path)
+                .context(format!("Failed to read batch script {}", path))?;
+            Ok((path.to_string(), script))
+        },
+        None => {
+            let mut script = String::new();
+            std::io::stdin().read_to_string(&mut script)
+                .context("Failed to read batch script from stdin")?;
+            Ok(("stdin".to_string(), script))
+        },
+    }
+}
+
+/// Run every command in the script, returning how many ran and which failed
+///
+/// Blank lines and lines starting with `#` are skipped. Later lines still run after a
+/// failure so that every error is reported at once.
+fn run_lines(script: &str) -> (usize, Vec<LineError>) {
+    let mut executed = 0;
+    let mut errors = Vec::new();
+
+    for (index, raw) in script.lines().enumerate() {
+        let command = raw.trim();
+        if command.is_empty() || command.starts_with('#') {
+            continue;
+        }
+
+        executed += 1;
+        println!("{}", format!("[{}] {}", index + 1, command).dimmed());
+
+        let result = match command_name(command) {
+            name if UNBATCHABLE_COMMANDS.contains(&name.as_str()) => {
+                Err(anyhow!("'{}' cannot be used in a batch script", name))
+            },
+            _ => process_command(command),
+        };
+
+        if let Err(error) = result {
+            println!("{} {:#}", "ERROR:".red().bold(), error);
+            errors.push(LineError {
+                line: index + 1,
+                command: command.to_string(),
+                error,
+            });
+        }
+    }
+
+    (executed, errors)
+}
+
+/// The command word of a line, e.g. "layout" for both "-layout sqlite" and ":-layout"
+fn command_name(command: &str) -> String {
+    command.trim_start_matches(':')
+        .trim_start_matches('-')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect()
+}
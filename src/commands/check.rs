@@ -1,102 +1,165 @@
 use anyhow::{Result, Context};
 use colored::Colorize;
-use std::fs;
-use std::path::Path;
-use std::collections::HashMap;
-use regex::Regex;
-
-use crate::utils::filesystem;
-use crate::utils::parser;
-
-/// Check for changes since last review
-pub fn check_changes() -> Result<()> {
-    // Get VQL directory
-    let vql_dir = filesystem::find_vql_root()
-        .context("VQL directory not found. Run 'vql init' first.")?;
-    
-    // Track changes for all assets
-    let mut changed_assets = HashMap::new();
-    let mut unchanged_assets = HashMap::new();
-    
-    // Process each asset type file
-    for file_type in &["models", "controllers", "ui"] {
-        let vql_file_path = vql_dir.join(format!("{}.vql.ref", file_type));
-        
-        if !vql_file_path.exists() {
-            continue;
+
+use crate::models::asset_paths;
+use crate::models::check::{self, CheckFailure};
+use crate::models::filter::AssetFilter;
+use crate::models::json_storage::{find_vql_storage, JsonStorage};
+use crate::utils::git;
+
+/// Check reviews against the files they describe: -check [principles] [tag:name] [@context]
+///
+/// Lists every asset whose files are missing and every current review that is stale because its
+/// asset's files changed or its principle's guidance was revised. Ends with exit status 0 when
+/// everything is current, `check::EXIT_STALE` for stale reviews and `check::EXIT_MISSING` for missing files.
+pub fn run_check(args: &[&str]) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let (selected, filter) = select(&storage, args)?;
+
+    let project_root = asset_paths::project_root(&vql_dir);
+    let report = check::check_storage(&storage, &project_root, &filter, &selected)?;
+
+    println!("{} {} asset(s), {} review(s){}",
+        "Checked".bold(),
+        report.assets,
+        report.reviews,
+        if filter.is_empty() { String::new() } else { format!(" ({})", filter.describe()) });
+
+    if !report.missing.is_empty() {
+        println!("\n{}", "Missing files:".red().bold());
+        for (asset_name, paths) in &report.missing {
+            println!("  {:<12} {}", asset_name.blue().bold(), paths.join(", "));
         }
-        
-        let content = fs::read_to_string(&vql_file_path)
-            .context(format!("Failed to read {}.vql.ref", file_type))?;
-        
-        // Extract all asset entries
-        let asset_pattern = r"ASSET:(\+[^\s|]*)\s*\|(.*?)---";
-        let re = Regex::new(asset_pattern).context("Failed to compile asset regex pattern")?;
-        
-        for captures in re.captures_iter(&content) {
-            let asset_ref = captures.get(1).unwrap().as_str();
-            let asset_data = parser::parse_asset(&content, asset_ref)?;
-            
-            // Check if the asset has a file hash and path
-            if let (Some(file_hash), Some(file_path)) = (
-                asset_data.header.get("FILE_HASH"),
-                asset_data.header.get("FILE_PATH")
-            ) {
-                let path = Path::new(file_path);
-                
-                // Calculate current hash if file exists
-                if path.exists() {
-                    let current_hash = filesystem::get_file_hash(path)?;
-                    
-                    if current_hash != *file_hash {
-                        changed_assets.insert(asset_ref.to_string(), (asset_data, current_hash));
-                    } else {
-                        unchanged_assets.insert(asset_ref.to_string(), asset_data);
-                    }
-                } else {
-                    // File doesn't exist anymore
-                    changed_assets.insert(asset_ref.to_string(), (asset_data, "FILE_MISSING".to_string()));
-                }
-            } else {
-                // Missing hash or path - cannot determine changes
-                unchanged_assets.insert(asset_ref.to_string(), asset_data);
-            }
+    }
+
+    if !report.stale.is_empty() {
+        println!("\n{}", "Stale reviews:".yellow().bold());
+        for stale in &report.stale {
+            let reasons: Vec<String> = stale.reasons.iter().map(|r| r.to_string()).collect();
+            println!("  {:<12} {:<10} reviewed {}  {}",
+                stale.asset.blue().bold(),
+                stale.principle,
+                stale.review.last_modified,
+                reasons.join("; ").yellow());
         }
     }
-    
-    // Display results
-    println!("{}", "CHANGE DETECTION RESULTS".green().bold());
-    
-    if changed_assets.is_empty() {
-        println!("\n{} All assets are up to date with their last review.", "✓".green());
+
+    if report.waived > 0 || report.unhashed > 0 {
+        println!();
+    }
+    if report.waived > 0 {
+        println!("{} {} waived review(s) not checked", "NOTE:".cyan().bold(), report.waived);
+    }
+    if report.unhashed > 0 {
+        println!("{} {} review(s) were stored before file hashes were recorded; store them again to track changes to their files",
+            "NOTE:".cyan().bold(),
+            report.unhashed);
+    }
+
+    match report.exit_code() {
+        0 => {
+            println!("\n{} Every review is current and every asset's files exist", "SUCCESS:".green().bold());
+            Ok(())
+        },
+        code => {
+            if !report.stale.is_empty() {
+                println!("\nRe-review with {} and store the results to clear them.", ":[asset].rv([principle])".blue());
+            }
+            Err(CheckFailure {
+                code,
+                message: format!("{} asset(s) with missing files, {} stale review(s)", report.missing.len(), report.stale.len()),
+            }.into())
+        },
+    }
+}
+
+/// Rank current reviews by how much git says their assets changed since: -changed-since-review [principles] [tag:name] [@context]
+///
+/// Each review records the commit checked out when it was stored. Commits touching the asset's
+/// paths since then, and lines changed between that commit and the working tree, show which
+/// reviews are most likely out of date. Outside a git repository there is nothing to compare.
+pub fn changed_since_review(args: &[&str]) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let (selected, filter) = select(&storage, args)?;
+
+    let project_root = asset_paths::project_root(&vql_dir);
+    if !git::is_repository(&project_root) {
+        println!("{} {} is not in a git repository (or git is not installed), so there is no history to compare reviews with. {} compares file hashes instead.",
+            "INFO:".blue().bold(),
+            project_root.display(),
+            "vql -check".blue());
+        return Ok(());
+    }
+
+    let report = check::changes_since_reviews(&storage, &project_root, &filter, &selected);
+
+    if report.changed.is_empty() {
+        println!("{} No reviewed asset has changed since its review{}",
+            "INFO:".blue().bold(),
+            if filter.is_empty() { String::new() } else { format!(" ({})", filter.describe()) });
     } else {
-        println!("\n{} The following assets have changed since their last review:", "!".yellow().bold());
-        
-        for (asset_ref, (asset_data, current_hash)) in &changed_assets {
-            let last_update = asset_data.header.get("LAST_UPDATE")
-                .cloned()
-                .unwrap_or_else(|| "unknown".to_string());
-            
-            let status = if current_hash == "FILE_MISSING" {
-                "MISSING".red().bold()
-            } else {
-                "CHANGED".yellow().bold()
-            };
-            
-            println!("  {} {} (Last review: {}, Status: {})", 
-                "●".yellow(),
-                asset_ref.blue().bold(),
-                last_update,
-                status);
+        println!("{} Reviews most likely out of date{}:",
+            "INFO:".blue().bold(),
+            if filter.is_empty() { String::new() } else { format!(" ({})", filter.describe()) });
+        println!("  {:<12} {:>7} {:>12}  {:<8}  {:<20}  Principles", "Asset", "Commits", "Lines", "Commit", "Reviewed");
+        for entry in &report.changed {
+            let lines = format!("+{} -{}", entry.changes.added, entry.changes.removed);
+            println!("  {:<12} {:>7} {:>12}  {:<8}  {:<20}  {}",
+                entry.asset.blue().bold(),
+                entry.changes.commits.to_string().yellow(),
+                lines.yellow(),
+                git::short(entry.commit),
+                entry.reviewed,
+                entry.principles.join(", "));
         }
-        
-        println!("\n{} To review a changed asset:", "ACTION:".cyan().bold());
-        println!("  {}", "vql prepare <asset-ref> --aspect=<arch|sec|perf|all>".cyan());
     }
-    
-    println!("\n{} Total assets: {}", "SUMMARY:".yellow().bold(), changed_assets.len() + unchanged_assets.len());
-    println!("  Changed: {}", changed_assets.len());
-    println!("  Unchanged: {}", unchanged_assets.len());
-    
+
+    if !report.unavailable.is_empty() {
+        println!();
+        for (asset_name, reason) in &report.unavailable {
+            println!("{} Could not compare reviews of {}: {}", "NOTE:".cyan().bold(), asset_name.blue().bold(), reason);
+        }
+    }
+
+    if report.unchanged > 0 || report.untracked > 0 || report.waived > 0 {
+        println!();
+    }
+    if report.unchanged > 0 {
+        println!("{} {} review(s) of unchanged assets not listed", "NOTE:".cyan().bold(), report.unchanged);
+    }
+    if report.untracked > 0 {
+        println!("{} {} review(s) were stored without a commit; store them again to track their changes in git",
+            "NOTE:".cyan().bold(),
+            report.untracked);
+    }
+    if report.waived > 0 {
+        println!("{} {} waived review(s) not checked", "NOTE:".cyan().bold(), report.waived);
+    }
+
+    if !report.changed.is_empty() {
+        println!("\nRe-review with {} and store the results to bring them up to date.", ":[asset].rv([principle])".blue());
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Principles (resolved, all when none are given) and the asset filter from check arguments
+fn select(storage: &JsonStorage, args: &[&str]) -> Result<(Vec<String>, AssetFilter)> {
+    let (principles, filter) = AssetFilter::from_args(args)?;
+    filter.check(storage)?;
+
+    let mut selected = Vec::new();
+    for name in principles.iter().flat_map(|p| p.split(',')).map(str::trim).filter(|p| !p.is_empty()) {
+        let principle = storage.resolve_principle(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown principle: '{}'", name))?;
+        selected.push(principle.to_string());
+    }
+
+    Ok((selected, filter))
+}
@@ -0,0 +1,124 @@
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+
+use crate::models::json_storage::{find_vql_storage, JsonStorage};
+
+/// Record that an asset depends on others: -ar -dep uc um / :uc.dep(um)
+pub fn add_dependency(asset_name: &str, dependencies: &[&str]) -> Result<()> {
+    if dependencies.is_empty() {
+        return Err(anyhow!("No dependencies given. Usage: -ar -dep asset dependency [dependency...]"));
+    }
+
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let added = storage.add_asset_dependencies(asset_name, dependencies)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    if added.is_empty() {
+        println!("{} {} already depends on {}", "INFO:".blue().bold(), asset_name.blue().bold(), dependencies.join(", "));
+    } else {
+        println!("{} {} now depends on {}", "SUCCESS:".green().bold(), asset_name.blue().bold(), added.join(", "));
+    }
+
+    Ok(())
+}
+
+/// Remove dependencies of an asset: -ar -undep uc um / :uc.undep(um)
+pub fn remove_dependency(asset_name: &str, dependencies: &[&str]) -> Result<()> {
+    if dependencies.is_empty() {
+        return Err(anyhow!("No dependencies given. Usage: -ar -undep asset dependency [dependency...]"));
+    }
+
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let removed = storage.remove_asset_dependencies(asset_name, dependencies)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    if removed.is_empty() {
+        println!("{} {} does not depend on {}", "INFO:".blue().bold(), asset_name.blue().bold(), dependencies.join(", "));
+    } else {
+        println!("{} {} no longer depends on {}", "SUCCESS:".green().bold(), asset_name.blue().bold(), removed.join(", "));
+    }
+
+    Ok(())
+}
+
+/// Show what an asset depends on, directly and through other assets: -deps uc
+pub fn run_deps(args: &[&str]) -> Result<()> {
+    let [asset_name] = args else {
+        return Err(anyhow!("Usage: vql -deps asset"));
+    };
+
+    // Find VQL storage
+    let (_, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let asset = storage.asset_references.get(*asset_name)
+        .ok_or_else(|| anyhow!("Asset {} not found", asset_name))?;
+
+    if asset.depends_on.is_empty() {
+        println!("{} {} has no recorded dependencies", "INFO:".blue().bold(), asset_name.blue().bold());
+        return Ok(());
+    }
+
+    println!("{} {}:", "Dependencies of".bold(), asset_name.blue().bold());
+    print_tree(&storage, asset_name, &|name| storage.asset_references[name].depends_on.iter().map(String::as_str).collect(), 1, &mut vec![asset_name.to_string()]);
+
+    Ok(())
+}
+
+/// Show every asset affected when an asset changes, directly or through other assets: -rdeps um
+pub fn run_rdeps(args: &[&str]) -> Result<()> {
+    let [asset_name] = args else {
+        return Err(anyhow!("Usage: vql -rdeps asset"));
+    };
+
+    // Find VQL storage
+    let (_, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    if !storage.asset_references.contains_key(*asset_name) {
+        return Err(anyhow!("Asset {} not found", asset_name));
+    }
+
+    let affected = storage.downstream_assets(&[asset_name]).len() - 1;
+    if affected == 0 {
+        println!("{} No assets depend on {}", "INFO:".blue().bold(), asset_name.blue().bold());
+        return Ok(());
+    }
+
+    println!("{} {} ({} asset(s) affected):", "Dependents of".bold(), asset_name.blue().bold(), affected);
+    print_tree(&storage, asset_name, &|name| storage.asset_dependents(name), 1, &mut vec![asset_name.to_string()]);
+
+    println!("\nRe-review them with {}", format!(":-rv(-pr, {}, downstream)", asset_name).blue());
+
+    Ok(())
+}
+
+/// Print the assets reached from `asset_name` one level per indent, marking cycles
+fn print_tree<'a>(storage: &'a JsonStorage, asset_name: &str, next: &dyn Fn(&str) -> Vec<&'a str>, depth: usize, path: &mut Vec<String>) {
+    for name in next(asset_name) {
+        let indent = "  ".repeat(depth);
+        let description = storage.asset_references.get(name)
+            .map(|asset| format!("({}, {})", asset.entity, asset.paths().join(", ")))
+            .unwrap_or_default();
+
+        if path.iter().any(|p| p == name) {
+            println!("{}{} {}", indent, name.blue().bold(), "(cycle)".yellow());
+            continue;
+        }
+
+        println!("{}{} {}", indent, name.blue().bold(), description.dimmed());
+        path.push(name.to_string());
+        print_tree(storage, name, next, depth + 1, path);
+        path.pop();
+    }
+}
@@ -0,0 +1,192 @@
+use std::path::Path;
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+
+use crate::models::config::VqlConfig;
+use crate::models::discovery::{self, Proposal};
+use crate::models::json_storage::find_vql_storage;
+
+/// Find assets from the asset types' patterns: -discover [dir] [-apply [names...]] [-skip names...]
+///
+/// Without `-apply` only the plan is shown. With it the proposed assets (all of them, or just the
+/// named ones, less any skipped) and their new entities are registered in one save, so a single
+/// `vql -undo` takes the whole discovery back.
+pub fn run_discover(args: &[&str]) -> Result<()> {
+    if let Some(("-ignore", patterns)) = args.split_first().map(|(first, rest)| (*first, rest)) {
+        return set_ignore_patterns(patterns);
+    }
+
+    let usage = "Usage: vql -discover [dir] [-apply [names...]] [-skip names...]";
+    let mut within = None;
+    let mut apply = false;
+    let mut accepted = Vec::new();
+    let mut skipped = Vec::new();
+    let mut list = None;
+    for arg in args {
+        match *arg {
+            "-apply" => {
+                apply = true;
+                list = Some(&mut accepted);
+            },
+            "-skip" => list = Some(&mut skipped),
+            flag if flag.starts_with('-') => return Err(anyhow!("Unknown option {}. {}", flag, usage)),
+            name => match list.as_mut() {
+                Some(list) => list.push(name),
+                None if within.is_none() => within = Some(name),
+                None => return Err(anyhow!("Only one directory can be searched. {}", usage)),
+            },
+        }
+    }
+
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    if storage.asset_types.values().all(|t| t.patterns.is_empty()) {
+        println!("{} No asset type has discovery patterns. Add some with {}",
+            "INFO:".blue().bold(),
+            "vql -at -patterns c \"*Controller.{js,ts}\"".blue());
+        return Ok(());
+    }
+
+    let plan = discovery::plan(&storage, &vql_dir, within.map(Path::new))?;
+
+    for name in accepted.iter().chain(&skipped) {
+        if !plan.proposals.iter().any(|p| p.short_name == *name) {
+            return Err(anyhow!("{} is not in the discovery plan", name));
+        }
+    }
+    let selected: Vec<&Proposal> = plan.proposals.iter()
+        .filter(|p| accepted.is_empty() || accepted.contains(&p.short_name.as_str()))
+        .filter(|p| !skipped.contains(&p.short_name.as_str()))
+        .collect();
+
+    if plan.proposals.is_empty() {
+        println!("{} No new assets found in {} file(s) ({} matching already tracked)",
+            "INFO:".blue().bold(),
+            plan.scanned,
+            plan.tracked);
+        return Ok(());
+    }
+
+    if !apply {
+        print_plan(&plan.proposals);
+        println!("\n{} file(s) scanned, {} matching already tracked", plan.scanned, plan.tracked);
+        println!("Register them with {}, or pick with {} / {}",
+            "vql -discover -apply".blue(),
+            "-apply name...".blue(),
+            "-skip name...".blue());
+        return Ok(());
+    }
+
+    if selected.is_empty() {
+        println!("{} Every proposed asset was skipped", "INFO:".blue().bold());
+        return Ok(());
+    }
+
+    discovery::register(&mut storage, &selected)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    let new_entities = selected.iter()
+        .filter_map(|p| p.new_entity.as_ref().map(|_| &p.entity))
+        .collect::<std::collections::BTreeSet<_>>();
+    println!("{} Registered {} asset(s) and {} new entit{}",
+        "SUCCESS:".green().bold(),
+        selected.len(),
+        new_entities.len(),
+        if new_entities.len() == 1 { "y" } else { "ies" });
+    for proposal in &selected {
+        println!("  {:<12} {}", proposal.short_name.blue().bold(), proposal.path);
+    }
+    println!("{} Undo the whole discovery with {}", "NOTE:".cyan().bold(), "vql -undo".blue());
+
+    Ok(())
+}
+
+/// Print the proposed assets with the entities they would create
+fn print_plan(proposals: &[Proposal]) {
+    println!("{} {} asset(s) to register:", "INFO:".blue().bold(), proposals.len());
+    println!("  {:<12} {:<10} {:<5} Path", "Asset", "Entity", "Type");
+    for proposal in proposals {
+        let entity = match proposal.new_entity {
+            Some(_) => format!("{}+", proposal.entity).green(),
+            None => proposal.entity.normal(),
+        };
+        println!("  {:<12} {:<10} {:<5} {}  {}",
+            proposal.short_name.blue().bold(),
+            entity,
+            proposal.asset_type,
+            proposal.path,
+            format!("({})", proposal.pattern).dimmed());
+    }
+
+    let mut new_entities: Vec<(&str, &str)> = proposals.iter()
+        .filter_map(|p| p.new_entity.as_deref().map(|description| (p.entity.as_str(), description)))
+        .collect();
+    new_entities.sort();
+    new_entities.dedup();
+    if !new_entities.is_empty() {
+        println!("\nNew entities (+):");
+        for (entity, description) in new_entities {
+            println!("  {:<10} {}", entity.green(), description);
+        }
+    }
+}
+
+/// Set the patterns discovery skips: -discover -ignore pattern... (-clear for none)
+fn set_ignore_patterns(patterns: &[&str]) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    if patterns.is_empty() {
+        let built_in = VqlConfig::default().ignore_patterns;
+        println!("{} Discovery skips hidden files, the VQL directory and {}", "INFO:".blue().bold(), built_in.join(", "));
+        if !storage.discovery_ignore.is_empty() {
+            println!("  Project patterns: {}", storage.discovery_ignore.join(", "));
+        }
+        return Ok(());
+    }
+
+    let patterns = if patterns == ["-clear"] { &[][..] } else { patterns };
+    storage.set_discovery_ignore(patterns)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    if patterns.is_empty() {
+        println!("{} Cleared the project's discovery ignore patterns", "SUCCESS:".green().bold());
+    } else {
+        println!("{} Discovery now also skips {}", "SUCCESS:".green().bold(), patterns.join(", "));
+    }
+
+    Ok(())
+}
+
+/// Set the patterns an asset type is discovered by: -at -patterns c "*Controller.{js,ts}"... (-clear for none)
+pub fn set_asset_type_patterns(asset_type: &str, patterns: &[&str]) -> Result<()> {
+    if patterns.is_empty() {
+        return Err(anyhow!("No patterns given. Usage: -at -patterns type pattern... (or -clear)"));
+    }
+    let patterns = if patterns == ["-clear"] { &[][..] } else { patterns };
+
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    storage.set_asset_type_patterns(asset_type, patterns)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    if patterns.is_empty() {
+        println!("{} Asset type {} is no longer discovered", "SUCCESS:".green().bold(), asset_type.blue().bold());
+    } else {
+        println!("{} Asset type {} is discovered by {}", "SUCCESS:".green().bold(), asset_type.blue().bold(), patterns.join(", "));
+        println!("{} Preview what it finds with {}", "NOTE:".cyan().bold(), "vql -discover".blue());
+    }
+
+    Ok(())
+}
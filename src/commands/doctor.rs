@@ -0,0 +1,98 @@
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+use serde_json::Value;
+
+use crate::models::asset_paths;
+use crate::models::doctor::{self, Problem, Severity};
+use crate::models::json_storage::find_vql_storage;
+
+/// Check VQL storage for consistency problems: -doctor [--fix]
+///
+/// Fails when errors remain, so the command can gate CI.
+pub fn run_doctor(args: &[&str]) -> Result<()> {
+    let fix = args.iter().any(|arg| matches!(*arg, "--fix" | "-fix"));
+    if let Some(unknown) = args.iter().find(|arg| !matches!(**arg, "--fix" | "-fix")) {
+        return Err(anyhow!("Unknown doctor option: {}. Usage: vql -doctor [--fix]", unknown));
+    }
+
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    // The stored document can hold fields the current schema silently ignores
+    let raw: Value = match storage.backend() {
+        Some(backend) => serde_json::from_str(&backend.load()?)
+            .context("Failed to parse VQL storage")?,
+        None => serde_json::to_value(&storage)?,
+    };
+
+    let project_root = asset_paths::project_root(&vql_dir);
+
+    let mut problems = doctor::diagnose(&storage, &raw, &project_root);
+
+    if fix {
+        let repairs: Vec<_> = problems.iter().filter_map(|p| p.repair.clone()).collect();
+        if repairs.is_empty() {
+            println!("{} Nothing to fix automatically", "INFO:".blue().bold());
+        } else {
+            let mut fixed = Vec::new();
+            for repair in &repairs {
+                match doctor::apply_repair(&mut storage, repair) {
+                    Ok(description) => fixed.push(description),
+                    Err(e) => println!("{} Could not repair: {}", "NOTE:".cyan().bold(), e),
+                }
+            }
+
+            storage.record_operation(format!("doctor --fix ({} repairs)", fixed.len()));
+            storage.save(&vql_dir)?;
+
+            println!("{} Fixed {} problem(s):", "SUCCESS:".green().bold(), fixed.len());
+            for description in &fixed {
+                println!("  - {}", description);
+            }
+            println!();
+
+            // Whatever is left needs a person
+            let repaired = serde_json::to_value(&storage)?;
+            problems = doctor::diagnose(&storage, &repaired, &project_root);
+        }
+    }
+
+    print_problems(&problems, fix);
+
+    let errors = problems.iter().filter(|p| p.severity == Severity::Error).count();
+    if errors > 0 {
+        return Err(anyhow!("VQL storage has {} error(s)", errors));
+    }
+
+    Ok(())
+}
+
+/// Print problems grouped by severity, with a summary line
+fn print_problems(problems: &[Problem], fixed: bool) {
+    if problems.is_empty() {
+        println!("{} No problems found in VQL storage", "SUCCESS:".green().bold());
+        return;
+    }
+
+    println!("{}", "VQL storage problems:".bold());
+    for problem in problems {
+        let severity = match problem.severity {
+            Severity::Error => format!("{:<7}", problem.severity).red().bold(),
+            Severity::Warning => format!("{:<7}", problem.severity).yellow().bold(),
+            Severity::Info => format!("{:<7}", problem.severity).blue(),
+        };
+        let fixable = if problem.repair.is_some() { " [fixable]".green().to_string() } else { String::new() };
+
+        println!("  {} {}: {}{}", severity, problem.subject.bold(), problem.message, fixable);
+    }
+
+    let count = |severity: Severity| problems.iter().filter(|p| p.severity == severity).count();
+    let fixable = problems.iter().filter(|p| p.repair.is_some()).count();
+
+    println!();
+    println!("{} error(s), {} warning(s), {} info", count(Severity::Error), count(Severity::Warning), count(Severity::Info));
+    if fixable > 0 && !fixed {
+        println!("{} {} problem(s) can be repaired with vql -doctor --fix", "NOTE:".cyan().bold(), fixable);
+    }
+}
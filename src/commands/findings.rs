@@ -0,0 +1,135 @@
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+
+use crate::models::findings::{Finding, Severity};
+use crate::models::json_storage::find_vql_storage;
+
+/// Show the findings of an asset's current reviews: uc?findings, uc?findings(a,s), uc?findings(all)
+///
+/// Only open findings are shown unless `all` is among the arguments.
+pub fn show_findings(asset_name: &str, args: Option<&str>) -> Result<()> {
+    // Find VQL storage
+    let (_, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let asset = storage.asset_references.get(asset_name)
+        .ok_or_else(|| anyhow!("Asset {} not found", asset_name))?;
+
+    let mut include_resolved = false;
+    let mut principles = Vec::new();
+    for arg in args.unwrap_or_default().split(',').map(str::trim).filter(|a| !a.is_empty()) {
+        if arg == "all" {
+            include_resolved = true;
+            continue;
+        }
+        let principle = storage.resolve_principle(arg)
+            .ok_or_else(|| anyhow!("Unknown principle: '{}'", arg))?;
+        principles.push(principle);
+    }
+
+    let findings: Vec<(&str, &Finding)> = asset.findings().into_iter()
+        .filter(|(principle, _)| principles.is_empty() || principles.contains(principle))
+        .collect();
+    let open = findings.iter().filter(|(_, f)| f.is_open()).count();
+    let resolved = findings.len() - open;
+
+    let shown: Vec<_> = findings.into_iter().filter(|(_, f)| include_resolved || f.is_open()).collect();
+    if shown.is_empty() {
+        println!("{} No {}findings for asset {}{}", "INFO:".blue().bold(),
+            if include_resolved { "" } else { "open " },
+            asset_name.blue().bold(),
+            if resolved > 0 && !include_resolved { format!(" ({} resolved - {}?findings(all) shows them)", resolved, asset_name) } else { String::new() });
+        return Ok(());
+    }
+
+    println!("{} Findings for asset {} ({} open, {} resolved):", "INFO:".blue().bold(), asset_name.blue().bold(), open, resolved);
+
+    let mut current_principle = "";
+    for (principle, finding) in shown {
+        if principle != current_principle {
+            let long_name = storage.principles.get(principle).map(|p| p.long_name.as_str()).unwrap_or_default();
+            println!("\n  {} Principle ({}):", principle.blue().bold(), long_name);
+            current_principle = principle;
+        }
+        print_finding(finding, "    ");
+    }
+
+    if open > 0 {
+        println!("\nClose fixed findings with {}", format!(":{}.resolve(id[, \"how it was fixed\"])", asset_name).blue());
+    }
+
+    Ok(())
+}
+
+/// Print one finding with its location, area, description and suggested fix
+pub fn print_finding(finding: &Finding, indent: &str) {
+    let status = if finding.is_open() {
+        "open".yellow().to_string()
+    } else {
+        format!("resolved {}", finding.resolved.as_deref().unwrap_or_default()).green().to_string()
+    };
+
+    let mut header = vec![format!("#{}", finding.id), severity_display(finding.severity).to_string()];
+    header.extend(finding.location());
+    header.extend(finding.area.as_ref().map(|a| format!("[{}]", a)));
+    header.push(status);
+
+    println!("{}{}", indent, header.join(" "));
+    println!("{}   {}", indent, finding.description);
+    if let Some(fix) = &finding.fix {
+        println!("{}   Fix: {}", indent, fix.dimmed());
+    }
+    if let Some(resolution) = &finding.resolution {
+        println!("{}   Resolution: {}", indent, resolution.dimmed());
+    }
+}
+
+/// Mark findings fixed: -resolve uc 3 [4...] [how it was fixed] / :uc.resolve(3[, 4...][, "how it was fixed"])
+pub fn resolve_findings(asset_name: &str, args: &[&str]) -> Result<()> {
+    // Leading numbers (optionally written #3) are finding ids, anything after them is the note
+    let ids: Vec<u32> = args.iter()
+        .map_while(|arg| arg.trim_start_matches('#').parse::<u32>().ok())
+        .collect();
+    if ids.is_empty() {
+        return Err(anyhow!("No finding ids given. Usage: -resolve asset id [id...] [how it was fixed]"));
+    }
+    let note = args[ids.len()..].join(" ");
+    let note = note.trim().trim_matches('"').trim();
+
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let resolved = storage.resolve_findings(asset_name, &ids, Some(note).filter(|n| !n.is_empty()))?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    if resolved.is_empty() {
+        println!("{} The findings were already resolved", "INFO:".blue().bold());
+        return Ok(());
+    }
+
+    for (id, principle) in &resolved {
+        println!("{} Resolved finding #{} of asset {} ({} principle)", "SUCCESS:".green().bold(), id, asset_name.blue().bold(), principle);
+    }
+
+    let open = storage.asset_references[asset_name].findings().iter().filter(|(_, f)| f.is_open()).count();
+    if open > 0 {
+        println!("{} {} finding(s) still open - see {}", "NOTE:".cyan().bold(), open, format!("{}?findings", asset_name).blue());
+    }
+
+    Ok(())
+}
+
+/// A severity coloured by how serious it is
+pub fn severity_display(severity: Severity) -> colored::ColoredString {
+    let name = severity.to_string().to_uppercase();
+    match severity {
+        Severity::Critical => name.red().bold(),
+        Severity::High => name.red(),
+        Severity::Medium => name.yellow(),
+        Severity::Low => name.normal(),
+        Severity::Info => name.dimmed(),
+    }
+}
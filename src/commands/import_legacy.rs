@@ -0,0 +1,312 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+use regex::Regex;
+
+use crate::models::json_storage::{JsonStorage, Review, find_vql_storage};
+use crate::utils::filesystem::{get_asset_type_from_ref, get_type_name_from_short_name};
+use crate::utils::parser;
+
+/// Legacy analysis sections and the principles they map to
+const LEGACY_ASPECTS: [(&str, &str, &str, &str); 3] = [
+    ("ARCH", "arch", "a", "Architecture"),
+    ("SEC", "sec", "s", "Security"),
+    ("PERF", "perf", "p", "Performance"),
+];
+
+/// Placeholder analysis written by the legacy `create_asset_entry`
+const PLACEHOLDER_ANALYSIS: &str = "Awaiting initial review.";
+
+/// Counts and problems collected while importing
+#[derive(Default)]
+struct ImportReport {
+    files: usize,
+    entities: Vec<String>,
+    asset_types: Vec<String>,
+    principles: Vec<String>,
+    assets: Vec<String>,
+    reviews: usize,
+    unmapped: Vec<String>,
+}
+
+/// Import legacy .vql.ref caches into JSON storage
+///
+/// Looks in `path` if given, otherwise in the VQL directory and a sibling lowercase `vql` directory.
+pub fn import_legacy(path: Option<&str>) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let search_dirs: Vec<PathBuf> = match path {
+        Some(p) => vec![PathBuf::from(p.trim_matches('"'))],
+        None => {
+            let mut dirs = vec![vql_dir.clone()];
+            if let Some(parent) = vql_dir.parent() {
+                dirs.push(parent.join("vql"));
+            }
+            dirs
+        }
+    };
+
+    let ref_files = find_ref_files(&search_dirs)?;
+    if ref_files.is_empty() {
+        return Err(anyhow!("No .vql.ref files found in {}",
+            search_dirs.iter().map(|d| d.display().to_string()).collect::<Vec<_>>().join(", ")));
+    }
+
+    let mut report = ImportReport::default();
+
+    for file in &ref_files {
+        import_ref_file(file, &mut storage, &mut report)?;
+        report.files += 1;
+    }
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    print_report(&report);
+
+    Ok(())
+}
+
+/// Collect every *.vql.ref file in the given directories, de-duplicating case-insensitive aliases
+fn find_ref_files(dirs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    for dir in dirs {
+        if !dir.is_dir() {
+            continue;
+        }
+
+        let entries = fs::read_dir(dir)
+            .context(format!("Failed to read directory {}", dir.display()))?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_ref = path.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.ends_with(".vql.ref"))
+                .unwrap_or(false);
+
+            if !is_ref || !path.is_file() {
+                continue;
+            }
+
+            let canonical = fs::canonicalize(&path).unwrap_or(path);
+            if !files.contains(&canonical) {
+                files.push(canonical);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Import every asset entry of one .vql.ref file
+fn import_ref_file(file: &Path, storage: &mut JsonStorage, report: &mut ImportReport) -> Result<()> {
+    let content = fs::read_to_string(file)
+        .context(format!("Failed to read {}", file.display()))?;
+    let file_name = file.file_name().and_then(|n| n.to_str()).unwrap_or("?");
+
+    let asset_re = Regex::new(r"ASSET:(\+[^\s|]*)\s*\|").unwrap();
+
+    for captures in asset_re.captures_iter(&content) {
+        let asset_ref = captures.get(1).unwrap().as_str();
+
+        let asset_data = match parser::parse_asset(&content, asset_ref) {
+            Ok(data) => data,
+            Err(e) => {
+                report.unmapped.push(format!("{}: {} could not be parsed ({})", file_name, asset_ref, e));
+                continue;
+            }
+        };
+
+        let short_name = asset_ref.trim_start_matches('+');
+
+        // Legacy refs are the entity name followed by the asset type character (e.g. +userm)
+        let type_char = match get_asset_type_from_ref(asset_ref) {
+            Ok(t) => t.trim_start_matches('^').to_string(),
+            Err(_) => {
+                report.unmapped.push(format!("{}: {} has no asset type character", file_name, asset_ref));
+                continue;
+            }
+        };
+        let entity_name = &short_name[..short_name.len() - type_char.len()];
+        if entity_name.is_empty() {
+            report.unmapped.push(format!("{}: {} has no entity name", file_name, asset_ref));
+            continue;
+        }
+
+        let file_path = match asset_data.header.get("FILE_PATH") {
+            Some(p) if !p.is_empty() => p.clone(),
+            _ => {
+                report.unmapped.push(format!("{}: {} has no FILE_PATH - register it with -ar -add", file_name, asset_ref));
+                continue;
+            }
+        };
+
+        if storage.asset_references.contains_key(short_name) {
+            report.unmapped.push(format!("{}: {} is already registered - skipped", file_name, short_name));
+            continue;
+        }
+
+        if !ensure_asset_type(storage, &type_char, report) {
+            report.unmapped.push(format!("{}: {} - short name '{}' is taken by another item, asset type not created", file_name, asset_ref, type_char));
+            continue;
+        }
+        if !ensure_entity(storage, entity_name, report) {
+            report.unmapped.push(format!("{}: {} - short name '{}' is taken by another item, entity not created", file_name, asset_ref, entity_name));
+            continue;
+        }
+
+        if let Err(e) = storage.add_asset_reference(short_name, entity_name, &type_char, &file_path) {
+            report.unmapped.push(format!("{}: {} could not be registered ({})", file_name, asset_ref, e));
+            continue;
+        }
+        report.assets.push(short_name.to_string());
+
+        let last_update = asset_data.header.get("LAST_UPDATE").cloned();
+
+        for (header_key, analysis_key, principle, long_name) in LEGACY_ASPECTS {
+            let rating = asset_data.header.get(header_key)
+                .map(|r| r.to_uppercase())
+                .filter(|r| !r.is_empty() && r != "?");
+            let analysis = asset_data.analysis.get(analysis_key)
+                .filter(|a| !a.is_empty() && a.as_str() != PLACEHOLDER_ANALYSIS)
+                .cloned();
+
+            if rating.is_none() && analysis.is_none() {
+                continue;
+            }
+
+            if let Some(r) = &rating {
+                if !["H", "M", "L"].contains(&r.as_str()) {
+                    report.unmapped.push(format!("{}: {} has unrecognised {} rating '{}'", file_name, short_name, header_key, r));
+                    continue;
+                }
+            }
+
+            if !ensure_principle(storage, principle, long_name, report) {
+                report.unmapped.push(format!("{}: {} {} review - short name '{}' is taken by another item", file_name, short_name, header_key, principle));
+                continue;
+            }
+
+            let review = Review {
+                rating,
+                analysis,
+                last_modified: last_update.clone()
+                    .unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+                principle_revision: None,
+                author: None,
+                source: None,
+                model: None,
+                confidence: None,
+                findings: Vec::new(),
+                content_hash: None,
+                commit: None,
+            };
+
+            match storage.insert_asset_review(short_name, principle, review) {
+                Ok(()) => report.reviews += 1,
+                Err(e) => report.unmapped.push(format!("{}: {} {} review could not be stored ({})", file_name, short_name, header_key, e)),
+            }
+        }
+
+        if let Some(asset) = storage.asset_references.get_mut(short_name) {
+            asset.exemplar = asset_data.header.get("EXEMPLAR")
+                .map(|e| e.eq_ignore_ascii_case("T"))
+                .unwrap_or(false);
+            if let Some(last_update) = last_update {
+                asset.last_modified = last_update;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Make sure an asset type exists, creating it from the legacy type character if needed
+fn ensure_asset_type(storage: &mut JsonStorage, short_name: &str, report: &mut ImportReport) -> bool {
+    if storage.asset_types.contains_key(short_name) {
+        return true;
+    }
+
+    let description = capitalize(&get_type_name_from_short_name(short_name));
+    if storage.add_asset_type(short_name, &description).is_err() {
+        return false;
+    }
+
+    report.asset_types.push(short_name.to_string());
+    true
+}
+
+/// Make sure an entity exists, creating it from the legacy entity name if needed
+fn ensure_entity(storage: &mut JsonStorage, short_name: &str, report: &mut ImportReport) -> bool {
+    if storage.entities.contains_key(short_name) {
+        return true;
+    }
+
+    if storage.add_entity(short_name, &capitalize(short_name)).is_err() {
+        return false;
+    }
+
+    report.entities.push(short_name.to_string());
+    true
+}
+
+/// Make sure a principle exists, creating the legacy default if needed
+fn ensure_principle(storage: &mut JsonStorage, short_name: &str, long_name: &str, report: &mut ImportReport) -> bool {
+    if storage.principles.contains_key(short_name) {
+        return true;
+    }
+
+    if storage.add_principle(short_name, long_name, None).is_err() {
+        return false;
+    }
+
+    report.principles.push(short_name.to_string());
+    true
+}
+
+/// Uppercase the first character of a name
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Print the reconciliation report
+fn print_report(report: &ImportReport) {
+    println!("{} Imported {} legacy .vql.ref file(s)",
+        "SUCCESS:".green().bold(),
+        report.files);
+
+    let created = |label: &str, names: &[String]| {
+        if names.is_empty() {
+            println!("  {}: none", label);
+        } else {
+            println!("  {}: {} ({})", label, names.len(), names.join(", "));
+        }
+    };
+
+    created("Entities created", &report.entities);
+    created("Asset types created", &report.asset_types);
+    created("Principles created", &report.principles);
+    created("Assets imported", &report.assets);
+    println!("  Reviews imported: {}", report.reviews);
+
+    if report.unmapped.is_empty() {
+        println!("\n{} Everything was mapped", "✓".green());
+    } else {
+        println!("\n{} {} item(s) could not be mapped:",
+            "RECONCILE:".yellow().bold(),
+            report.unmapped.len());
+        for item in &report.unmapped {
+            println!("  - {}", item);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+
+use crate::models::journal::{Journal, JournalEntry};
+use crate::models::json_storage::find_vql_storage;
+
+/// Number of operations listed by default
+const DEFAULT_LIST_COUNT: usize = 10;
+
+/// Undo the last N operations (default 1)
+pub fn undo(count: Option<&str>) -> Result<()> {
+    step(parse_count(count, 1)?, true)
+}
+
+/// Redo the last N undone operations (default 1)
+pub fn redo(count: Option<&str>) -> Result<()> {
+    step(parse_count(count, 1)?, false)
+}
+
+/// Undo or redo up to `count` operations
+fn step(count: usize, undo: bool) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let journal_dir = storage.backend()
+        .and_then(|backend| backend.directory().map(|dir| dir.to_path_buf()))
+        .ok_or_else(|| anyhow!("Undo and redo are only available for storage kept in a VQL directory"))?;
+
+    let mut journal = Journal::load(&journal_dir)?;
+    let mut document = serde_json::to_value(&storage)
+        .context("Failed to serialize VQL storage to JSON")?;
+
+    let mut applied = Vec::new();
+    let mut notes = Vec::new();
+    for _ in 0..count {
+        let entry = if undo { journal.undo.pop() } else { journal.redo.pop() };
+        let Some(entry) = entry else { break };
+
+        notes.extend(entry.apply(&mut document, undo));
+        applied.push(format!("#{} {}", entry.id, entry.operation));
+
+        if undo {
+            journal.redo.push(entry);
+        } else {
+            journal.undo.push(entry);
+        }
+    }
+
+    let verb = if undo { "undo" } else { "redo" };
+    if applied.is_empty() {
+        println!("{} Nothing to {}", "INFO:".blue().bold(), verb);
+        return Ok(());
+    }
+
+    storage.restore(document)?;
+
+    // Save changes, then the journal, while still holding the storage lock
+    storage.save(&vql_dir)?;
+    journal.save(&journal_dir)?;
+
+    println!("{} {} {} operation(s):",
+        "SUCCESS:".green().bold(),
+        if undo { "Undid" } else { "Redid" },
+        applied.len());
+    for operation in &applied {
+        println!("  {}", operation);
+    }
+    for note in &notes {
+        println!("{} {}", "NOTE:".cyan().bold(), note);
+    }
+
+    Ok(())
+}
+
+/// List recent operations, newest first, with those that can be redone
+pub fn list_journal(count: Option<&str>) -> Result<()> {
+    let count = parse_count(count, DEFAULT_LIST_COUNT)?;
+
+    // Find VQL storage
+    let (_, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let journal_dir = storage.backend()
+        .and_then(|backend| backend.directory().map(|dir| dir.to_path_buf()))
+        .ok_or_else(|| anyhow!("The journal is only kept for storage in a VQL directory"))?;
+
+    let journal = Journal::load(&journal_dir)?;
+
+    if journal.undo.is_empty() && journal.redo.is_empty() {
+        println!("{} No operations recorded yet", "INFO:".blue().bold());
+        return Ok(());
+    }
+
+    println!("{}", "Recent operations (newest first):".bold());
+
+    // The redo stack holds the newest undone operation at the bottom, so order both stacks by id
+    let mut entries: Vec<(&JournalEntry, bool)> = journal.undo.iter().map(|entry| (entry, false))
+        .chain(journal.redo.iter().map(|entry| (entry, true)))
+        .collect();
+    entries.sort_by_key(|(entry, _)| std::cmp::Reverse(entry.id));
+
+    for (entry, undone) in entries.into_iter().take(count) {
+        if undone {
+            println!("  {} {}  {}  {}",
+                format!("#{}", entry.id).dimmed(),
+                entry.timestamp,
+                entry.operation.dimmed(),
+                "(undone - vql -redo)".yellow());
+        } else {
+            println!("  {} {}  {}  ({} change{})",
+                format!("#{}", entry.id).blue(),
+                entry.timestamp,
+                entry.operation,
+                entry.changes.len(),
+                if entry.changes.len() == 1 { "" } else { "s" });
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse an optional positive count argument
+fn parse_count(count: Option<&str>, default: usize) -> Result<usize> {
+    match count.map(str::trim).filter(|c| !c.is_empty()) {
+        None => Ok(default),
+        Some(value) => match value.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(anyhow!("Invalid count: {}. Must be a positive number", value)),
+        },
+    }
+}
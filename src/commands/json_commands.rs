@@ -1,13 +1,19 @@
 use anyhow::{Result, Context, anyhow};
 use colored::Colorize;
 use std::env;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::fs;
 use regex::Regex;
 use std::io::{BufRead, BufReader};
 use std::collections::HashMap;
 
-use crate::models::json_storage::{JsonStorage, find_vql_storage};
+use crate::models::asset_paths;
+use crate::models::attribution::{self, Attribution, ReviewSource, ATTRIBUTION_FIELDS};
+use crate::models::json_storage::{AssetReference, JsonStorage, Review, find_vql_storage};
+use crate::models::backend;
+use crate::models::rating::{self, RatingScale};
+use crate::models::filter::{AssetFilter, ReviewFilter};
+use crate::models::findings::{Finding, parse_findings, split_json_prefix};
 
 /// Process a command (with or without colon prefix) or asset.method format
 /// Process a command in either LLM format or CLI format
@@ -52,29 +58,47 @@ fn process_llm_command(command: &str) -> Result<()> {
         let name = captures.get(1).unwrap().as_str().trim();
         return delete_item(name);
     }
-    
+
+    // Format: :undo(), :undo(N), :redo(), :redo(N), :journal() - journal operations
+    let journal_re = Regex::new(r"^(undo|redo|journal)\(\s*([0-9]*)\s*\)$").unwrap();
+    if let Some(captures) = journal_re.captures(command) {
+        let count = captures.get(2).map(|m| m.as_str()).filter(|c| !c.is_empty());
+        return match captures.get(1).unwrap().as_str() {
+            "undo" => crate::commands::journal::undo(count),
+            "redo" => crate::commands::journal::redo(count),
+            _ => crate::commands::journal::list_journal(count),
+        };
+    }
+
     // Format: :ls() or :ls - list all types
     if command == "ls()" || command == "ls" {
         println!("\nVQL Summary:");
         show_principles()?;
         println!();
-        list_entities()?;
+        list_entities(None)?;
         println!();
         list_asset_types()?;
         println!();
-        list_asset_references()?;
+        list_asset_references(&AssetFilter::default())?;
         return Ok(());
     }
     
     // Check for type-specific list commands
     match command {
         "pr()" | "pr" => return show_principles(),
-        "er()" | "er" => return list_entities(),
+        "er()" | "er" => return list_entities(None),
         "at()" | "at" => return list_asset_types(),
-        "ar()" | "ar" => return list_asset_references(),
+        "ar()" | "ar" => return list_asset_references(&AssetFilter::default()),
         _ => {}
     }
     
+    // Format: :ar(tag:critical) / :ar(@billing) - list some assets
+    let ar_tag_re = Regex::new(r"^ar\(((?:tag:|@)[^)]*)\)$").unwrap();
+    if let Some(captures) = ar_tag_re.captures(command) {
+        let (_, filter) = AssetFilter::from_list(captures.get(1).unwrap().as_str())?;
+        return list_asset_references(&filter);
+    }
+    
     // Format: :pr.add(short, long, "guidance")
     let pr_add_re = Regex::new(r#"^pr\.add\(([^,]+),\s*([^,]+)(?:,\s*"([^"]*)")?\)$"#).unwrap();
     if let Some(captures) = pr_add_re.captures(command) {
@@ -84,12 +108,60 @@ fn process_llm_command(command: &str) -> Result<()> {
         return add_principle(short_name, long_name, guidance);
     }
     
+    // Format: :pr.set(short, "guidance")
+    let pr_set_re = Regex::new(r#"^pr\.set\(([^,]+),\s*"([^"]*)"\)$"#).unwrap();
+    if let Some(captures) = pr_set_re.captures(command) {
+        let short_name = captures.get(1).unwrap().as_str().trim();
+        let guidance = captures.get(2).unwrap().as_str();
+        return set_principle_guidance(short_name, guidance);
+    }
+    
+    // Format: :pr.weight(short, weight)
+    let pr_weight_re = Regex::new(r"^pr\.weight\(([^,]+),\s*([^)]+)\)$").unwrap();
+    if let Some(captures) = pr_weight_re.captures(command) {
+        let short_name = captures.get(1).unwrap().as_str().trim();
+        let weight = captures.get(2).unwrap().as_str().trim();
+        return set_principle_weight(short_name, weight);
+    }
+    
+    // Format: :pr.scale(short, scale)
+    let pr_scale_re = Regex::new(r"^pr\.scale\(([^,]+),\s*([^)]+)\)$").unwrap();
+    if let Some(captures) = pr_scale_re.captures(command) {
+        let short_name = captures.get(1).unwrap().as_str().trim();
+        let scale = captures.get(2).unwrap().as_str().trim();
+        return crate::commands::scale::set_principle_scale(short_name, scale);
+    }
+    
+    // Format: :pr.alias(short, alias) and :pr.unalias(alias)
+    let pr_alias_re = Regex::new(r"^pr\.alias\(([^,]+),\s*([^)]+)\)$").unwrap();
+    if let Some(captures) = pr_alias_re.captures(command) {
+        let short_name = captures.get(1).unwrap().as_str().trim();
+        let aliases: Vec<&str> = captures.get(2).unwrap().as_str().split(',').map(|a| a.trim()).collect();
+        return add_principle_aliases(short_name, &aliases);
+    }
+    
+    let pr_unalias_re = Regex::new(r"^pr\.unalias\(([^)]+)\)$").unwrap();
+    if let Some(captures) = pr_unalias_re.captures(command) {
+        return remove_principle_alias(captures.get(1).unwrap().as_str().trim());
+    }
+    
     // Format: :er.add(short, long)
-    let er_add_re = Regex::new(r"^er\.add\(([^,]+),\s*([^)]+)\)$").unwrap();
+    let er_add_re = Regex::new(r"^er\.add\(([^,]+),\s*([^,)]+)(?:,\s*([^)]+))?\)$").unwrap();
     if let Some(captures) = er_add_re.captures(command) {
         let short_name = captures.get(1).unwrap().as_str().trim();
         let long_name = captures.get(2).unwrap().as_str().trim();
-        return add_entity(&[short_name, long_name]);
+        return match captures.get(3) {
+            Some(parent) => add_entity(&[short_name, long_name, parent.as_str().trim()]),
+            None => add_entity(&[short_name, long_name]),
+        };
+    }
+    
+    // Format: :er.parent(i, billing) - "none" moves the entity to the top level
+    let er_parent_re = Regex::new(r"^er\.parent\(([^,]+),\s*([^)]+)\)$").unwrap();
+    if let Some(captures) = er_parent_re.captures(command) {
+        let short_name = captures.get(1).unwrap().as_str().trim();
+        let parent = captures.get(2).unwrap().as_str().trim();
+        return set_entity_parent(short_name, parent);
     }
     
     // Format: :at.add(short, description)
@@ -100,14 +172,34 @@ fn process_llm_command(command: &str) -> Result<()> {
         return add_asset_type(&[short_name, description]);
     }
     
-    // Format: :ar.add(short, entity, type, "path")
-    let ar_add_re = Regex::new(r#"^ar\.add\(([^,]+),\s*([^,]+),\s*([^,]+),\s*"([^"]*)"\)$"#).unwrap();
+    // Format: :at.patterns(c, "*Controller.{js,ts}"[, "pattern"...])
+    let at_patterns_re = Regex::new(r#"^at\.patterns\(([^,]+),\s*("[^"]*"(?:\s*,\s*"[^"]*")*)\)$"#).unwrap();
+    if let Some(captures) = at_patterns_re.captures(command) {
+        let short_name = captures.get(1).unwrap().as_str().trim();
+        return crate::commands::discover::set_asset_type_patterns(short_name, &quoted_list(captures.get(2).unwrap().as_str()));
+    }
+    
+    // Format: :ar.add(short, entity, type, "path"[, "path"...])
+    let ar_add_re = Regex::new(r#"^ar\.add\(([^,]+),\s*([^,]+),\s*([^,]+),\s*("[^"]*"(?:\s*,\s*"[^"]*")*)\)$"#).unwrap();
     if let Some(captures) = ar_add_re.captures(command) {
         let short_name = captures.get(1).unwrap().as_str().trim();
         let entity = captures.get(2).unwrap().as_str().trim();
         let asset_type = captures.get(3).unwrap().as_str().trim();
-        let path = captures.get(4).unwrap().as_str();
-        return add_asset_reference(&[short_name, entity, asset_type, path]);
+        let mut args = vec![short_name, entity, asset_type];
+        args.extend(quoted_list(captures.get(4).unwrap().as_str()));
+        return add_asset_reference(&args);
+    }
+    
+    // Format: :uc.paths("src/user", "src/user/*.service.ts")
+    let asset_paths_re = Regex::new(r#"^([a-zA-Z0-9_]+)\.paths\(("[^"]*"(?:\s*,\s*"[^"]*")*)\)$"#).unwrap();
+    if let Some(captures) = asset_paths_re.captures(command) {
+        let asset_name = captures.get(1).unwrap().as_str();
+        return set_asset_paths(asset_name, &quoted_list(captures.get(2).unwrap().as_str()));
+    }
+    
+    // Check for review history queries like :uc?history(a) or :uc?at(2025-05-01, a)
+    if let Some(result) = try_review_history_query(command) {
+        return result;
     }
     
     // Check for asset question format like :uc ? (a) or :uc?(a) - with or without space before/after question mark
@@ -147,13 +239,103 @@ fn process_llm_command(command: &str) -> Result<()> {
         let asset_ref = captures.get(1).unwrap().as_str().trim();
         let entity = captures.get(2).unwrap().as_str().trim();
         let asset_type = captures.get(3).unwrap().as_str().trim();
-        let path_with_quotes = captures.get(4).unwrap().as_str().trim();
+        let mut args = vec![asset_ref, entity, asset_type];
         // Remove surrounding quotes if present
-        let path = path_with_quotes.trim_matches('"');
+        args.extend(captures.get(4).unwrap().as_str().split(',').map(|p| p.trim().trim_matches('"')));
         
-        return add_asset_reference(&[asset_ref, entity, asset_type, path]);
+        return add_asset_reference(&args);
     }
     
+    // Format: :uc.tag(critical, legacy) / :uc.untag(legacy)
+    let asset_tag_re = Regex::new(r"^([a-zA-Z0-9_]+)\.(tag|untag)\(([^)]*)\)$").unwrap();
+    if let Some(captures) = asset_tag_re.captures(command) {
+        let asset_name = captures.get(1).unwrap().as_str();
+        let tags: Vec<&str> = captures.get(3).unwrap().as_str()
+            .split(',')
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        if captures.get(2).unwrap().as_str() == "tag" {
+            return crate::commands::tags::tag_asset(asset_name, &tags);
+        } else {
+            return crate::commands::tags::untag_asset(asset_name, &tags);
+        }
+    }
+
+    // Format: :uc.dep(um, ux) / :uc.undep(um)
+    let asset_dep_re = Regex::new(r"^([a-zA-Z0-9_]+)\.(dep|undep)\(([^)]*)\)$").unwrap();
+    if let Some(captures) = asset_dep_re.captures(command) {
+        let asset_name = captures.get(1).unwrap().as_str();
+        let dependencies: Vec<&str> = captures.get(3).unwrap().as_str()
+            .split(',')
+            .map(|d| d.trim())
+            .filter(|d| !d.is_empty())
+            .collect();
+
+        if captures.get(2).unwrap().as_str() == "dep" {
+            return crate::commands::deps::add_dependency(asset_name, &dependencies);
+        } else {
+            return crate::commands::deps::remove_dependency(asset_name, &dependencies);
+        }
+    }
+
+    // Format: :uc.resolve(3, 4, "Extracted a request validator") - close findings fixed by a refactor
+    let asset_resolve_re = Regex::new(r"^([a-zA-Z0-9_]+)\.resolve\((.*)\)$").unwrap();
+    if let Some(captures) = asset_resolve_re.captures(command) {
+        let asset_name = captures.get(1).unwrap().as_str();
+        let parts: Vec<&str> = captures.get(2).unwrap().as_str()
+            .split(',')
+            .map(|a| a.trim())
+            .collect();
+        
+        // The note after the ids may hold commas and parentheses of its own
+        let ids = parts.iter().take_while(|p| p.trim_start_matches('#').parse::<u32>().is_ok()).count();
+        let note = parts[ids..].join(", ");
+        let mut args = parts[..ids].to_vec();
+        args.push(&note);
+        
+        return crate::commands::findings::resolve_findings(asset_name, &args);
+    }
+
+    // Format: :uc.waive(a, 2026-12-31, "Legacy adapter, replaced next quarter"[, author=dom]) / :uc.unwaive(a)
+    // The arguments run to the final ")" so the justification can hold parentheses of its own
+    let asset_waive_re = Regex::new(r"^([a-zA-Z0-9_]+)\.(waive|unwaive)\((.*)\)$").unwrap();
+    if let Some(captures) = asset_waive_re.captures(command) {
+        let asset_name = captures.get(1).unwrap().as_str();
+        let mut args: Vec<&str> = captures.get(3).unwrap().as_str()
+            .split(',')
+            .map(|a| a.trim())
+            .collect();
+        
+        if captures.get(2).unwrap().as_str() == "unwaive" {
+            return crate::commands::waivers::unwaive(asset_name, args[0]);
+        }
+        
+        // A trailing author=name names who accepted the risk; the justification may itself contain commas
+        let author = args.last().and_then(|a| a.strip_prefix("author=")).map(|a| a.trim().trim_matches('"'));
+        if author.is_some() {
+            args.pop();
+        }
+        let justification = args.get(2..).map(|rest| rest.join(", ")).unwrap_or_default();
+        let mut waive_args = args.iter().take(2).copied().collect::<Vec<_>>();
+        if let Some(author) = author {
+            waive_args.extend(["-author", author]);
+        }
+        waive_args.push(&justification);
+        
+        return crate::commands::waivers::waive(asset_name, &waive_args);
+    }
+
+    // Format: :uc.meta(owner, "payments-team") - an empty value removes the key
+    let asset_meta_re = Regex::new(r#"^([a-zA-Z0-9_]+)\.meta\(([^,]+),\s*"([^"]*)"\)$"#).unwrap();
+    if let Some(captures) = asset_meta_re.captures(command) {
+        let asset_name = captures.get(1).unwrap().as_str();
+        let key = captures.get(2).unwrap().as_str();
+        let value = captures.get(3).unwrap().as_str();
+        return crate::commands::tags::set_asset_metadata(asset_name, key, value);
+    }
+
     // Check if this is an asset method with specialized LLM syntax
     let asset_llm_method_re = Regex::new(r"^([a-zA-Z0-9_]+)\.([a-z]{2})\(([^)]*)\)$").unwrap();
     if let Some(captures) = asset_llm_method_re.captures(command) {
@@ -173,15 +355,31 @@ fn process_llm_command(command: &str) -> Result<()> {
     }
     
     // Check for global commands with special format
-    let global_llm_command_re = Regex::new(r"^-([a-z]{2})\(([^)]*)\)$").unwrap();
+    // Global review and refactor can be scoped to bounded contexts after the call: :-rv(-pr) @billing
+    let global_llm_command_re = Regex::new(r"^-([a-z]{2})\(([^)]*)\)((?:\s+@[a-zA-Z0-9_]+)*)$").unwrap();
     if let Some(captures) = global_llm_command_re.captures(command) {
         let method = captures.get(1).unwrap().as_str();
         let args = captures.get(2).unwrap().as_str();
+        let contexts: Vec<&str> = captures.get(3).unwrap().as_str().split_whitespace().collect();
+        
+        if !contexts.is_empty() && method != "rv" && method != "rf" {
+            return Err(anyhow!("Only :-rv and :-rf can be scoped to a context with {}", contexts.join(" ")));
+        }
+        let args = if contexts.is_empty() {
+            args.to_string()
+        } else {
+            format!("{}, {}", args, contexts.join(", "))
+        };
+        let args = args.as_str();
         
         // Handle global LLM commands
         match method {
             "rv" => return handle_global_review(args),
             "rf" => return handle_global_refactor(args),
+            "hc" => {
+                let tokens: Vec<&str> = args.split_whitespace().collect();
+                return compact_review_history(&tokens);
+            },
             "su" => {
                 // Handle VQL setup with project folder path
                 let project_path = args.trim().trim_matches('"'); // Remove quotes if present
@@ -210,9 +408,9 @@ fn process_llm_command(command: &str) -> Result<()> {
     // Check for simple principle commands
     match command {
         "-pr" => return show_principles(),
-        "-er" => return list_entities(),
+        "-er" => return list_entities(None),
         "-at" => return list_asset_types(),
-        "-ar" => return list_asset_references(),
+        "-ar" => return list_asset_references(&AssetFilter::default()),
         _ => {}
     }
     
@@ -232,6 +430,11 @@ fn process_cli_command(command: &str) -> Result<()> {
     // Note: Setup command has been moved to use -su flag format
     // The old setup command format is no longer supported
     
+    // Check for review history queries like "uc?history(a)" or "uc?at(2025-05-01, a)"
+    if let Some(result) = try_review_history_query(command) {
+        return result;
+    }
+    
     // Check if this is an asset question format (for retrieving reviews)
     // Support "asset ? (principle)" or "asset?(principle)" format with or without spaces
     let question_re = Regex::new(r"^([a-zA-Z0-9_]+)(\s+)?\?(\s*)?(\(([^)]*)\))?$").unwrap();
@@ -351,6 +554,47 @@ fn process_cli_flag_command(command: &str) -> Result<()> {
                         
                         return delete_principle(name);
                     },
+                    "set" => {
+                        // -pr -set a "New guidance"
+                        if parts.len() < 4 {
+                            return Err(anyhow!("Not enough arguments for principle set. Usage: -pr -set principle \"guidance\""));
+                        }
+                        
+                        let guidance = parts[3..].join(" ");
+                        set_principle_guidance(parts[2], guidance.trim_matches('"'))
+                    },
+                    "weight" => {
+                        // -pr -weight s 3
+                        if parts.len() < 4 {
+                            return Err(anyhow!("Not enough arguments for principle weight. Usage: -pr -weight principle weight"));
+                        }
+                        
+                        set_principle_weight(parts[2], parts[3])
+                    },
+                    "scale" => {
+                        // -pr -scale s pass_fail
+                        if parts.len() < 4 {
+                            return Err(anyhow!("Not enough arguments for principle scale. Usage: -pr -scale principle scale"));
+                        }
+                        
+                        crate::commands::scale::set_principle_scale(parts[2], parts[3])
+                    },
+                    "alias" => {
+                        // -pr -alias s sec security
+                        if parts.len() < 4 {
+                            return Err(anyhow!("Not enough arguments for principle alias. Usage: -pr -alias principle alias [alias...]"));
+                        }
+                        
+                        add_principle_aliases(parts[2], &parts[3..])
+                    },
+                    "unalias" => {
+                        // -pr -unalias sec
+                        if parts.len() < 3 {
+                            return Err(anyhow!("Not enough arguments for principle unalias. Usage: -pr -unalias alias"));
+                        }
+                        
+                        remove_principle_alias(parts[2])
+                    },
                     _ => return Err(anyhow!("Unknown principle subcommand: {}", parts[1])),
                 }
             } else {
@@ -361,6 +605,11 @@ fn process_cli_flag_command(command: &str) -> Result<()> {
         "er" => {
             // Entity commands
             if parts.len() > 1 {
+                // Show one bounded context: -er @billing
+                if let Some(context) = parts[1].strip_prefix('@') {
+                    return list_entities(Some(context));
+                }
+                
                 // Check if subcommand starts with dash
                 if !parts[1].starts_with('-') {
                     return Err(anyhow!("Invalid subcommand format. Subcommands must start with - (e.g., -add)"));
@@ -371,7 +620,7 @@ fn process_cli_flag_command(command: &str) -> Result<()> {
                 
                 match subcmd {
                     "add" => {
-                        // -er -add u User
+                        // -er -add u User [parent]
                         if parts.len() < 3 {
                             return Err(anyhow!("Not enough arguments for entity add"));
                         }
@@ -379,8 +628,19 @@ fn process_cli_flag_command(command: &str) -> Result<()> {
                         let short_name = parts[2];
                         let long_name = if parts.len() > 3 { parts[3] } else { short_name };
                         
+                        if let Some(parent) = parts.get(4) {
+                            return add_entity(&[short_name, long_name, parent]);
+                        }
                         return add_entity(&[short_name, long_name]);
                     },
+                    "parent" => {
+                        // -er -parent i billing ("none" moves it to the top level)
+                        if parts.len() < 4 {
+                            return Err(anyhow!("Not enough arguments for entity parent. Usage: -er -parent name parent|none"));
+                        }
+                        
+                        set_entity_parent(parts[2], parts[3])
+                    },
                     "rn" => {
                         // -er -rn u usr
                         if parts.len() < 4 {
@@ -406,7 +666,7 @@ fn process_cli_flag_command(command: &str) -> Result<()> {
                 }
             } else {
                 // Just er by itself
-                return list_entities();
+                list_entities(None)
             }
         },
         "at" => {
@@ -443,6 +703,14 @@ fn process_cli_flag_command(command: &str) -> Result<()> {
                         
                         return rename_asset_type(old_name, new_name);
                     },
+                    "patterns" => {
+                        // -at -patterns c "*Controller.{js,ts}" "controllers/*.js"
+                        if parts.len() < 3 {
+                            return Err(anyhow!("Not enough arguments for asset type patterns. Usage: -at -patterns type pattern... (or -clear)"));
+                        }
+                        
+                        crate::commands::discover::set_asset_type_patterns(parts[2], &parts[3..])
+                    },
                     "dl" => {
                         // -at -dl model
                         if parts.len() < 3 {
@@ -463,6 +731,12 @@ fn process_cli_flag_command(command: &str) -> Result<()> {
         "ar" => {
             // Asset reference commands
             if parts.len() > 1 {
+                // List only some assets: -ar tag:critical / -ar @billing
+                if AssetFilter::is_filter_arg(parts[1]) {
+                    let (_, filter) = AssetFilter::from_args(&parts[1..])?;
+                    return list_asset_references(&filter);
+                }
+                
                 // Check if subcommand starts with dash
                 if !parts[1].starts_with('-') {
                     return Err(anyhow!("Invalid subcommand format. Subcommands must start with - (e.g., -add)"));
@@ -473,17 +747,12 @@ fn process_cli_flag_command(command: &str) -> Result<()> {
                 
                 match subcmd {
                     "add" => {
-                        // Format: -ar -add short_name entity asset_type path (space-separated only)
+                        // Format: -ar -add short_name entity asset_type path [path...] (space-separated only)
                         if parts.len() >= 6 {
-                            // All parameters specified
-                            let short_name = parts[2];
-                            let entity = parts[3];
-                            let asset_type = parts[4];
-                            let path = parts[5];
-                            
-                            return add_asset_reference(&[short_name, entity, asset_type, path]);
+                            // All parameters specified; paths may be files, directories or glob patterns
+                            add_asset_reference(&parts[2..])
                         } else {
-                            return Err(anyhow!("Not enough arguments for asset reference add. Usage: -ar -add shortName entityType assetType path"));
+                            Err(anyhow!("Not enough arguments for asset reference add. Usage: -ar -add shortName entityType assetType path [path...]"))
                         }
                     },
                     "rn" => {
@@ -507,15 +776,56 @@ fn process_cli_flag_command(command: &str) -> Result<()> {
                         
                         return delete_asset_reference(name);
                     },
+                    "paths" => {
+                        // -ar -paths uc src/user "src/user/*.service.ts"
+                        if parts.len() < 4 {
+                            return Err(anyhow!("Not enough arguments for asset paths. Usage: -ar -paths asset path [path...]"));
+                        }
+                        
+                        set_asset_paths(parts[2], &parts[3..])
+                    },
+                    "dep" | "undep" => {
+                        // -ar -dep uc um / -ar -undep uc um
+                        if parts.len() < 4 {
+                            return Err(anyhow!("Not enough arguments for asset {}. Usage: -ar -{} asset dependency [dependency...]", subcmd, subcmd));
+                        }
+                        
+                        if subcmd == "dep" {
+                            crate::commands::deps::add_dependency(parts[2], &parts[3..])
+                        } else {
+                            crate::commands::deps::remove_dependency(parts[2], &parts[3..])
+                        }
+                    },
+                    "tag" | "untag" => {
+                        // -ar -tag uc critical legacy / -ar -untag uc legacy
+                        if parts.len() < 4 {
+                            return Err(anyhow!("Not enough arguments for asset {}. Usage: -ar -{} asset tag [tag...]", subcmd, subcmd));
+                        }
+                        
+                        if subcmd == "tag" {
+                            crate::commands::tags::tag_asset(parts[2], &parts[3..])
+                        } else {
+                            crate::commands::tags::untag_asset(parts[2], &parts[3..])
+                        }
+                    },
+                    "meta" => {
+                        // -ar -meta uc owner=payments-team (an empty value removes the key)
+                        let entry = parts.get(3).map(|_| parts[3..].join(" "));
+                        let Some((key, value)) = entry.as_deref().and_then(|e| e.split_once('=')) else {
+                            return Err(anyhow!("Usage: -ar -meta asset key=value (key= removes the key)"));
+                        };
+                        
+                        crate::commands::tags::set_asset_metadata(parts[2], key, value)
+                    },
                     _ => return Err(anyhow!("Unknown asset reference subcommand: {}", parts[1])),
                 }
             } else {
                 // Just ar by itself
-                return list_asset_references();
+                list_asset_references(&AssetFilter::default())
             }
         },
         "st" => {
-            // Store command: -st asset_name principle "Review Content"
+            // Store command: -st asset_name principle [-author "name"] [-source cli|mcp|llm] [-model id] [-confidence 0.8] [-findings json|file] "Review Content"
             
             if parts.len() < 4 {
                 return Err(anyhow!("Not enough arguments for store command. Usage: -st asset_name principle \"Review Content\""));
@@ -524,11 +834,48 @@ fn process_cli_flag_command(command: &str) -> Result<()> {
             let asset_name = parts[1];
             let principle = parts[2];
             
+            // Attribution and findings flags come before the content
+            let mut attribution = Attribution::from_env(ReviewSource::Cli)?;
+            let mut findings = Vec::new();
+            let mut content_start = 3;
+            while let Some(field) = parts.get(content_start).and_then(|p| p.strip_prefix('-')).filter(|f| *f == "findings" || ATTRIBUTION_FIELDS.contains(f)) {
+                let value = parts.get(content_start + 1)
+                    .ok_or_else(|| anyhow!("No value given for -{}", field))?;
+                
+                if field != "findings" && value.starts_with('"') {
+                    // A quoted value such as -author "Jane Doe" was split on whitespace, so rejoin it
+                    let words = parts[content_start + 1..].iter().enumerate()
+                        .position(|(i, p)| p.ends_with('"') && (i > 0 || p.len() > 1))
+                        .ok_or_else(|| anyhow!("Unclosed quote in the value of -{}", field))? + 1;
+                    let quoted = parts[content_start + 1..content_start + 1 + words].join(" ");
+                    attribution.set(field, quoted.trim_matches('"'))?;
+                    content_start += 1 + words;
+                } else if field != "findings" {
+                    attribution.set(field, value)?;
+                    content_start += 2;
+                } else if value.starts_with('[') || value.starts_with('{') {
+                    // Inline JSON may have been split on whitespace, so rejoin it and take the first JSON value
+                    let rest = parts[content_start + 1..].join(" ");
+                    let (json, _) = split_json_prefix(&rest)?;
+                    findings = parse_findings(json)?;
+                    content_start += 1 + json.split_whitespace().count();
+                } else {
+                    let json = fs::read_to_string(value)
+                        .with_context(|| format!("Failed to read findings file: {}", value))?;
+                    findings = parse_findings(&json)?;
+                    content_start += 2;
+                }
+            }
+            
+            if content_start >= parts.len() {
+                return Err(anyhow!("No review content given. Usage: -st asset_name principle [-author name] [-model id] [-confidence 0.8] \"Review Content\""));
+            }
+            
             // Handle quoted content properly - join remaining parts and strip quotes if present
-            let content_raw = parts[3..].join(" ");
+            let content_raw = parts[content_start..].join(" ");
             let content = content_raw.trim_matches('"');
             
-            return store_asset_review(asset_name, principle, content);
+            store_asset_review(asset_name, principle, content, &attribution, findings)
         },
         "se" => {
             // Set exemplar: -se asset t|f
@@ -551,8 +898,54 @@ fn process_cli_flag_command(command: &str) -> Result<()> {
             let principle = parts[2];
             let rating = parts[3];
             
-            return set_asset_compliance(&[asset_name, principle, rating]);
+            set_asset_compliance(&[asset_name, principle, rating], ReviewSource::Cli)
+        },
+        // Compact review history: -hc -keep 5 -before 2025-01-01
+        "hc" => compact_review_history(&parts[1..]),
+        // Show or convert the storage layout: -layout [single|sharded]
+        "layout" => convert_storage_layout(parts.get(1).copied()),
+        // Import legacy .vql.ref caches: -import-legacy [directory]
+        "import-legacy" => crate::commands::import_legacy::import_legacy(parts.get(1).copied()),
+        // List reviews written against older principle guidance: -stale [principles] [tag:name] [@context]
+        "stale" => list_stale_reviews(&parts[1..]),
+        // Files and reviews checked for CI: -check [principles] [tag:name] [@context]
+        "check" => crate::commands::check::run_check(&parts[1..]),
+        // Reviews ranked by git changes to their assets since: -changed-since-review [principles] [tag:name] [@context]
+        "changed-since-review" => crate::commands::check::changed_since_review(&parts[1..]),
+        // Assets found from asset type patterns: -discover [dir] [-apply [names...]] [-skip names...], -discover -ignore [patterns]
+        "discover" => crate::commands::discover::run_discover(&parts[1..]),
+        // Waivers: -waive uc a 2026-12-31 [-author dom] justification, -unwaive uc a, -waivers [-expired]
+        "waive" => match parts.get(1) {
+            Some(asset_name) => crate::commands::waivers::waive(asset_name, &parts[2..]),
+            None => Err(anyhow!("Usage: -waive asset principle YYYY-MM-DD [-author name] justification")),
+        },
+        "unwaive" => match parts[1..] {
+            [asset_name, principle] => crate::commands::waivers::unwaive(asset_name, principle),
+            _ => Err(anyhow!("Usage: -unwaive asset principle")),
+        },
+        "waivers" => crate::commands::waivers::list_waivers(&parts[1..]),
+        // Close review findings: -resolve uc 3 [4...] [how it was fixed]
+        "resolve" => match parts.get(1) {
+            Some(asset_name) => crate::commands::findings::resolve_findings(asset_name, &parts[2..]),
+            None => Err(anyhow!("Usage: -resolve asset id [id...] [how it was fixed]")),
         },
+        // Current reviews, filtered by attribution: -reviews [principles] [author:name] [source:llm] [model:id] [min-confidence:0.7]
+        "reviews" => list_reviews(&parts[1..]),
+        // Rating scales: -scale [-add name levels... | -remove name | -default name]
+        "scale" => crate::commands::scale::run_scale(&parts[1..]),
+        // Asset dependency graph: -deps asset (what it uses), -rdeps asset (what it affects)
+        "deps" => crate::commands::deps::run_deps(&parts[1..]),
+        "rdeps" => crate::commands::deps::run_rdeps(&parts[1..]),
+        // Weighted quality scores: -score [asset | tag:name | @context]
+        "score" => crate::commands::score::run_score(&parts[1..]),
+        // Run a script of commands as one change: -batch [file.vql]
+        "batch" => crate::commands::batch::run_batch(parts.get(1).map(|_| parts[1..].join(" ")).as_deref()),
+        // Check storage consistency: -doctor [--fix]
+        "doctor" => crate::commands::doctor::run_doctor(&parts[1..]),
+        // Undo, redo and list recent operations: -undo [N], -redo [N], -journal [N]
+        "undo" => crate::commands::journal::undo(parts.get(1).copied()),
+        "redo" => crate::commands::journal::redo(parts.get(1).copied()),
+        "journal" => crate::commands::journal::list_journal(parts.get(1).copied()),
         "su" => {
             // Setup VQL: -su "path/to/directory"
             if parts.len() > 1 {
@@ -649,9 +1042,8 @@ fn setup_vql_directory_in_path(path: &Path) -> Result<()> {
             "INFO:".blue().bold(), 
             vql_dir.display().to_string().blue());
             
-        // Check if json storage exists, if not, create it
-        let json_file_path = vql_dir.join("vql_storage.json");
-        if !json_file_path.exists() {
+        // Check if storage exists in either layout, if not, create it
+        if backend::detect_backend(&vql_dir).is_none() {
             let storage = JsonStorage::new();
             storage.save(&vql_dir)?;
             
@@ -786,9 +1178,15 @@ fn show_pr_usage() -> Result<()> {
     println!("\nAvailable commands: (colon prefix is optional)");
     println!("  {} or {} - List all principles", ":pr.list".blue(), "pr.list".blue());
     println!("  {} or {} - Add a new principle", ":pr.add(shortName, longName, guidance)".blue(), "pr.add(shortName, longName, guidance)".blue());
+    println!("  {} or {} - Add aliases for a principle", ":pr.alias(shortName, alias)".blue(), "pr.alias(shortName, alias)".blue());
     println!("\nCLI format:");
     println!("  {} - List all principles", "-pr".blue());
     println!("  {} - Add a new principle", "-pr -add a Architecture \"Architecture Guidelines\"".blue());
+    println!("  {} - Update guidance (makes older reviews stale)", "-pr -set a \"New guidance\"".blue());
+    println!("  {} - Make a principle count more in quality scores (default 1)", "-pr -weight s 3".blue());
+    println!("  {} - Rate a principle on another scale (see vql -scale)", "-pr -scale s pass_fail".blue());
+    println!("  {} - Add aliases for a principle", "-pr -alias s sec security".blue());
+    println!("  {} - Remove an alias", "-pr -unalias sec".blue());
     
     Ok(())
 }
@@ -797,10 +1195,6 @@ fn show_pr_usage() -> Result<()> {
 
 /// Add a new principle to the storage
 fn add_principle(short_name: &str, long_name: &str, guidance: Option<&str>) -> Result<()> {
-    if short_name.chars().count() != 1 {
-        return Err(anyhow!("Principle short name must be a single character"));
-    }
-    
     // Find VQL storage
     let (vql_dir, mut storage) = find_vql_storage()
         .context("Failed to find or load VQL storage")?;
@@ -819,6 +1213,47 @@ fn add_principle(short_name: &str, long_name: &str, guidance: Option<&str>) -> R
     Ok(())
 }
 
+/// Add aliases that resolve to a principle
+fn add_principle_aliases(short_name: &str, aliases: &[&str]) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    for alias in aliases {
+        storage.add_principle_alias(short_name, alias)?;
+    }
+    
+    // Save changes
+    storage.save(&vql_dir)?;
+    
+    let principle = storage.resolve_principle(short_name).unwrap_or(short_name);
+    println!("{} Added alias(es) {} for principle {}", 
+        "SUCCESS:".green().bold(), 
+        aliases.join(", ").blue(),
+        principle.blue().bold());
+    
+    Ok(())
+}
+
+/// Remove a principle alias
+fn remove_principle_alias(alias: &str) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    let principle = storage.remove_principle_alias(alias)?;
+    
+    // Save changes
+    storage.save(&vql_dir)?;
+    
+    println!("{} Removed alias {} of principle {}", 
+        "SUCCESS:".green().bold(), 
+        alias.blue(),
+        principle.blue().bold());
+    
+    Ok(())
+}
+
 /// Show all principles
 fn show_principles() -> Result<()> {
     // Find VQL storage
@@ -835,20 +1270,63 @@ fn show_principles() -> Result<()> {
     
     println!("{}", "Principles:".bold());
     
-    // Sort principles by short name
-    let mut sorted_principles: Vec<_> = principles.values().collect();
-    sorted_principles.sort_by(|a, b| a.short_name.cmp(&b.short_name));
-    
-    for princ in sorted_principles {
-        println!("  {} ({}): {}", 
-            princ.short_name.blue().bold(),
-            princ.long_name,
-            princ.guidance.as_deref().unwrap_or("No guidance provided"));
+    // Top-level principles by short name, each followed by its sub-principles
+    for princ in principles.values().filter(|p| p.parent().is_none()) {
+        print_principle_tree(&storage, princ, 1);
     }
     
     Ok(())
 }
 
+/// Print a principle and, indented below it, its sub-principles
+fn print_principle_tree(storage: &JsonStorage, princ: &crate::models::json_storage::Principle, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let sub_principles = storage.sub_principles(&princ.short_name);
+    
+    let aliases = if princ.aliases.is_empty() {
+        String::new()
+    } else {
+        format!(" [aliases: {}]", princ.aliases.join(", "))
+    };
+    
+    let revision = if princ.revision > 1 {
+        format!(" r{}", princ.revision)
+    } else {
+        String::new()
+    };
+    
+    let weight = match princ.weight {
+        Some(w) => format!(" weight {}", w),
+        None => String::new(),
+    };
+    
+    let scale = match &princ.rating_scale {
+        Some(scale) => format!(" scale {}", scale),
+        None => String::new(),
+    };
+    
+    // A parent's guidance spans its sub-principles, so list them instead of repeating it
+    let guidance = if sub_principles.is_empty() {
+        princ.guidance.as_deref().unwrap_or("No guidance provided").to_string()
+    } else {
+        format!("{} sub-principle(s)", sub_principles.len())
+    };
+    
+    println!("{}{} ({}){}{}{}{}: {}", 
+        indent,
+        princ.short_name.blue().bold(),
+        princ.long_name,
+        revision.dimmed(),
+        weight.dimmed(),
+        scale.dimmed(),
+        aliases.dimmed(),
+        guidance);
+    
+    for sub in sub_principles {
+        print_principle_tree(storage, &storage.principles[sub], depth + 1);
+    }
+}
+
 /// Add a new entity
 fn add_entity(args: &[&str]) -> Result<()> {
     if args.len() < 2 {
@@ -857,27 +1335,57 @@ fn add_entity(args: &[&str]) -> Result<()> {
     
     let short_name = args[0];
     let description = args[1];
+    let parent = args.get(2).copied();
     
     // Find VQL storage
     let (vql_dir, mut storage) = find_vql_storage()
         .context("Failed to find or load VQL storage")?;
     
-    // Add or update entity
+    // Add or update entity, optionally under a parent context
     storage.add_entity(short_name, description)?;
+    if parent.is_some() {
+        storage.set_entity_parent(short_name, parent)?;
+    }
     
     // Save changes
     storage.save(&vql_dir)?;
     
-    println!("{} Added entity: {} ({})", 
+    println!("{} Added entity: {} ({}){}", 
         "SUCCESS:".green().bold(), 
         short_name.blue(),
-        description);
+        description,
+        parent.map(|p| format!(" under {}", p)).unwrap_or_default());
+    
+    Ok(())
+}
+
+/// Place an entity under a parent entity, or at the top level with "none"
+fn set_entity_parent(short_name: &str, parent: &str) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    let parent = Some(parent).filter(|p| *p != "none");
+    storage.set_entity_parent(short_name, parent)?;
+    
+    // Save changes
+    storage.save(&vql_dir)?;
+    
+    match parent {
+        Some(parent) => println!("{} Entity {} is now under {}", 
+            "SUCCESS:".green().bold(), 
+            short_name.blue().bold(),
+            parent.blue().bold()),
+        None => println!("{} Entity {} is now a top-level entity", 
+            "SUCCESS:".green().bold(), 
+            short_name.blue().bold()),
+    }
     
     Ok(())
 }
 
 /// List all entities
-fn list_entities() -> Result<()> {
+fn list_entities(context: Option<&str>) -> Result<()> {
     // Find VQL storage
     let (_, storage) = find_vql_storage()
         .context("Failed to find or load VQL storage")?;
@@ -890,21 +1398,57 @@ fn list_entities() -> Result<()> {
         return Ok(());
     }
     
-    println!("{}", "Entities:".bold());
-    
-    // Sort entities by short name
-    let mut sorted_entities: Vec<_> = entities.values().collect();
-    sorted_entities.sort_by(|a, b| a.short_name.cmp(&b.short_name));
+    // Show the whole hierarchy, or one context with everything under it
+    let roots: Vec<&str> = match context {
+        Some(context) => {
+            if !entities.contains_key(context) {
+                return Err(anyhow!("Entity '{}' not found", context));
+            }
+            println!("{} {}:", "Entities in".bold(), context.blue().bold());
+            vec![context]
+        },
+        None => {
+            println!("{}", "Entities:".bold());
+            // Top-level entities, plus any whose parent is missing so nothing is hidden
+            entities.iter()
+                .filter(|(_, e)| e.parent.as_ref().is_none_or(|p| !entities.contains_key(p)))
+                .map(|(name, _)| name.as_str())
+                .collect()
+        },
+    };
     
-    for entity in sorted_entities {
-        println!("  {} ({})", 
-            entity.short_name.blue().bold(),
-            entity.description);
+    for root in roots {
+        print_entity_tree(&storage, root, 1);
     }
     
     Ok(())
 }
 
+/// Print an entity and the entities under it, one level per indent
+fn print_entity_tree(storage: &JsonStorage, name: &str, depth: usize) {
+    let Some(entity) = storage.entities.get(name) else { return };
+    
+    let asset_count = storage.asset_references.values().filter(|a| a.entity == name).count();
+    let assets_str = if asset_count > 0 {
+        format!(" - {} asset(s)", asset_count).dimmed()
+    } else {
+        "".normal()
+    };
+    
+    println!("{}{} ({}){}", 
+        "  ".repeat(depth),
+        name.blue().bold(),
+        entity.description,
+        assets_str);
+    
+    // Parent loops (reported by vql -doctor) are not followed
+    for child in storage.entity_children(name) {
+        if !storage.entity_in_context(name, child) {
+            print_entity_tree(storage, child, depth + 1);
+        }
+    }
+}
+
 /// Add a new asset type
 fn add_asset_type(args: &[&str]) -> Result<()> {
     if args.len() < 2 {
@@ -960,6 +1504,9 @@ fn list_asset_types() -> Result<()> {
         println!("  {} ({})", 
             asset_type.short_name.blue().bold(),
             asset_type.description);
+        if !asset_type.patterns.is_empty() {
+            println!("    Discovered by: {}", asset_type.patterns.join(", ").dimmed());
+        }
     }
     
     Ok(())
@@ -968,45 +1515,28 @@ fn list_asset_types() -> Result<()> {
 /// Add a new asset reference
 fn add_asset_reference(args: &[&str]) -> Result<()> {
     if args.len() < 4 {
-        return Err(anyhow!("Not enough arguments. Usage: add_asset_reference short_name entity asset_type path"));
+        return Err(anyhow!("Not enough arguments. Usage: add_asset_reference short_name entity asset_type path [path...]"));
     }
     
     let short_name = args[0];
     let entity = args[1];
     let asset_type = args[2];
-    let path = args[3];
+    let paths = &args[3..];
     
     // Find VQL storage first to get its directory
     let (vql_dir, mut storage) = find_vql_storage()
         .context("Failed to find or load VQL storage")?;
-        
-    // Resolve the path relative to the VQL directory
-    // First get the absolute path of the VQL directory
-    let vql_dir_abs = fs::canonicalize(&vql_dir)
-        .context("Failed to canonicalize VQL directory path")?;
-        
-    // Get the parent of the VQL directory
-    let vql_parent = vql_dir_abs.parent().unwrap_or(&vql_dir_abs);
     
-    // Resolve the path based on whether it's absolute or relative
-    let resolved_path = if path.starts_with("/") {
-        // Absolute path
-        PathBuf::from(path)
-    } else {
-        // Relative path (relative to VQL directory's parent)
-        vql_parent.join(path)
-    };
-    
-    // Check if the file exists
-    if !resolved_path.exists() {
-        return Err(anyhow!("File not found: {}. The file must exist to be added as an asset reference. Path is resolved relative to the VQL directory's parent.", path));
-    }
-    if !resolved_path.is_file() {
-        return Err(anyhow!("Path is not a file: {}. Only files can be added as asset references.", path));
-    };
+    // Paths are resolved relative to the VQL directory's parent; each file or directory
+    // must exist and each glob pattern must match at least one file
+    let project_root = asset_paths::project_root(&vql_dir);
+    asset_paths::validate_paths(&project_root, paths)?;
     
     // Add or update asset reference
-    storage.add_asset_reference(short_name, entity, asset_type, path)?;
+    storage.add_asset_reference(short_name, entity, asset_type, paths[0])?;
+    if paths.len() > 1 {
+        storage.set_asset_paths(short_name, paths)?;
+    }
     
     // Save changes
     storage.save(&vql_dir)?;
@@ -1016,7 +1546,43 @@ fn add_asset_reference(args: &[&str]) -> Result<()> {
         short_name.blue().bold(),
         entity,
         asset_type,
-        path);
+        paths.join(", "));
+    note_matched_files(&project_root, &storage.asset_references[short_name])?;
+    
+    Ok(())
+}
+
+/// Replace the paths of an asset: -ar -paths uc src/user "src/user/*.service.ts"
+fn set_asset_paths(asset_name: &str, paths: &[&str]) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    let project_root = asset_paths::project_root(&vql_dir);
+    asset_paths::validate_paths(&project_root, paths)?;
+    
+    storage.set_asset_paths(asset_name, paths)?;
+    
+    // Save changes
+    storage.save(&vql_dir)?;
+    
+    println!("{} Asset {} now covers: {}", 
+        "SUCCESS:".green().bold(), 
+        asset_name.blue().bold(),
+        paths.join(", "));
+    note_matched_files(&project_root, &storage.asset_references[asset_name])?;
+    
+    Ok(())
+}
+
+/// Tell how many files an asset made of directories or patterns currently matches
+fn note_matched_files(project_root: &Path, asset: &AssetReference) -> Result<()> {
+    if asset.extra_paths.is_empty() && asset_paths::resolve(project_root, &asset.path).is_file() {
+        return Ok(());
+    }
+    
+    let files = asset_paths::asset_files(project_root, asset)?;
+    println!("{} {} currently matches {} file(s)", "INFO:".blue().bold(), asset.short_name, files.len());
     
     Ok(())
 }
@@ -1038,6 +1604,9 @@ fn rename_principle(old_name: &str, new_name: &str) -> Result<()> {
             }
         })
         .collect();
+    let sub_principles = storage.principles.keys()
+        .filter(|key| key.starts_with(&format!("{}.", old_name)))
+        .count();
     
     // Rename the principle
     storage.rename_principle(old_name, new_name)?;
@@ -1049,6 +1618,13 @@ fn rename_principle(old_name: &str, new_name: &str) -> Result<()> {
         "SUCCESS:".green().bold(),
         old_name.blue().bold(),
         new_name.blue().bold());
+    
+    if sub_principles > 0 {
+        println!("{} Renamed {} sub-principle(s) to {}.*", 
+            "CASCADE:".yellow().bold(),
+            sub_principles,
+            new_name);
+    }
         
     if !affected_assets.is_empty() {
         println!("{} Updated principle key in {} asset review(s):", 
@@ -1181,6 +1757,9 @@ fn delete_principle(name: &str) -> Result<()> {
             }
         })
         .collect();
+    let sub_principles = storage.principles.keys()
+        .filter(|key| key.starts_with(&format!("{}.", name)))
+        .count();
     
     // Delete the principle
     storage.delete_principle(name)?;
@@ -1192,6 +1771,13 @@ fn delete_principle(name: &str) -> Result<()> {
         "SUCCESS:".green().bold(),
         name.blue().bold());
     
+    if sub_principles > 0 {
+        println!("{} Deleted {} sub-principle(s) of '{}' and their reviews", 
+            "CASCADE:".yellow().bold(),
+            sub_principles,
+            name);
+    }
+    
     if !affected_assets.is_empty() {
         println!("{} Removed principle '{}' from {} asset(s):", 
             "CASCADE:".yellow().bold(),
@@ -1339,7 +1925,7 @@ fn delete_item(name: &str) -> Result<()> {
 }
 
 /// List all asset references
-fn list_asset_references() -> Result<()> {
+fn list_asset_references(filter: &AssetFilter) -> Result<()> {
     // Find VQL storage
     let (_, storage) = find_vql_storage()
         .context("Failed to find or load VQL storage")?;
@@ -1352,17 +1938,29 @@ fn list_asset_references() -> Result<()> {
         return Ok(());
     }
     
-    println!("{}", "Asset References:".bold());
+    filter.check(&storage)?;
     
     // Sort asset references by short name
-    let mut sorted_asset_refs: Vec<_> = asset_references.values().collect();
-    sorted_asset_refs.sort_by(|a, b| a.short_name.cmp(&b.short_name));
+    let mut sorted_asset_refs: Vec<_> = asset_references.values().filter(|a| filter.matches(&storage, a)).collect();
     
-    // Find the maximum length of each column for alignment
-    let max_name_len = sorted_asset_refs.iter()
-        .map(|a| a.short_name.len() + (if a.exemplar { 10 } else { 0 }))
-        .max()
-        .unwrap_or(10);
+    if sorted_asset_refs.is_empty() {
+        println!("{} No asset references match {}", "INFO:".blue().bold(), filter.describe());
+        return Ok(());
+    }
+    
+    if filter.is_empty() {
+        println!("{}", "Asset References:".bold());
+    } else {
+        println!("{} {}", "Asset References".bold(), format!("({}):", filter.describe()).bold());
+    }
+    
+    sorted_asset_refs.sort_by(|a, b| a.short_name.cmp(&b.short_name));
+    
+    // Find the maximum length of each column for alignment
+    let max_name_len = sorted_asset_refs.iter()
+        .map(|a| a.short_name.len() + (if a.exemplar { 10 } else { 0 }))
+        .max()
+        .unwrap_or(10);
     let max_entity_len = sorted_asset_refs.iter()
         .map(|a| a.entity.len())
         .max()
@@ -1389,17 +1987,58 @@ fn list_asset_references() -> Result<()> {
         let exemplar_str = if asset_ref.exemplar { " (Exemplar)".green() } else { "".normal() };
         let asset_name = format!("{}{}", asset_ref.short_name.blue().bold(), exemplar_str);
         
-        // Extract just the filename from the full path
-        let filename = std::path::Path::new(&asset_ref.path)
-            .file_name()
-            .and_then(|name| name.to_str())
-            .unwrap_or(&asset_ref.path);
+        // Extract just the filename from the full path; glob patterns are shown as written
+        let filename = if asset_paths::is_pattern(&asset_ref.path) {
+            asset_ref.path.clone()
+        } else {
+            std::path::Path::new(&asset_ref.path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or(&asset_ref.path)
+                .to_string()
+        };
+        let filename = match asset_ref.extra_paths.len() {
+            0 => filename,
+            n => format!("{} (+{} more)", filename, n),
+        };
         
-        println!("  {:<width_name$}  {:<width_entity$}  {:<width_type$}  {}", 
+        // Reviews written against guidance that has since changed
+        let stale_count = asset_ref.principle_reviews.iter()
+            .filter(|(principle, review)| storage.principles.get(*principle).is_some_and(|p| p.is_stale(review)))
+            .count();
+        let stale_str = if stale_count > 0 {
+            format!(" ({} stale)", stale_count).yellow()
+        } else {
+            "".normal()
+        };
+        
+        // Accepted-risk waivers, flagging those that have expired
+        let expired_count = asset_ref.waivers.values().filter(|w| !w.is_active()).count();
+        let waived_str = match (asset_ref.waivers.len() - expired_count, expired_count) {
+            (0, 0) => "".normal(),
+            (waived, 0) => format!(" ({} waived)", waived).cyan(),
+            (_, expired) => format!(" ({} waiver(s) expired)", expired).yellow().bold(),
+        };
+        
+        let tags_str = if asset_ref.tags.is_empty() {
+            "".normal()
+        } else {
+            format!("  {}", crate::commands::tags::format_tags(asset_ref)).cyan()
+        };
+        let metadata_str = asset_ref.metadata.iter()
+            .map(|(key, value)| format!("  {}={}", key, value))
+            .collect::<String>()
+            .dimmed();
+        
+        println!("  {:<width_name$}  {:<width_entity$}  {:<width_type$}  {}{}{}{}{}", 
             asset_name,
             asset_ref.entity,
             asset_ref.asset_type,
             filename,
+            stale_str,
+            waived_str,
+            tags_str,
+            metadata_str,
             width_name = max_name_len,
             width_entity = max_entity_len,
             width_type = max_type_len);
@@ -1442,16 +2081,18 @@ fn set_asset_exemplar(args: &[&str]) -> Result<()> {
 }
 
 /// Store asset review and try to extract rating from the analysis text
-fn store_asset_review(asset_name: &str, principle: &str, analysis: &str) -> Result<()> {
+fn store_asset_review(asset_name: &str, principle: &str, analysis: &str, attribution: &Attribution, findings: Vec<Finding>) -> Result<()> {
     // Find VQL storage
     let (vql_dir, mut storage) = find_vql_storage()
         .context("Failed to find or load VQL storage")?;
     
-    // Try to extract rating from analysis
-    let rating = extract_rating_from_text(analysis);
+    // Try to extract a rating on the principle's scale from the analysis
+    let key = storage.resolve_principle(principle).unwrap_or(principle);
+    let rating = storage.rating_scale_for(key).extract_from_text(analysis);
     
     // Store review with auto-extracted rating if available
-    storage.store_asset_review(asset_name, principle, rating.as_deref(), analysis)?;
+    let new_findings = findings.len();
+    storage.store_asset_review(asset_name, principle, rating.as_deref(), analysis, attribution, findings)?;
     
     // Save changes
     storage.save(&vql_dir)?;
@@ -1466,55 +2107,41 @@ fn store_asset_review(asset_name: &str, principle: &str, analysis: &str) -> Resu
             "".to_string()
         });
     
-    Ok(())
-}
-
-/// Extract rating (H/M/L) from text
-fn extract_rating_from_text(text: &str) -> Option<String> {
-    let text_lower = text.to_lowercase();
-    
-    // Check for explicit ratings
-    if text_lower.contains("high compliance") || text_lower.contains("compliance: high") {
-        return Some("H".to_string());
-    } else if text_lower.contains("medium compliance") || text_lower.contains("compliance: medium") {
-        return Some("M".to_string());
-    } else if text_lower.contains("low compliance") || text_lower.contains("compliance: low") {
-        return Some("L".to_string());
-    }
-    
-    // Fallback for simple mentions like "rated as HIGH"
-    if text_lower.contains(" high ") || text_lower.contains(" high.") || text_lower.contains(" high,") {
-        return Some("H".to_string());
-    } else if text_lower.contains(" medium ") || text_lower.contains(" medium.") || text_lower.contains(" medium,") {
-        return Some("M".to_string());
-    } else if text_lower.contains(" low ") || text_lower.contains(" low.") || text_lower.contains(" low,") {
-        return Some("L".to_string());
+    // Report findings, including open ones carried over from the review this one replaced
+    let open = storage.get_asset_review(asset_name, principle)?
+        .map(|review| review.findings.iter().filter(|f| f.is_open()).count())
+        .unwrap_or(0);
+    if open > 0 {
+        println!("{} {} new finding(s), {} open for this principle - see {}", 
+            "NOTE:".cyan().bold(),
+            new_findings,
+            open,
+            format!("{}?findings", asset_name).blue());
     }
     
-    None
+    Ok(())
 }
 
 /// Set asset compliance rating
-fn set_asset_compliance(args: &[&str]) -> Result<()> {
+fn set_asset_compliance(args: &[&str], source: ReviewSource) -> Result<()> {
     if args.len() < 3 {
         return Err(anyhow!("Not enough arguments. Usage: set_asset_compliance asset_name principle rating"));
     }
     
     let asset_name = args[0];
     let principle = args[1];
-    let rating = args[2];
-    
-    // Validate rating
-    if !["H", "M", "L", "h", "m", "l"].contains(&rating) {
-        return Err(anyhow!("Invalid rating: {}. Must be H, M, or L", rating));
-    }
     
     // Find VQL storage
     let (vql_dir, mut storage) = find_vql_storage()
         .context("Failed to find or load VQL storage")?;
     
+    // Validate rating against the principle's scale (e.g. "h" or "high" for H)
+    let key = storage.resolve_principle(principle).unwrap_or(principle);
+    let rating = storage.rating_scale_for(key).parse(args[2])?.to_string();
+    
     // Store review with rating (empty analysis)
-    storage.store_asset_review(asset_name, principle, Some(rating.to_uppercase().as_str()), "")?;
+    let attribution = Attribution::from_env(source)?;
+    storage.store_asset_review(asset_name, principle, Some(&rating), "", &attribution, Vec::new())?;
     
     // Save changes
     storage.save(&vql_dir)?;
@@ -1523,7 +2150,7 @@ fn set_asset_compliance(args: &[&str]) -> Result<()> {
         "SUCCESS:".green().bold(), 
         principle,
         asset_name.blue().bold(),
-        rating.to_uppercase());
+        rating);
     
     Ok(())
 }
@@ -1538,13 +2165,15 @@ fn show_asset_principle_review(asset_name: &str, principle: &str) -> Result<()>
     let review = storage.get_asset_review(asset_name, principle)?;
     
     if let Some(review) = review {
-        println!("{} Review for asset {} from {} principle:", 
+        let key = storage.resolve_principle(principle).unwrap_or(principle);
+        println!("{} Review for asset {} from {} principle:{}", 
             "INFO:".blue().bold(), 
             asset_name.blue().bold(),
-            principle);
+            principle,
+            stale_marker(&storage, key, review));
             
         if let Some(rating) = &review.rating {
-            println!("  Rating: {}", get_rating_display(rating));
+            println!("  Rating: {}", get_rating_display(storage.rating_scale_for(key), rating));
         } else {
             println!("  Rating: Not rated");
         }
@@ -1554,6 +2183,9 @@ fn show_asset_principle_review(asset_name: &str, principle: &str) -> Result<()>
         } else {
             println!("  Analysis: No analysis provided");
         }
+        print_attribution(review, "  ");
+        print_findings_count(review, "  ");
+        crate::commands::waivers::print_waiver(&storage.asset_references[asset_name], key, "  ");
         
         println!("  Last modified: {}", review.last_modified);
     } else {
@@ -1562,6 +2194,7 @@ fn show_asset_principle_review(asset_name: &str, principle: &str) -> Result<()>
             asset_name.blue().bold(),
             principle);
     }
+    print_rolled_up_rating(&storage, asset_name, storage.resolve_principle(principle).unwrap_or(principle), "  ");
     
     Ok(())
 }
@@ -1584,8 +2217,28 @@ fn show_asset_why(asset_name: &str, principle: Option<&str>) -> Result<()> {
         
     println!("  Entity: {}", asset.entity);
     println!("  Type: {}", asset.asset_type);
-    println!("  Path: {}", asset.path);
+    if asset.extra_paths.is_empty() {
+        println!("  Path: {}", asset.path);
+    } else {
+        println!("  Paths: {}", asset.paths().join(", "));
+    }
     println!("  Exemplar: {}", if asset.exemplar { "Yes".green() } else { "No".red() });
+    if !asset.tags.is_empty() {
+        println!("  Tags: {}", crate::commands::tags::format_tags(asset).cyan());
+    }
+    if !asset.depends_on.is_empty() {
+        println!("  Depends on: {}", asset.depends_on.join(", "));
+    }
+    let dependents = storage.asset_dependents(asset_name);
+    if !dependents.is_empty() {
+        println!("  Used by: {}", dependents.join(", "));
+    }
+    if !asset.metadata.is_empty() {
+        println!("  Metadata:");
+        for (key, value) in &asset.metadata {
+            println!("    {}: {}", key, value);
+        }
+    }
     println!("  Last modified: {}", asset.last_modified);
     
     // If specific principle requested, check if it contains commas
@@ -1596,11 +2249,12 @@ fn show_asset_why(asset_name: &str, principle: Option<&str>) -> Result<()> {
             println!("\n  Reviews for selected principles:");
             
             for &princ in &principles {
-                if let Some(review) = asset.principle_reviews.get(princ) {
-                    println!("    {} Principle:", princ);
+                let key = storage.resolve_principle(princ).unwrap_or(princ);
+                if let Some(review) = asset.principle_reviews.get(key) {
+                    println!("    {} Principle:{}", princ, stale_marker(&storage, key, review));
                     
                     if let Some(rating) = &review.rating {
-                        println!("      Rating: {}", get_rating_display(rating));
+                        println!("      Rating: {}", get_rating_display(storage.rating_scale_for(key), rating));
                     } else {
                         println!("      Rating: Not rated");
                     }
@@ -1610,19 +2264,25 @@ fn show_asset_why(asset_name: &str, principle: Option<&str>) -> Result<()> {
                     } else {
                         println!("      Analysis: No analysis provided");
                     }
+                    print_attribution(review, "      ");
+                    print_findings_count(review, "      ");
+                    crate::commands::waivers::print_waiver(asset, key, "      ");
+                    print_rolled_up_rating(&storage, asset_name, key, "      ");
                     
                     println!("");
                 } else {
                     println!("    {} Principle: No review", princ);
+                    print_rolled_up_rating(&storage, asset_name, key, "      ");
                 }
             }
         } else {
             // Single principle
-            if let Some(review) = asset.principle_reviews.get(p) {
-                println!("\n  {} Principle:", p);
+            let key = storage.resolve_principle(p).unwrap_or(p);
+            if let Some(review) = asset.principle_reviews.get(key) {
+                println!("\n  {} Principle:{}", p, stale_marker(&storage, key, review));
                 
                 if let Some(rating) = &review.rating {
-                    println!("    Rating: {}", get_rating_display(rating));
+                    println!("    Rating: {}", get_rating_display(storage.rating_scale_for(key), rating));
                 } else {
                     println!("    Rating: Not rated");
                 }
@@ -1632,8 +2292,13 @@ fn show_asset_why(asset_name: &str, principle: Option<&str>) -> Result<()> {
                 } else {
                     println!("    Analysis: No analysis provided");
                 }
+                print_attribution(review, "    ");
+                print_findings_count(review, "    ");
+                crate::commands::waivers::print_waiver(asset, key, "    ");
+                print_rolled_up_rating(&storage, asset_name, key, "    ");
             } else {
                 println!("\n  {} Principle: No review", p);
+                print_rolled_up_rating(&storage, asset_name, key, "    ");
             }
         }
     } else {
@@ -1643,11 +2308,27 @@ fn show_asset_why(asset_name: &str, principle: Option<&str>) -> Result<()> {
         if asset.principle_reviews.is_empty() {
             println!("    No reviews available");
         } else {
-            for (princ, review) in &asset.principle_reviews {
-                println!("    {} Principle:", princ);
+            // Parents without a review of their own still show the roll-up of their sub-principles;
+            // principles under an active waiver are shown apart, below
+            let mut reviewed: Vec<&str> = asset.principle_reviews.keys().map(String::as_str).collect();
+            reviewed.extend(storage.principles.keys().map(String::as_str)
+                .filter(|p| !asset.principle_reviews.contains_key(*p)
+                    && storage.rolled_up_rating(asset_name, p).is_some()));
+            reviewed.retain(|p| asset.active_waiver(p).is_none());
+            reviewed.sort();
+            
+            for princ in reviewed {
+                let Some(review) = asset.principle_reviews.get(princ) else {
+                    println!("    {} Principle: No review", princ);
+                    print_rolled_up_rating(&storage, asset_name, princ, "      ");
+                    println!();
+                    continue;
+                };
+                
+                println!("    {} Principle:{}", princ, stale_marker(&storage, princ, review));
                 
                 if let Some(rating) = &review.rating {
-                    println!("      Rating: {}", get_rating_display(rating));
+                    println!("      Rating: {}", get_rating_display(storage.rating_scale_for(princ), rating));
                 } else {
                     println!("      Rating: Not rated");
                 }
@@ -1657,25 +2338,466 @@ fn show_asset_why(asset_name: &str, principle: Option<&str>) -> Result<()> {
                 } else {
                     println!("      Analysis: No analysis provided");
                 }
+                print_attribution(review, "      ");
+                print_findings_count(review, "      ");
+                crate::commands::waivers::print_waiver(asset, princ, "      ");
+                print_rolled_up_rating(&storage, asset_name, princ, "      ");
                 
                 println!("");
             }
         }
+        
+        // Accepted risks, with the rating they accept
+        if !asset.waivers.is_empty() {
+            println!("\n  Waived:");
+            for (princ, waiver) in &asset.waivers {
+                let rating = match asset.principle_reviews.get(princ).and_then(|r| r.rating.as_deref()) {
+                    Some(rating) => get_rating_display(storage.rating_scale_for(princ), rating),
+                    None => "Not rated".normal(),
+                };
+                println!("    {} Principle: {}  {}", princ, rating, crate::commands::waivers::describe_waiver(waiver));
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Handle review history and findings queries (uc?history, uc?history(a), uc?at(date), uc?at(date, a,s),
+/// uc?findings, uc?findings(a, all))
+///
+/// Returns None if the command is not a history or findings query.
+fn try_review_history_query(command: &str) -> Option<Result<()>> {
+    let findings_re = Regex::new(r"^([a-zA-Z0-9_]+)\s*\?\s*findings\s*(\(([^)]*)\))?$").unwrap();
+    if let Some(captures) = findings_re.captures(command) {
+        let asset_name = captures.get(1).unwrap().as_str();
+        let args = captures.get(3).map(|m| m.as_str());
+        return Some(crate::commands::findings::show_findings(asset_name, args));
+    }
+    
+    let history_re = Regex::new(r"^([a-zA-Z0-9_]+)\s*\?\s*history\s*(\(([^)]*)\))?$").unwrap();
+    if let Some(captures) = history_re.captures(command) {
+        let asset_name = captures.get(1).unwrap().as_str();
+        let principles = captures.get(3).map(|m| m.as_str());
+        return Some(show_review_history(asset_name, principles));
+    }
+    
+    let at_re = Regex::new(r"^([a-zA-Z0-9_]+)\s*\?\s*at\s*\(([^,)]+)(?:,([^)]*))?\)$").unwrap();
+    if let Some(captures) = at_re.captures(command) {
+        let asset_name = captures.get(1).unwrap().as_str();
+        let date = captures.get(2).unwrap().as_str().trim();
+        let principles = captures.get(3).map(|m| m.as_str());
+        return Some(show_reviews_at(asset_name, date, principles));
+    }
+    
+    None
+}
+
+/// Resolve the principles to report on for an asset: the requested list, or every principle the asset has history for
+fn principles_for_history(asset_name: &str, principles: Option<&str>, storage: &JsonStorage) -> Result<Vec<String>> {
+    let asset = storage.asset_references.get(asset_name)
+        .ok_or_else(|| anyhow!("Asset {} not found", asset_name))?;
+    
+    match principles {
+        Some(p) if !p.trim().is_empty() => parse_principle_list(p, storage),
+        _ => {
+            let mut names: Vec<String> = asset.principle_reviews.keys()
+                .chain(asset.review_history.keys())
+                .cloned()
+                .collect();
+            names.sort();
+            names.dedup();
+            Ok(names)
+        }
+    }
+}
+
+/// Show the full review history of an asset for one or more principles
+fn show_review_history(asset_name: &str, principles: Option<&str>) -> Result<()> {
+    // Find VQL storage
+    let (_, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    let principle_names = principles_for_history(asset_name, principles, &storage)?;
+    
+    if principle_names.is_empty() {
+        println!("{} No reviews found for asset {}", 
+            "INFO:".blue().bold(), 
+            asset_name.blue().bold());
+        return Ok(());
+    }
+    
+    for principle in &principle_names {
+        let history = storage.get_review_history(asset_name, principle)?;
+        
+        println!("{} Review history for asset {} from {} principle ({} entries):", 
+            "INFO:".blue().bold(), 
+            asset_name.blue().bold(),
+            principle,
+            history.len());
+        
+        if history.is_empty() {
+            println!("    No reviews available");
+        }
+        
+        let latest = history.len().saturating_sub(1);
+        for (index, review) in history.iter().enumerate() {
+            let rating = match &review.rating {
+                Some(rating) => get_rating_display(storage.rating_scale_for(principle), rating),
+                None => "Not rated".normal(),
+            };
+            
+            println!("  {}. {}  {}{}", 
+                index + 1,
+                review.last_modified,
+                rating,
+                if index == latest { " (current)".dimmed().to_string() } else { String::new() });
+            
+            if let Some(analysis) = &review.analysis {
+                if !analysis.is_empty() {
+                    println!("      {}", analysis);
+                }
+            }
+            print_attribution(review, "      ");
+            print_findings_count(review, "      ");
+        }
+        
+        println!();
+    }
+    
+    Ok(())
+}
+
+/// Show the reviews that were current for an asset at a past date
+fn show_reviews_at(asset_name: &str, date: &str, principles: Option<&str>) -> Result<()> {
+    // Find VQL storage
+    let (_, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    let principle_names = principles_for_history(asset_name, principles, &storage)?;
+    
+    println!("{} Reviews for asset {} as of {}:", 
+        "INFO:".blue().bold(), 
+        asset_name.blue().bold(),
+        date.blue());
+    
+    if principle_names.is_empty() {
+        println!("    No reviews available");
+        return Ok(());
+    }
+    
+    for principle in &principle_names {
+        match storage.get_review_at(asset_name, principle, date)? {
+            Some(review) => {
+                let rating = match &review.rating {
+                    Some(rating) => get_rating_display(storage.rating_scale_for(principle), rating),
+                    None => "Not rated".normal(),
+                };
+                println!("    {} Principle: {} (reviewed {})", principle, rating, review.last_modified);
+            },
+            None => println!("    {} Principle: No review yet", principle),
+        }
+    }
+    
+    Ok(())
+}
+
+/// Compact review history: -hc [-keep N] [-before YYYY-MM-DD]
+fn compact_review_history(args: &[&str]) -> Result<()> {
+    let mut keep: Option<usize> = None;
+    let mut before: Option<&str> = None;
+    
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].trim_start_matches('-') {
+            "keep" => {
+                let value = args.get(i + 1)
+                    .ok_or_else(|| anyhow!("Missing value for -keep. Usage: -hc -keep N [-before YYYY-MM-DD]"))?;
+                keep = Some(value.parse::<usize>()
+                    .map_err(|_| anyhow!("Invalid value for -keep: {}. Must be a positive number", value))?);
+                i += 2;
+            },
+            "before" => {
+                let value = args.get(i + 1)
+                    .ok_or_else(|| anyhow!("Missing value for -before. Usage: -hc [-keep N] -before YYYY-MM-DD"))?;
+                before = Some(value);
+                i += 2;
+            },
+            _ => return Err(anyhow!("Unknown history compaction option: {}. Usage: -hc [-keep N] [-before YYYY-MM-DD]", args[i])),
+        }
+    }
+    
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    let removed = storage.compact_review_history(keep, before)?;
+    
+    // Save changes
+    storage.save(&vql_dir)?;
+    
+    println!("{} Compacted review history: removed {} entr{}", 
+        "SUCCESS:".green().bold(),
+        removed,
+        if removed == 1 { "y" } else { "ies" });
+    
+    Ok(())
+}
+
+/// Show the storage layout, or convert storage to another layout (single, sharded or sqlite)
+fn convert_storage_layout(target: Option<&str>) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    let current = storage.backend()
+        .ok_or_else(|| anyhow!("VQL storage has no backend"))?;
+    
+    let target = match target {
+        Some(name) => backend::backend_by_name(&vql_dir, name)?,
+        None => {
+            println!("{} VQL storage uses the {} layout at {}", 
+                "INFO:".blue().bold(),
+                current.name().bold(),
+                current.location().blue());
+            return Ok(());
+        }
+    };
+    
+    if target.name() == current.name() {
+        println!("{} VQL storage already uses the {} layout", 
+            "INFO:".blue().bold(),
+            current.name().bold());
+        return Ok(());
     }
     
+    // Write the new layout before removing the old one so an interruption never loses data
+    storage.set_backend(target.clone());
+    storage.save(&vql_dir)?;
+    current.remove()?;
+    
+    println!("{} Converted VQL storage from the {} layout to the {} layout", 
+        "SUCCESS:".green().bold(),
+        current.name(),
+        target.name().bold());
+    
     Ok(())
 }
 
-/// Display a rating with color
-fn get_rating_display(rating: &str) -> colored::ColoredString {
-    match rating {
-        "H" => "High".green().bold(),
-        "M" => "Medium".yellow().bold(),
-        "L" => "Low".red().bold(),
-        _ => rating.normal(),
+/// Display a rating with the label and colour its scale gives it
+fn get_rating_display(scale: &RatingScale, rating: &str) -> colored::ColoredString {
+    match scale.level(rating) {
+        Some(level) => match level.color.as_deref().and_then(rating::parse_color) {
+            Some(color) => level.label.color(color).bold(),
+            None => level.label.bold(),
+        },
+        None => rating.normal(),
     }
 }
 
+/// Print who wrote a review and how confident they were, if recorded
+fn print_attribution(review: &Review, indent: &str) {
+    if let Some(by) = attribution::describe(review) {
+        println!("{}By: {}", indent, by.dimmed());
+    }
+}
+
+/// Print how many findings a review has, if any
+fn print_findings_count(review: &Review, indent: &str) {
+    if review.findings.is_empty() {
+        return;
+    }
+    let open = review.findings.iter().filter(|f| f.is_open()).count();
+    println!("{}Findings: {} open, {} resolved", indent, open, review.findings.len() - open);
+}
+
+/// Print the rating a principle gets from its sub-principles, if any of them are rated
+fn print_rolled_up_rating(storage: &JsonStorage, asset_name: &str, principle: &str, indent: &str) {
+    if let Some((sub, rating)) = storage.rolled_up_rating(asset_name, principle) {
+        println!("{}Rolled up from sub-principles (lowest): {} ({})", indent, get_rating_display(storage.rating_scale_for(sub), rating), sub);
+    }
+}
+
+/// Marker shown next to a review written against older principle guidance
+fn stale_marker(storage: &JsonStorage, principle: &str, review: &crate::models::json_storage::Review) -> String {
+    match storage.principles.get(principle) {
+        Some(p) if p.is_stale(review) => format!(" {}", format!("(stale: written for revision {}, guidance is now at revision {})",
+            review.principle_revision.unwrap_or(1),
+            p.revision).yellow()),
+        _ => String::new(),
+    }
+}
+
+/// Set how much a principle counts in quality scores
+fn set_principle_weight(short_name: &str, weight: &str) -> Result<()> {
+    let weight: f64 = weight.parse()
+        .map_err(|_| anyhow!("Invalid weight: {}. Must be a number, e.g. 3 or 0.5", weight))?;
+    
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    // The default weight is stored as no weight at all
+    storage.set_principle_weight(short_name, Some(weight).filter(|w| *w != 1.0))?;
+    
+    // Save changes
+    storage.save(&vql_dir)?;
+    
+    println!("{} Set weight of principle {} to {}", 
+        "SUCCESS:".green().bold(),
+        short_name.blue().bold(),
+        weight);
+    
+    Ok(())
+}
+
+/// Update a principle's guidance, bumping its revision
+fn set_principle_guidance(short_name: &str, guidance: &str) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    let key = storage.resolve_principle(short_name)
+        .ok_or_else(|| anyhow!("Principle '{}' not found", short_name))?
+        .to_string();
+    let previous = storage.principles[&key].revision;
+    let revision = storage.update_principle(&key, None, Some(guidance))?;
+    
+    if revision == previous {
+        println!("{} Guidance for principle {} is unchanged (revision {})", 
+            "INFO:".blue().bold(), 
+            key.blue().bold(),
+            revision);
+        return Ok(());
+    }
+    
+    // Save changes
+    storage.save(&vql_dir)?;
+    
+    println!("{} Updated guidance for principle {} (revision {})", 
+        "SUCCESS:".green().bold(), 
+        key.blue().bold(),
+        revision);
+    
+    let stale = storage.stale_reviews().iter().filter(|(_, p, _)| *p == key).count();
+    if stale > 0 {
+        println!("{} {} review(s) were written against earlier guidance and are now stale - list them with vql -stale {}", 
+            "NOTE:".cyan().bold(),
+            stale,
+            key);
+    }
+    
+    Ok(())
+}
+
+/// List every review written against older principle guidance: -stale [principles]
+fn list_stale_reviews(args: &[&str]) -> Result<()> {
+    // Find VQL storage
+    let (_, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    let (principles, filter) = AssetFilter::from_args(args)?;
+    let (principles, reviews) = ReviewFilter::from_args(&principles)?;
+    filter.check(&storage)?;
+    let selected = if principles.is_empty() {
+        storage.principles.keys().cloned().collect()
+    } else {
+        parse_principle_list(&principles.join(","), &storage)?
+    };
+    
+    let stale: Vec<_> = storage.stale_reviews().into_iter()
+        .filter(|(_, principle, _)| selected.iter().any(|s| s == principle))
+        .filter(|(asset, _, _)| filter.matches(&storage, &storage.asset_references[*asset]))
+        .filter(|(_, _, review)| reviews.matches(review))
+        .filter(|(asset, principle, _)| storage.asset_references[*asset].active_waiver(principle).is_none())
+        .collect();
+    
+    if stale.is_empty() {
+        println!("{} No stale reviews - every review matches its principle's current guidance", "SUCCESS:".green().bold());
+        return Ok(());
+    }
+    
+    println!("{} {} stale review(s):", "INFO:".blue().bold(), stale.len());
+    for principle_name in &selected {
+        let entries: Vec<_> = stale.iter().filter(|(_, p, _)| p == principle_name).collect();
+        if entries.is_empty() {
+            continue;
+        }
+        
+        let principle = &storage.principles[principle_name];
+        println!("\n  {} ({}) - guidance at revision {}:", 
+            principle_name.blue().bold(),
+            principle.long_name,
+            principle.revision);
+        
+        for (asset_name, _, review) in entries {
+            println!("    {:<12} {}  written for revision {} on {}", 
+                asset_name,
+                review.rating.as_deref()
+                    .map(|rating| get_rating_display(storage.rating_scale_for(principle_name), rating))
+                    .unwrap_or_else(|| "Not rated".normal()),
+                review.principle_revision.unwrap_or(1),
+                review.last_modified);
+        }
+    }
+    
+    println!("\nRe-review with {} and store the results to clear them.", ":[asset].rv([principle])".blue());
+    
+    Ok(())
+}
+
+/// List current reviews with who wrote them: -reviews [principles] [tag:name] [@context] [author:name] [source:llm] [model:id] [min-confidence:0.7]
+fn list_reviews(args: &[&str]) -> Result<()> {
+    // Find VQL storage
+    let (_, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    let (principles, filter) = AssetFilter::from_args(args)?;
+    let (principles, reviews) = ReviewFilter::from_args(&principles)?;
+    filter.check(&storage)?;
+    let selected = if principles.is_empty() {
+        storage.principles.keys().cloned().collect()
+    } else {
+        parse_principle_list(&principles.join(","), &storage)?
+    };
+    
+    let mut matching = Vec::new();
+    for (asset_name, asset) in &storage.asset_references {
+        if !filter.matches(&storage, asset) {
+            continue;
+        }
+        for (principle, review) in &asset.principle_reviews {
+            if selected.contains(principle) && reviews.matches(review) {
+                matching.push((asset_name, principle, review));
+            }
+        }
+    }
+    
+    let described: Vec<String> = [filter.describe(), reviews.describe()].into_iter()
+        .filter(|d| !d.is_empty())
+        .collect();
+    if matching.is_empty() {
+        println!("{} No reviews match{}", "INFO:".blue().bold(),
+            if described.is_empty() { String::new() } else { format!(" {}", described.join(" ")) });
+        return Ok(());
+    }
+    
+    println!("{} {} review(s){}:", "INFO:".blue().bold(), matching.len(),
+        if described.is_empty() { String::new() } else { format!(" matching {}", described.join(" ")) });
+    for (asset_name, principle, review) in matching {
+        println!("  {:<12} {:<10} {:<8} {}  {}", 
+            asset_name.blue().bold(),
+            principle,
+            review.rating.as_deref()
+                .map(|rating| get_rating_display(storage.rating_scale_for(principle), rating))
+                .unwrap_or_else(|| "Not rated".normal()),
+            review.last_modified,
+            attribution::describe(review).unwrap_or_else(|| "unattributed".to_string()).dimmed());
+    }
+    
+    Ok(())
+}
+
 /// Load principles from markdown file
 fn load_principles_from_md(file_path: &str) -> Result<()> {
     // Find VQL storage 
@@ -1704,43 +2826,79 @@ fn load_principles_from_md(file_path: &str) -> Result<()> {
     let reader = BufReader::new(file);
     let mut lines = reader.lines();
     
-    // Pattern to match principle headers - e.g. "# Architecture Principles (a)"
-    let header_regex = Regex::new(r"^# (.*) \(([a-z])\)$").unwrap();
+    // Pattern to match principle headers - e.g. "# Architecture Principles (a)" or, with aliases,
+    // "# Security Principles (s, sec)"
+    let header_regex = Regex::new(r"^# (.*) \(([a-zA-Z0-9_]+(?:\s*,\s*[a-zA-Z0-9_]+)*)\)$").unwrap();
+    
+    // Sub-principle headers within a principle - e.g. "## DRY (Don't Repeat Yourself)" under (a) is a.dry
+    let sub_header_regex = Regex::new(r"^## (.+)$").unwrap();
     
-    // Keep track of principles we've found
-    let mut principles_added = 0;
-    let mut current_principle: Option<(String, String, String)> = None;
-    let mut current_content = String::new();
+    // Principles in file order, each followed by its sub-principles: (short name, long name, guidance).
+    // A principle's guidance keeps its whole section, sub-sections included.
+    let mut sections: Vec<(String, String, String)> = Vec::new();
+    let mut aliases: Vec<(String, String)> = Vec::new();
+    let mut current_principle: Option<usize> = None;
+    let mut current_sub_principle: Option<usize> = None;
     
     // Process file line by line
     while let Some(Ok(line)) = lines.next() {
         // Check if this is a header line
         if let Some(captures) = header_regex.captures(&line) {
-            // If we were processing a principle, save it
-            if let Some((short_name, long_name, content)) = current_principle.take() {
-                storage.add_principle(&short_name, &long_name, Some(&content))?;
-                principles_added += 1;
-            }
-            
-            // Start tracking a new principle
+            // Start tracking a new principle; any names after the first are aliases
             let long_name = captures.get(1).unwrap().as_str().to_string();
-            let short_name = captures.get(2).unwrap().as_str().to_string();
-            current_principle = Some((short_name, long_name, String::new()));
-            current_content.clear();
-        } else if let Some((_, _, ref mut content)) = current_principle {
-            // Add this line to the current principle's content
-            if !current_content.is_empty() {
-                current_content.push('\n');
+            let mut names = captures.get(2).unwrap().as_str().split(',').map(|n| n.trim().to_string());
+            let short_name = names.next().unwrap_or_default();
+            aliases.extend(names.map(|alias| (short_name.clone(), alias)));
+            sections.push((short_name, long_name, String::new()));
+            current_principle = Some(sections.len() - 1);
+            current_sub_principle = None;
+            continue;
+        }
+        
+        let Some(principle_index) = current_principle else {
+            continue;
+        };
+        
+        if let Some(captures) = sub_header_regex.captures(&line) {
+            // Start tracking a sub-principle named after its heading
+            let heading = captures.get(1).unwrap().as_str().trim().trim_end_matches(':').trim_end();
+            let parent = &sections[principle_index].0;
+            let mut name = sub_principle_name(heading);
+            if name.is_empty() {
+                // Headings without letters or digits to name them by, e.g. in another script, go by position
+                let prefix = format!("{}.", parent);
+                name = (sections.iter().filter(|(n, _, _)| n.starts_with(&prefix)).count() + 1).to_string();
             }
-            current_content.push_str(&line);
-            *content = current_content.clone();
+            let base_name = format!("{}.{}", parent, name);
+            let mut short_name = base_name.clone();
+            let mut suffix = 1;
+            while sections.iter().any(|(name, _, _)| *name == short_name) {
+                suffix += 1;
+                short_name = format!("{}_{}", base_name, suffix);
+            }
+            sections.push((short_name, heading.to_string(), String::new()));
+            current_sub_principle = Some(sections.len() - 1);
+        } else if let Some(sub_index) = current_sub_principle {
+            // Add this line to the current sub-principle's content
+            append_md_line(&mut sections[sub_index].2, &line);
         }
+        
+        // Add this line to the current principle's content
+        append_md_line(&mut sections[principle_index].2, &line);
+    }
+    
+    // Parents come before their sub-principles, so each one can be added in order
+    let principles_added = sections.len();
+    let mut revised: Vec<(String, u32)> = Vec::new();
+    for (short_name, long_name, content) in &sections {
+        save_principle_from_md(&mut storage, short_name, long_name, content, &mut revised)?;
     }
     
-    // Save the last principle if there is one
-    if let Some((short_name, long_name, content)) = current_principle {
-        storage.add_principle(&short_name, &long_name, Some(&content))?;
-        principles_added += 1;
+    // Aliases go last, once every principle they could clash with exists
+    for (short_name, alias) in &aliases {
+        if storage.resolve_principle(alias) != Some(short_name.as_str()) {
+            storage.add_principle_alias(short_name, alias)?;
+        }
     }
     
     // Save changes to storage
@@ -1751,6 +2909,60 @@ fn load_principles_from_md(file_path: &str) -> Result<()> {
         principles_added,
         expanded_path.blue());
     
+    // Principles already loaded whose guidance changed in the file
+    for (short_name, revision) in &revised {
+        let stale = storage.stale_reviews().iter().filter(|(_, p, _)| p == short_name).count();
+        println!("{} Guidance for {} changed (now revision {}); {} review(s) are stale", 
+            "NOTE:".cyan().bold(),
+            short_name.blue().bold(),
+            revision,
+            stale);
+    }
+    
+    Ok(())
+}
+
+/// Append a markdown line to a principle's guidance, dropping leading blank lines
+fn append_md_line(content: &mut String, line: &str) {
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content.push_str(line);
+}
+
+/// Sub-principle name for a markdown heading, e.g. "dry" for "DRY (Don't Repeat Yourself)"
+/// or "presentation_tier" for "Presentation Tier"
+///
+/// Empty when the heading has no ASCII letters or digits outside its parenthesised note.
+fn sub_principle_name(heading: &str) -> String {
+    let without_note = heading.split('(').next().unwrap_or(heading);
+    
+    let mut name = String::new();
+    for c in without_note.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c.to_ascii_lowercase());
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    
+    name.trim_end_matches('_').to_string()
+}
+
+/// Add a principle read from markdown, or update it if it already exists
+///
+/// Principles whose guidance changed are added to `revised` with their new revision.
+fn save_principle_from_md(storage: &mut JsonStorage, short_name: &str, long_name: &str, guidance: &str, revised: &mut Vec<(String, u32)>) -> Result<()> {
+    let Some(principle) = storage.principles.get(short_name) else {
+        return storage.add_principle(short_name, long_name, Some(guidance));
+    };
+    
+    let previous = principle.revision;
+    let revision = storage.update_principle(short_name, Some(long_name), Some(guidance))?;
+    if revision != previous {
+        revised.push((short_name.to_string(), revision));
+    }
+    
     Ok(())
 }
 
@@ -1773,26 +2985,87 @@ fn handle_interface_mode(mode: &str) -> Result<()> {
     Ok(())
 }
 
+/// The strings of a comma-separated list of quoted strings, e.g. `"a", "b"`
+fn quoted_list(list: &str) -> Vec<&str> {
+    let quoted_re = Regex::new(r#""([^"]*)""#).unwrap();
+    quoted_re.captures_iter(list).map(|c| c.get(1).unwrap().as_str()).collect()
+}
+
 /// Handle asset store command (LLM format with commas)
+///
+/// Quoted content may be followed by named arguments, as in
+/// `:uc.st(a, "Review, with commas", model=gpt-4o, findings=[...])`. They are only read after
+/// the closing quote, so the review itself can say anything. Unquoted content runs to the end.
 fn handle_asset_store(asset_name: &str, args: &str) -> Result<()> {
     // Parse args: principle, content (with commas, as per VQL Prompt file)
-    let parts: Vec<&str> = args.split(',').map(|s| s.trim()).collect();
-    
-    if parts.is_empty() {
-        return Err(anyhow!("Not enough arguments for asset store command"));
-    }
+    let (principle, content) = args.split_once(',').unwrap_or((args, ""));
+    let principle = principle.trim();
+    let content = content.trim();
     
-    let principle = parts[0];
-    
-    // Join all remaining parts together as the content (in case there are multiple commas)
-    let content = if parts.len() > 1 { 
-        parts[1..].join(", ") 
-    } else { 
-        String::new() 
+    let (content, named) = if content.starts_with('"') {
+        split_quoted_content(content)?
+    } else {
+        (content, Vec::new())
     };
     
+    // Named arguments attribute the review (author=dom, model=gpt-4o, confidence=0.8) or carry its findings
+    let mut attribution = Attribution::from_env(ReviewSource::Llm)?;
+    let mut findings = Vec::new();
+    for (field, value) in named {
+        if field == "findings" {
+            findings = parse_findings(value)?;
+        } else {
+            attribution.set(field, value)?;
+        }
+    }
+    
     // Store the review
-    store_asset_review(asset_name, principle, &content)
+    store_asset_review(asset_name, principle, content, &attribution, findings)
+}
+
+/// Split quoted review content from the named arguments after its closing quote
+///
+/// The closing quote is the first one followed by nothing but named arguments, so quotes
+/// inside the review are fine as long as what follows them does not look like arguments.
+fn split_quoted_content(text: &str) -> Result<(&str, Vec<(&str, &str)>)> {
+    let mut error = None;
+    for (close, _) in text.match_indices('"').skip(1) {
+        let (content, rest) = text.split_at(close + 1);
+        match named_args(rest) {
+            Ok(named) => return Ok((content, named)),
+            Err(e) => error = Some(e),
+        }
+    }
+    
+    Err(error.unwrap_or_else(|| anyhow!("Unclosed quote in review content")))
+}
+
+/// Named arguments such as `, author=dom, confidence=0.8, findings=[...]`
+fn named_args(mut rest: &str) -> Result<Vec<(&str, &str)>> {
+    let mut named = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return Ok(named);
+        }
+        
+        let Some((field, value)) = rest.strip_prefix(',').and_then(|arg| arg.split_once('=')) else {
+            return Err(anyhow!("Unexpected text after the review: {}", rest));
+        };
+        let field = field.trim();
+        if field == "findings" {
+            // A findings payload is JSON with commas of its own
+            let (json, after) = split_json_prefix(value)?;
+            named.push((field, json));
+            rest = after;
+        } else if ATTRIBUTION_FIELDS.contains(&field) {
+            let end = value.find(',').unwrap_or(value.len());
+            named.push((field, value[..end].trim()));
+            rest = &value[end..];
+        } else {
+            return Err(anyhow!("Unknown argument '{}' after the review: use {} or findings", field, ATTRIBUTION_FIELDS.join(", ")));
+        }
+    }
 }
 
 /// Parse and validate a comma-separated list of principles
@@ -1812,14 +3085,15 @@ fn parse_principle_list(args: &str, storage: &JsonStorage) -> Result<Vec<String>
     let mut seen = std::collections::HashSet::new();
     let mut unique_principles = Vec::new();
     
-    for principle in requested {
-        // Validate that the principle exists
-        if !storage.principles.contains_key(principle) {
-            return Err(anyhow!("Unknown principle: '{}'. Available principles: {}", 
-                principle,
+    for requested_name in requested {
+        // Validate that the principle exists, resolving aliases to short names
+        let principle = match storage.resolve_principle(requested_name) {
+            Some(principle) => principle,
+            None => return Err(anyhow!("Unknown principle: '{}'. Available principles: {}", 
+                requested_name,
                 storage.principles.keys().cloned().collect::<Vec<_>>().join(", ")
-            ));
-        }
+            )),
+        };
         
         // Add only if not seen before
         if seen.insert(principle.to_string()) {
@@ -1834,10 +3108,22 @@ fn parse_principle_list(args: &str, storage: &JsonStorage) -> Result<Vec<String>
     Ok(unique_principles)
 }
 
+/// Ratings to ask for in review instructions, e.g. "H/M/L" or "s: H/M/L, u: pass/fail"
+fn rating_choices(storage: &JsonStorage, principles: &[String]) -> String {
+    let choices: Vec<(&str, String)> = principles.iter()
+        .map(|p| (p.as_str(), storage.rating_scale_for(p).choices()))
+        .collect();
+    
+    match choices.first() {
+        Some((_, first)) if choices.iter().all(|(_, c)| c == first) => first.clone(),
+        _ => choices.iter().map(|(p, c)| format!("{}: {}", p, c)).collect::<Vec<_>>().join(", "),
+    }
+}
+
 /// Handle asset review command (LLM-only)
 fn handle_asset_review(asset_name: &str, args: &str) -> Result<()> {
     // Load storage to validate asset and principles
-    let (_vql_dir, storage) = find_vql_storage()
+    let (vql_dir, storage) = find_vql_storage()
         .context("Failed to find or load VQL storage")?;
     
     // Validate asset exists
@@ -1855,12 +3141,12 @@ fn handle_asset_review(asset_name: &str, args: &str) -> Result<()> {
     let asset = &storage.asset_references[asset_name];
     
     println!("LLM Review Request:");
-    println!("Asset: {} ({})", asset_name, asset.path);
+    println!("Asset: {} ({})", asset_name, asset.paths().join(", "));
     println!("Principles to review: {}", principles.join(", "));
     
     // Return review instructions
     println!("\nReview Instructions:");
-    println!("1. Read asset from: {}", asset.path);
+    print_read_asset_step(&vql_dir, asset)?;
     println!("2. Review for principles: {}", principles.join(", "));
     println!("3. For each principle:");
     for principle in &principles {
@@ -1872,9 +3158,57 @@ fn handle_asset_review(asset_name: &str, args: &str) -> Result<()> {
             );
         }
     }
-    println!("4. Rate each principle (H/M/L)");
+    println!("4. Rate each principle ({})", rating_choices(&storage, &principles));
     println!("5. Provide detailed analysis");
     println!("6. Store results using :{}.st({}, \"Review with rating...\")", asset_name, principles[0]);
+    println!("   Record specific problems as findings: :{}.st({}, \"Review with rating...\", findings=[{{\"file\": \"path\", \"lines\": \"40-62\", \"severity\": \"high\", \"description\": \"...\", \"fix\": \"...\"}}])", asset_name, principles[0]);
+    
+    if print_open_findings(asset, &principles, "Open findings from earlier reviews (they stay open until resolved - do not record them again):") > 0 {
+        println!("Resolve any that are no longer present with :{}.resolve(id)", asset_name);
+    }
+    
+    Ok(())
+}
+
+/// Print the open findings of an asset's current reviews for some principles (all when empty), returning how many
+fn print_open_findings(asset: &AssetReference, principles: &[String], heading: &str) -> usize {
+    let open: Vec<_> = asset.findings().into_iter()
+        .filter(|(principle, finding)| finding.is_open() && (principles.is_empty() || principles.iter().any(|p| p == principle)))
+        .collect();
+    if open.is_empty() {
+        return 0;
+    }
+    
+    println!("\n{}", heading);
+    let mut current_principle = "";
+    for (principle, finding) in &open {
+        if *principle != current_principle {
+            println!("  {}:", principle);
+            current_principle = principle;
+        }
+        crate::commands::findings::print_finding(finding, "    ");
+    }
+    
+    open.len()
+}
+
+/// First review/refactor instruction: read the asset, listing every file when it is more than one
+fn print_read_asset_step(vql_dir: &Path, asset: &AssetReference) -> Result<()> {
+    let project_root = asset_paths::project_root(vql_dir);
+    if asset.extra_paths.is_empty() && asset_paths::resolve(&project_root, &asset.path).is_file() {
+        println!("1. Read asset from: {}", asset.path);
+        return Ok(());
+    }
+    
+    let files = asset_paths::asset_files(&project_root, asset)?;
+    if files.is_empty() {
+        return Err(anyhow!("Asset {} matches no files: {}", asset.short_name, asset.paths().join(", ")));
+    }
+    
+    println!("1. Read all {} files of the asset:", files.len());
+    for file in &files {
+        println!("   - {}", asset_paths::display(&project_root, file));
+    }
     
     Ok(())
 }
@@ -1882,7 +3216,7 @@ fn handle_asset_review(asset_name: &str, args: &str) -> Result<()> {
 /// Handle asset refactor command (LLM-only)
 fn handle_asset_refactor(asset_name: &str, args: &str) -> Result<()> {
     // Load storage to validate asset and principles
-    let (_vql_dir, storage) = find_vql_storage()
+    let (vql_dir, storage) = find_vql_storage()
         .context("Failed to find or load VQL storage")?;
     
     // Parse the refactor arguments - could be principles, or principles + reference assets
@@ -1897,7 +3231,7 @@ fn handle_asset_refactor(asset_name: &str, args: &str) -> Result<()> {
         
         for part in parts {
             // Check if this is an asset reference (exists in storage)
-            if !found_reference && (part == "-pr" || storage.principles.contains_key(part)) {
+            if !found_reference && (part == "-pr" || storage.resolve_principle(part).is_some()) {
                 principle_parts.push(part);
             } else {
                 found_reference = true;
@@ -1947,13 +3281,13 @@ fn handle_asset_refactor(asset_name: &str, args: &str) -> Result<()> {
     let asset = &storage.asset_references[asset_name];
     
     println!("LLM Refactor Request:");
-    println!("Asset: {} ({})", asset_name, asset.path);
+    println!("Asset: {} ({})", asset_name, asset.paths().join(", "));
     
     if let Some(ref ref_assets) = reference_assets {
         println!("Using reference assets: {}", ref_assets.join(", "));
         for ref_asset in ref_assets {
             if let Some(ref_data) = storage.asset_references.get(ref_asset) {
-                println!("  - {} ({})", ref_asset, ref_data.path);
+                println!("  - {} ({})", ref_asset, ref_data.paths().join(", "));
             }
         }
         if !principles.is_empty() {
@@ -1965,7 +3299,7 @@ fn handle_asset_refactor(asset_name: &str, args: &str) -> Result<()> {
     
     // Return refactor instructions
     println!("\nRefactor Instructions:");
-    println!("1. Read asset from: {}", asset.path);
+    print_read_asset_step(&vql_dir, asset)?;
     
     if reference_assets.is_some() {
         println!("2. Read reference assets and analyze their patterns");
@@ -1988,6 +3322,10 @@ fn handle_asset_refactor(asset_name: &str, args: &str) -> Result<()> {
     println!("5. MANDATORY: Review refactored code and update all reviews");
     println!("6. Store updated reviews with 'After refactoring:' prefix");
     
+    if print_open_findings(asset, &principles, "Open findings to fix:") > 0 {
+        println!("7. Resolve the findings the refactoring fixed with :{}.resolve(id[, \"how it was fixed\"])", asset_name);
+    }
+    
     Ok(())
 }
 
@@ -2019,27 +3357,76 @@ fn handle_asset_set_compliance(asset_name: &str, args: &str) -> Result<()> {
     let rating = parts[1];
     
     // Set compliance rating
-    set_asset_compliance(&[asset_name, principle, rating])
+    set_asset_compliance(&[asset_name, principle, rating], ReviewSource::Llm)
 }
 
 /// Handle global review command (LLM-only)
-fn handle_global_review(args: &str) -> Result<()> {
-    // Load storage to validate principles
-    let (_vql_dir, storage) = find_vql_storage()
-        .context("Failed to find or load VQL storage")?;
+/// Split global review/refactor arguments into principles and the assets to work on
+///
+/// Besides principles the list can name assets to work on instead of all of them, hold
+/// `tag:` and `@context` filters, and say `downstream` to add every asset that depends on the selection.
+/// Also returns notes describing how the assets were selected.
+fn select_global_targets(args: &str, storage: &JsonStorage) -> Result<(Vec<String>, Vec<String>, Vec<String>)> {
+    let (args, filter) = AssetFilter::from_list(args)?;
+    filter.check(storage)?;
+    
+    let mut principle_args = Vec::new();
+    let mut named_assets = Vec::new();
+    let mut downstream = false;
+    for arg in args.split(',').map(|a| a.trim()).filter(|a| !a.is_empty()) {
+        if arg == "downstream" {
+            downstream = true;
+        } else if storage.asset_references.contains_key(arg) {
+            named_assets.push(arg);
+        } else {
+            principle_args.push(arg);
+        }
+    }
     
-    // Parse and validate principles
-    let principles = parse_principle_list(args, &storage)?;
+    let principles = parse_principle_list(&principle_args.join(","), storage)?;
     
-    // Get all assets
-    let assets: Vec<String> = storage.asset_references.keys().cloned().collect();
+    // Named assets (or all of them) that match the tag filter
+    let mut assets: Vec<String> = storage.asset_references.iter()
+        .filter(|(name, asset)| (named_assets.is_empty() || named_assets.contains(&name.as_str())) && filter.matches(storage, asset))
+        .map(|(name, _)| name.clone())
+        .collect();
     
     if assets.is_empty() {
+        if !filter.is_empty() {
+            return Err(anyhow!("No assets match {}", filter.describe()));
+        }
         return Err(anyhow!("No assets found in the project"));
     }
     
+    let mut selection = Vec::new();
+    if !named_assets.is_empty() {
+        selection.push(format!("Selected assets: {}", named_assets.join(", ")));
+    }
+    if !filter.is_empty() {
+        selection.push(format!("Filter: {}", filter.describe()));
+    }
+    if downstream {
+        let selected = assets.len();
+        assets = storage.downstream_assets(&assets.iter().map(String::as_str).collect::<Vec<_>>());
+        selection.push(format!("Expanded downstream: {} dependent asset(s) added", assets.len() - selected));
+    }
+    
+    Ok((principles, assets, selection))
+}
+
+fn handle_global_review(args: &str) -> Result<()> {
+    // Load storage to validate principles
+    let (_vql_dir, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+    
+    // Parse and validate principles, and select the assets
+    let (principles, assets, selection) = select_global_targets(args, &storage)?;
+    
     println!("LLM Global Review Request:");
     println!("Total assets: {}", assets.len());
+    for note in &selection {
+        println!("{}", note);
+    }
     println!("Principles to review: {}", principles.join(", "));
     
     // Return review instructions
@@ -2047,11 +3434,11 @@ fn handle_global_review(args: &str) -> Result<()> {
     println!("1. Review all {} assets:", assets.len());
     for asset_name in &assets {
         if let Some(asset) = storage.asset_references.get(asset_name) {
-            println!("   - {} ({})", asset_name, asset.path);
+            println!("   - {} ({})", asset_name, asset.paths().join(", "));
         }
     }
     println!("2. For each asset, review principles: {}", principles.join(", "));
-    println!("3. Rate each principle (H/M/L)");
+    println!("3. Rate each principle ({})", rating_choices(&storage, &principles));
     println!("4. Provide detailed analysis");
     println!("5. Store results using :[asset].st([principle], \"Review with rating...\")");
     println!("\nTotal reviews to perform: {} assets × {} principles = {} reviews", 
@@ -2069,18 +3456,14 @@ fn handle_global_refactor(args: &str) -> Result<()> {
     let (_vql_dir, storage) = find_vql_storage()
         .context("Failed to find or load VQL storage")?;
     
-    // Parse and validate principles
-    let principles = parse_principle_list(args, &storage)?;
-    
-    // Get all assets
-    let assets: Vec<String> = storage.asset_references.keys().cloned().collect();
-    
-    if assets.is_empty() {
-        return Err(anyhow!("No assets found in the project"));
-    }
+    // Parse and validate principles, and select the assets
+    let (principles, assets, selection) = select_global_targets(args, &storage)?;
     
     println!("LLM Global Refactor Request:");
     println!("Total assets: {}", assets.len());
+    for note in &selection {
+        println!("{}", note);
+    }
     println!("Principles to refactor for: {}", principles.join(", "));
     
     // Return refactor instructions
@@ -2088,7 +3471,7 @@ fn handle_global_refactor(args: &str) -> Result<()> {
     println!("1. Process all {} assets:", assets.len());
     for asset_name in &assets {
         if let Some(asset) = storage.asset_references.get(asset_name) {
-            println!("   - {} ({})", asset_name, asset.path);
+            println!("   - {} ({})", asset_name, asset.paths().join(", "));
         }
     }
     println!("2. For each asset:");
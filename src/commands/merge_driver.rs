@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+use serde_json::Value;
+
+use crate::models::journal::JOURNAL_FILE_NAME;
+use crate::models::json_storage::JsonStorage;
+use crate::models::merge;
+use crate::utils::git;
+use crate::utils::lock::{write_atomic, LOCK_FILE_NAME};
+
+/// Name of the merge driver in git config and .gitattributes
+const DRIVER_NAME: &str = "vql";
+
+/// Storage files under VQL/ handled by the merge driver
+const MERGED_PATTERNS: [&str; 6] = [
+    "vql_storage.json",
+    "vql_manifest.json",
+    "principles/*.json",
+    "entities/*.json",
+    "asset_types/*.json",
+    "assets/*.json",
+];
+
+/// Files under VQL/ that belong to one clone and are kept out of version control
+const LOCAL_FILES: [&str; 2] = [JOURNAL_FILE_NAME, LOCK_FILE_NAME];
+
+/// Merge %O (base) and %B (theirs) into %A (ours), as git expects from a merge driver
+///
+/// Returns whether the merge was clean. The merged storage is written to `ours` either way,
+/// holding our side wherever the two sides conflict. `path` is the file's name in the
+/// repository (%P), used in messages instead of git's temporary file name.
+pub fn run_merge_driver(base: &str, ours: &str, theirs: &str, path: Option<&str>) -> Result<bool> {
+    let display_name = path.unwrap_or(ours);
+
+    let base_doc = read_side(base)?;
+    let ours_doc = read_side(ours)?
+        .ok_or_else(|| anyhow!("Our version of the file is empty: {}", ours))?;
+    let theirs_doc = read_side(theirs)?
+        .ok_or_else(|| anyhow!("Their version of the file is empty: {}", theirs))?;
+
+    let whole_storage = ours_doc.get("asset_references").is_some();
+    let outcome = merge::merge_documents(base_doc, ours_doc, theirs_doc)?;
+
+    // Write in the same format VQL itself saves, so the next save does not reformat the file
+    let content = if whole_storage {
+        let storage: JsonStorage = serde_json::from_value(outcome.merged.clone())
+            .context("Merged VQL storage is not valid")?;
+        serde_json::to_string_pretty(&storage)?
+    } else {
+        let mut content = serde_json::to_string_pretty(&outcome.merged)?;
+        content.push('\n');
+        content
+    };
+
+    write_atomic(Path::new(ours), content.as_bytes())
+        .context(format!("Failed to write merged VQL storage to {}", ours))?;
+
+    for note in &outcome.notes {
+        println!("{} {}", "NOTE:".cyan().bold(), note);
+    }
+
+    if outcome.is_clean() {
+        println!("{} Merged VQL storage {}", "SUCCESS:".green().bold(), display_name);
+    } else {
+        println!("{} {} conflict(s) merging VQL storage {}:",
+            "CONFLICT:".red().bold(),
+            outcome.conflicts.len(),
+            display_name);
+        for conflict in &outcome.conflicts {
+            println!("  - {}", conflict);
+        }
+        println!("Edit the file to resolve them, then mark it resolved with git add.");
+    }
+
+    Ok(outcome.is_clean())
+}
+
+/// Read one side of the merge; an empty file (e.g. no common ancestor) is `None`
+fn read_side(path: &str) -> Result<Option<Value>> {
+    let content = fs::read_to_string(path)
+        .context(format!("Failed to read {}", path))?;
+
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+
+    serde_json::from_str(&content)
+        .map(Some)
+        .context(format!("Failed to parse {} as VQL storage", path))
+}
+
+/// Register the merge driver in the local git config and in .gitattributes
+pub fn install_merge_driver() -> Result<()> {
+    let repo_root = PathBuf::from(git::run(Path::new("."), &["rev-parse", "--show-toplevel"])
+        .context("Not inside a git repository")?);
+    let repo_root = fs::canonicalize(&repo_root).unwrap_or(repo_root);
+
+    let vql_dir = find_vql_dir()?;
+    let relative = vql_dir.strip_prefix(&repo_root)
+        .map_err(|_| anyhow!("VQL directory {} is outside the git repository {}", vql_dir.display(), repo_root.display()))?;
+
+    git::run(Path::new("."), &["config", "--local", &format!("merge.{}.name", DRIVER_NAME), "VQL semantic storage merge"])?;
+    git::run(Path::new("."), &["config", "--local", &format!("merge.{}.driver", DRIVER_NAME), "vql merge-driver %O %A %B %P"])?;
+
+    println!("{} Registered merge driver '{}' in the local git config",
+        "SUCCESS:".green().bold(),
+        DRIVER_NAME);
+
+    // Patterns are anchored to the repository root, with forward slashes on every platform
+    let prefix = relative.components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let attributes_path = repo_root.join(".gitattributes");
+    let lines: Vec<String> = MERGED_PATTERNS.iter()
+        .map(|pattern| format!("/{}/{} merge={}", prefix, pattern, DRIVER_NAME))
+        .collect();
+    let added = append_missing_lines(&attributes_path, &lines)?;
+
+    if added > 0 {
+        println!("{} Added {} VQL storage pattern(s) to {}",
+            "SUCCESS:".green().bold(),
+            added,
+            attributes_path.display().to_string().blue());
+    } else {
+        println!("{} {} already routes VQL storage to the merge driver",
+            "INFO:".blue().bold(),
+            attributes_path.display().to_string().blue());
+    }
+
+    // The journal and lock are per-clone working state: merging them would replay undo steps
+    // recorded against another branch's storage, so they stay out of version control
+    let ignore_path = repo_root.join(".gitignore");
+    let lines: Vec<String> = LOCAL_FILES.iter()
+        .map(|file| format!("/{}/{}", prefix, file))
+        .collect();
+    if append_missing_lines(&ignore_path, &lines)? > 0 {
+        println!("{} Added the VQL journal and lock file to {}",
+            "SUCCESS:".green().bold(),
+            ignore_path.display().to_string().blue());
+    }
+
+    for file in LOCAL_FILES {
+        let path = format!("{}/{}", prefix, file);
+        if git::run(&repo_root, &["ls-files", "--error-unmatch", &path]).is_ok() {
+            println!("{} {} is already committed; stop tracking it with {}",
+                "NOTE:".cyan().bold(),
+                path,
+                format!("git rm --cached {}", path).blue());
+        }
+    }
+
+    println!("{} Commit .gitattributes so everyone gets the driver; each clone still needs 'vql merge-driver --install' once for its git config, and 'vql' on the PATH.",
+        "NOTE:".cyan().bold());
+
+    Ok(())
+}
+
+/// Append each line not already in the file (created if missing); returns how many were added
+fn append_missing_lines(path: &Path, lines: &[String]) -> Result<usize> {
+    let mut content = if path.exists() {
+        fs::read_to_string(path)
+            .context(format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+
+    let mut added = 0;
+    for line in lines {
+        if content.lines().any(|existing| existing.trim() == line) {
+            continue;
+        }
+
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(line);
+        content.push('\n');
+        added += 1;
+    }
+
+    if added > 0 {
+        fs::write(path, &content)
+            .context(format!("Failed to write {}", path.display()))?;
+    }
+
+    Ok(added)
+}
+
+/// Find the VQL directory in the current directory or its ancestors
+fn find_vql_dir() -> Result<PathBuf> {
+    let mut search_dir = std::env::current_dir()
+        .context("Failed to get current directory")?;
+
+    loop {
+        let vql_dir = search_dir.join("VQL");
+        if vql_dir.is_dir() {
+            return Ok(fs::canonicalize(&vql_dir).unwrap_or(vql_dir));
+        }
+
+        if !search_dir.pop() {
+            return Err(anyhow!("VQL directory not found in current directory or ancestors"));
+        }
+    }
+}
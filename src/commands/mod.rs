@@ -8,4 +8,16 @@ pub mod export;
 pub mod check;
 pub mod set;
 pub mod vql;
-pub mod json_commands;
\ No newline at end of file
+pub mod json_commands;
+pub mod import_legacy;
+pub mod merge_driver;
+pub mod journal;
+pub mod batch;
+pub mod doctor;
+pub mod score;
+pub mod scale;
+pub mod tags;
+pub mod deps;
+pub mod findings;
+pub mod waivers;
+pub mod discover;
\ No newline at end of file
@@ -0,0 +1,139 @@
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+
+use crate::models::json_storage::{find_vql_storage, JsonStorage};
+use crate::models::rating::{RatingScale, DEFAULT_SCALE_NAME};
+
+/// Rating scale commands: -scale, -scale -add, -scale -remove, -scale -default
+pub fn run_scale(args: &[&str]) -> Result<()> {
+    match args {
+        [] => list_scales(),
+        ["-add", name, levels @ ..] if !levels.is_empty() => add_scale(name, levels),
+        ["-remove", name] => remove_scale(name),
+        ["-default", name] => set_default_scale(name),
+        _ => Err(anyhow!("Usage: vql -scale | -scale -add name value[:label[:colour]]... | -scale -remove name | -scale -default name")),
+    }
+}
+
+/// Set the rating scale of a principle: -pr -scale s pass_fail ("default" to inherit again)
+pub fn set_principle_scale(principle: &str, scale: &str) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let scale = Some(scale).filter(|s| *s != "default");
+    storage.set_principle_rating_scale(principle, scale)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    println!("{} Principle {} now uses rating scale {}",
+        "SUCCESS:".green().bold(),
+        principle.blue().bold(),
+        storage.rating_scale_name_for(principle).blue());
+
+    note_off_scale_ratings(&storage);
+
+    Ok(())
+}
+
+/// List the built-in and project scales with the principles that use them
+fn list_scales() -> Result<()> {
+    // Find VQL storage
+    let (_, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let default_name = storage.default_rating_scale.as_deref().unwrap_or(DEFAULT_SCALE_NAME);
+
+    println!("{}", "Rating scales (best level first):".bold());
+
+    let mut names: Vec<&str> = vec![DEFAULT_SCALE_NAME];
+    names.extend(storage.rating_scales.keys().map(String::as_str).filter(|n| *n != DEFAULT_SCALE_NAME));
+
+    for name in names {
+        let Some(scale) = storage.find_rating_scale(name) else { continue };
+
+        let marker = if name == default_name { " (default)".green().to_string() } else { String::new() };
+        println!("  {}{}: {}", name.blue().bold(), marker, scale.describe());
+
+        let principles: Vec<&str> = storage.principles.keys()
+            .map(String::as_str)
+            .filter(|p| storage.rating_scale_name_for(p) == name)
+            .collect();
+        if !principles.is_empty() {
+            println!("    {}", format!("used by {}", principles.join(", ")).dimmed());
+        }
+    }
+
+    Ok(())
+}
+
+/// Add a project rating scale from level specs, best first
+fn add_scale(name: &str, levels: &[&str]) -> Result<()> {
+    let scale = RatingScale::from_specs(levels)?;
+
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    storage.add_rating_scale(name, scale.clone())?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    println!("{} Added rating scale {}: {}",
+        "SUCCESS:".green().bold(),
+        name.blue().bold(),
+        scale.describe());
+
+    Ok(())
+}
+
+/// Remove a project rating scale
+fn remove_scale(name: &str) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    storage.remove_rating_scale(name)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    println!("{} Removed rating scale {}", "SUCCESS:".green().bold(), name.blue().bold());
+
+    Ok(())
+}
+
+/// Set the scale used by principles that do not choose their own
+fn set_default_scale(name: &str) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    storage.set_default_rating_scale(Some(name))?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    println!("{} Default rating scale is now {}", "SUCCESS:".green().bold(), name.blue().bold());
+
+    note_off_scale_ratings(&storage);
+
+    Ok(())
+}
+
+/// Point out existing ratings that a scale change has left off their principle's scale
+fn note_off_scale_ratings(storage: &JsonStorage) {
+    let off_scale = storage.off_scale_ratings();
+    if off_scale.is_empty() {
+        return;
+    }
+
+    println!("{} {} review(s) have ratings that are not on their principle's scale - re-rate them with vql -sc:",
+        "NOTE:".cyan().bold(),
+        off_scale.len());
+    for (asset, principle, rating) in off_scale {
+        println!("  {} {} (rated {})", asset, principle, rating);
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+
+use crate::models::json_storage::{find_vql_storage, JsonStorage};
+use crate::models::score::{self, Score};
+use crate::models::filter::{AssetFilter, ReviewFilter};
+
+/// Show weighted quality scores: -score [tag:name] [@context] for the whole project or part of it, -score <asset> for one asset
+///
+/// Review filters such as `author:dom` or `min-confidence:0.7` score only the ratings of matching reviews.
+pub fn run_score(args: &[&str]) -> Result<()> {
+    // Find VQL storage
+    let (_, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let (args, filter) = AssetFilter::from_args(args)?;
+    let (args, reviews) = ReviewFilter::from_args(&args)?;
+    filter.check(&storage)?;
+    match args.as_slice() {
+        [] => show_project_score(&storage, &filter, &reviews),
+        [asset_name] if filter.is_empty() => show_asset_score(&storage, asset_name, &reviews),
+        _ => Err(anyhow!("Too many arguments. Usage: vql -score [asset | tag:name | @context] [author:name | source:cli|mcp|llm | model:id | min-confidence:0.7]")),
+    }
+}
+
+/// Project total with breakdowns by principle, entity, asset type and asset
+fn show_project_score(storage: &JsonStorage, filter: &AssetFilter, reviews: &ReviewFilter) -> Result<()> {
+    let project = score::score_project(storage, filter, reviews);
+
+    let Some(total) = project.total.percent() else {
+        println!("{} No rated reviews yet - nothing to score", "INFO:".blue().bold());
+        return Ok(());
+    };
+
+    let rated = project.assets.iter().filter(|a| a.total.possible > 0.0).count();
+    let title = match (filter.is_empty(), reviews.is_empty()) {
+        (true, true) => "Project quality score:".to_string(),
+        (false, true) => format!("Quality score for {}:", filter.describe()),
+        (true, false) => format!("Quality score from reviews matching {}:", reviews.describe()),
+        (false, false) => format!("Quality score for {} from reviews matching {}:", filter.describe(), reviews.describe()),
+    };
+    println!("{} {}  ({} of {} assets rated)",
+        title.bold(),
+        format_percent(Some(total)),
+        rated,
+        project.assets.len());
+
+    println!("\n{}", "By principle:".bold());
+    for (name, principle_score) in &project.by_principle {
+        let principle = &storage.principles[*name];
+        println!("  {:<12} {:<8} {}  {}",
+            name.blue().bold(),
+            format!("weight {}", principle.effective_weight()),
+            format_percent(principle_score.percent()),
+            principle.long_name);
+    }
+
+    print_group("By entity:", &project.by_entity, |name| storage.entities.get(name).map(|e| e.description.clone()));
+    print_group("By asset type:", &project.by_asset_type, |name| storage.asset_types.get(name).map(|t| t.description.clone()));
+
+    // Lowest scores first, where attention is needed
+    let mut assets: Vec<_> = project.assets.iter().filter(|a| a.total.possible > 0.0).collect();
+    assets.sort_by(|a, b| a.total.percent().partial_cmp(&b.total.percent()).unwrap_or(std::cmp::Ordering::Equal));
+
+    println!("\n{}", "Assets (lowest first):".bold());
+    for asset in assets {
+        let principles: Vec<String> = asset.principles.iter()
+            .map(|(name, s)| format!("{} {}", name, format_percent(s.percent())))
+            .collect();
+        println!("  {:<12} {}  {}", asset.asset.blue().bold(), format_percent(asset.total.percent()), principles.join(", "));
+    }
+
+    if rated < project.assets.len() {
+        println!("{} {} asset(s) have no rated reviews and are not scored",
+            "NOTE:".cyan().bold(),
+            project.assets.len() - rated);
+    }
+
+    // Waived ratings are left out; expired waivers count again and need attention
+    let waivers: Vec<_> = storage.waivers().into_iter()
+        .filter(|(asset, _, _)| filter.matches(storage, &storage.asset_references[*asset]))
+        .collect();
+    let expired = waivers.iter().filter(|(_, _, waiver)| !waiver.is_active()).count();
+    if waivers.len() > expired {
+        println!("{} {} waived rating(s) left out - see {}", "NOTE:".cyan().bold(), waivers.len() - expired, "vql -waivers".blue());
+    }
+    if expired > 0 {
+        println!("{} {} waiver(s) have expired and their ratings count again - see {}", "NOTE:".cyan().bold(), expired, "vql -waivers -expired".blue());
+    }
+
+    Ok(())
+}
+
+/// One asset's score with each principle's weight and contribution
+fn show_asset_score(storage: &JsonStorage, asset_name: &str, reviews: &ReviewFilter) -> Result<()> {
+    let asset = storage.asset_references.get(asset_name)
+        .ok_or_else(|| anyhow!("Asset {} not found", asset_name))?;
+
+    let asset_score = score::score_asset(storage, asset_name, asset, reviews);
+
+    println!("{} {} {}",
+        "Quality score for".bold(),
+        asset_name.blue().bold(),
+        format_percent(asset_score.total.percent()));
+
+    for principle in storage.principles.values().filter(|p| p.parent().is_none()) {
+        let rating = asset.principle_reviews.get(&principle.short_name).filter(|r| reviews.matches(r)).and_then(|r| r.rating.as_deref());
+        let source = match (asset.active_waiver(&principle.short_name), rating) {
+            (Some(waiver), _) => format!("waived until {}", waiver.expires).cyan().to_string(),
+            (None, Some(rating)) => format!("rated {}", rating),
+            (None, None) if asset_score.principles.contains_key(principle.short_name.as_str()) => "weighted average of sub-principles".to_string(),
+            (None, None) => "not rated".dimmed().to_string(),
+        };
+
+        println!("  {:<12} {:<8} {}  {}",
+            principle.short_name.blue().bold(),
+            format!("weight {}", principle.effective_weight()),
+            format_percent(asset_score.principles.get(principle.short_name.as_str()).and_then(Score::percent)),
+            source);
+    }
+
+    Ok(())
+}
+
+/// Print scores grouped by entity or asset type
+fn print_group(title: &str, scores: &BTreeMap<&str, Score>, describe: impl Fn(&str) -> Option<String>) {
+    println!("\n{}", title.bold());
+    for (name, group_score) in scores {
+        println!("  {:<12} {}  {}",
+            name.blue().bold(),
+            format_percent(group_score.percent()),
+            describe(name).unwrap_or_default());
+    }
+}
+
+/// A percentage coloured like the rating it is closest to, or "-" when unscored
+fn format_percent(percent: Option<f64>) -> String {
+    match percent {
+        Some(p) if p >= 83.3 => format!("{:>5.1}%", p).green().bold().to_string(),
+        Some(p) if p >= 50.0 => format!("{:>5.1}%", p).yellow().bold().to_string(),
+        Some(p) => format!("{:>5.1}%", p).red().bold().to_string(),
+        None => format!("{:>6}", "-").dimmed().to_string(),
+    }
+}
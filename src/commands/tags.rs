@@ -0,0 +1,100 @@
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+
+use crate::models::json_storage::{find_vql_storage, AssetReference};
+
+/// Add tags to an asset: -ar -tag uc critical legacy
+pub fn tag_asset(asset_name: &str, tags: &[&str]) -> Result<()> {
+    if tags.is_empty() {
+        return Err(anyhow!("No tags given. Usage: -ar -tag asset tag [tag...]"));
+    }
+
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let added = storage.tag_asset(asset_name, tags)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    if added.is_empty() {
+        println!("{} Asset {} already has those tags", "INFO:".blue().bold(), asset_name.blue().bold());
+    } else {
+        println!("{} Tagged {} with {}",
+            "SUCCESS:".green().bold(),
+            asset_name.blue().bold(),
+            added.join(", "));
+    }
+    print_tags(&storage.asset_references[asset_name]);
+
+    Ok(())
+}
+
+/// Remove tags from an asset: -ar -untag uc legacy
+pub fn untag_asset(asset_name: &str, tags: &[&str]) -> Result<()> {
+    if tags.is_empty() {
+        return Err(anyhow!("No tags given. Usage: -ar -untag asset tag [tag...]"));
+    }
+
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let removed = storage.untag_asset(asset_name, tags)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    if removed.is_empty() {
+        println!("{} Asset {} has none of those tags", "INFO:".blue().bold(), asset_name.blue().bold());
+    } else {
+        println!("{} Removed {} from {}",
+            "SUCCESS:".green().bold(),
+            removed.join(", "),
+            asset_name.blue().bold());
+    }
+    print_tags(&storage.asset_references[asset_name]);
+
+    Ok(())
+}
+
+/// Set or remove (with an empty value) a metadata key on an asset: -ar -meta uc owner=payments-team
+pub fn set_asset_metadata(asset_name: &str, key: &str, value: &str) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let value = Some(value.trim()).filter(|v| !v.is_empty());
+    storage.set_asset_metadata(asset_name, key.trim(), value)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    match value {
+        Some(value) => println!("{} Set {} {} = {}",
+            "SUCCESS:".green().bold(),
+            asset_name.blue().bold(),
+            key.trim(),
+            value),
+        None => println!("{} Removed {} from {}",
+            "SUCCESS:".green().bold(),
+            key.trim(),
+            asset_name.blue().bold()),
+    }
+
+    Ok(())
+}
+
+/// An asset's tags for listings, e.g. "#critical #legacy"
+pub fn format_tags(asset: &AssetReference) -> String {
+    asset.tags.iter().map(|tag| format!("#{}", tag)).collect::<Vec<_>>().join(" ")
+}
+
+fn print_tags(asset: &AssetReference) {
+    if asset.tags.is_empty() {
+        println!("  Tags: {}", "none".dimmed());
+    } else {
+        println!("  Tags: {}", format_tags(asset).cyan());
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::{Result, Context, anyhow};
+use colored::Colorize;
+
+use crate::models::attribution::AUTHOR_ENV;
+use crate::models::json_storage::{find_vql_storage, AssetReference};
+use crate::models::waiver::Waiver;
+
+/// Accept an asset's rating for a principle until a date:
+/// -waive uc a 2026-12-31 [-author dom] justification / :uc.waive(a, 2026-12-31, "justification"[, author=dom])
+pub fn waive(asset_name: &str, args: &[&str]) -> Result<()> {
+    let usage = "Usage: -waive asset principle YYYY-MM-DD [-author name] justification";
+    let [principle, expires, rest @ ..] = args else {
+        return Err(anyhow!("Not enough arguments. {}", usage));
+    };
+
+    // The author defaults to VQL_AUTHOR, like review attribution
+    let (author, justification) = match rest {
+        ["-author", author, justification @ ..] => (Some(author.to_string()), justification),
+        _ => (std::env::var(AUTHOR_ENV).ok().filter(|a| !a.trim().is_empty()), rest),
+    };
+    if justification.is_empty() {
+        return Err(anyhow!("A waiver needs a justification. {}", usage));
+    }
+
+    let waiver = Waiver::new(&justification.join(" "), author.as_deref(), expires)?;
+
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    storage.waive(asset_name, principle, waiver)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    println!("{} Waived {} principle for asset {} until {}",
+        "SUCCESS:".green().bold(),
+        principle,
+        asset_name.blue().bold(),
+        expires.trim());
+    println!("{} The rating is left out of scores and checks until then, and flagged once the waiver expires",
+        "NOTE:".cyan().bold());
+
+    Ok(())
+}
+
+/// Remove a waiver: -unwaive uc a / :uc.unwaive(a)
+pub fn unwaive(asset_name: &str, principle: &str) -> Result<()> {
+    // Find VQL storage
+    let (vql_dir, mut storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    storage.unwaive(asset_name, principle)?;
+
+    // Save changes
+    storage.save(&vql_dir)?;
+
+    println!("{} Removed the waiver for {} principle on asset {}", "SUCCESS:".green().bold(), principle, asset_name.blue().bold());
+
+    Ok(())
+}
+
+/// List every waiver, expired ones first: -waivers [-expired]
+pub fn list_waivers(args: &[&str]) -> Result<()> {
+    let expired_only = match args {
+        [] => false,
+        ["-expired"] => true,
+        _ => return Err(anyhow!("Usage: vql -waivers [-expired]")),
+    };
+
+    // Find VQL storage
+    let (_, storage) = find_vql_storage()
+        .context("Failed to find or load VQL storage")?;
+
+    let mut waivers = storage.waivers();
+    waivers.retain(|(_, _, waiver)| !expired_only || !waiver.is_active());
+    waivers.sort_by_key(|(_, _, waiver)| waiver.is_active());
+
+    if waivers.is_empty() {
+        println!("{} No {}waivers", "INFO:".blue().bold(), if expired_only { "expired " } else { "" });
+        return Ok(());
+    }
+
+    let expired = waivers.iter().filter(|(_, _, waiver)| !waiver.is_active()).count();
+    println!("{} {} waiver(s), {} expired:", "INFO:".blue().bold(), waivers.len(), expired);
+    for (asset_name, principle, waiver) in waivers {
+        let rating = storage.asset_references[asset_name].principle_reviews.get(principle)
+            .and_then(|review| review.rating.clone())
+            .unwrap_or_else(|| "-".to_string());
+        println!("  {:<12} {:<10} {:<6} {}", asset_name.blue().bold(), principle, rating, describe_waiver(waiver));
+    }
+
+    if expired > 0 {
+        println!("\nRenew an expired waiver with {} or remove it with {}",
+            "-waive asset principle YYYY-MM-DD justification".blue(),
+            "-unwaive asset principle".blue());
+    }
+
+    Ok(())
+}
+
+/// Print the waiver for a principle of an asset, if it has one
+pub fn print_waiver(asset: &AssetReference, principle: &str, indent: &str) {
+    if let Some(waiver) = asset.waivers.get(principle) {
+        println!("{}Waiver: {}", indent, describe_waiver(waiver));
+    }
+}
+
+/// A waiver for display, e.g. "Legacy adapter (dom, until 2026-12-31)", flagged when expired
+pub fn describe_waiver(waiver: &Waiver) -> String {
+    let status = if waiver.is_active() {
+        format!("until {}", waiver.expires).cyan()
+    } else {
+        format!("EXPIRED {} - the rating counts again", waiver.expires).yellow().bold()
+    };
+
+    format!("{} ({}{})",
+        waiver.justification,
+        waiver.author.as_ref().map(|author| format!("{}, ", author)).unwrap_or_default(),
+        status)
+}
@@ -32,6 +32,25 @@ enum Commands {
         /// Optional path to initialize VQL (defaults to current directory)
         #[clap(long)]
         path: Option<String>,
+    },
+    
+    /// Git merge driver for VQL storage files: vql merge-driver %O %A %B [%P]
+    MergeDriver {
+        /// Common ancestor version (%O)
+        base: Option<String>,
+        
+        /// Our version, replaced with the merge result (%A)
+        ours: Option<String>,
+        
+        /// Their version (%B)
+        theirs: Option<String>,
+        
+        /// Path of the file in the repository, for messages (%P)
+        path: Option<String>,
+        
+        /// Register the driver in .gitattributes and the local git config
+        #[clap(long)]
+        install: bool,
     }
 }
 
@@ -44,7 +63,14 @@ fn main() -> Result<()> {
         let command = cli.args.join(" ");
         
         // Process the command with our json_commands handler
-        return commands::json_commands::process_command(&command);
+        let result = commands::json_commands::process_command(&command);
+        
+        // A failed check ends with its own exit status, so CI can tell stale reviews from missing files
+        if let Some(failure) = result.as_ref().err().and_then(|e| e.downcast_ref::<models::check::CheckFailure>()) {
+            eprintln!("Error: {}", failure);
+            std::process::exit(failure.code);
+        }
+        return result;
     }
     
     // Process formal subcommands if provided
@@ -62,6 +88,22 @@ fn main() -> Result<()> {
                     "setup".to_string()
                 };
                 commands::json_commands::process_command(&path_arg)
+            },
+            Commands::MergeDriver { base, ours, theirs, path, install } => {
+                if *install {
+                    return commands::merge_driver::install_merge_driver();
+                }
+                
+                match (base, ours, theirs) {
+                    (Some(base), Some(ours), Some(theirs)) => {
+                        // Git treats a non-zero exit status as an unresolved conflict
+                        if !commands::merge_driver::run_merge_driver(base, ours, theirs, path.as_deref())? {
+                            std::process::exit(1);
+                        }
+                        Ok(())
+                    },
+                    _ => Err(anyhow::anyhow!("Usage: vql merge-driver %O %A %B, or vql merge-driver --install")),
+                }
             }
         }
     } else {
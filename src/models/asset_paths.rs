@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+
+use crate::models::json_storage::AssetReference;
+use crate::utils::filesystem;
+
+/// Project root asset paths are relative to: the VQL directory's parent
+pub fn project_root(vql_dir: &Path) -> PathBuf {
+    let vql_dir_abs = fs::canonicalize(vql_dir).unwrap_or_else(|_| vql_dir.to_path_buf());
+    vql_dir_abs.parent().unwrap_or(Path::new(".")).to_path_buf()
+}
+
+/// Resolve an asset path the way `-ar -add` does: relative to the project root unless absolute
+pub fn resolve(project_root: &Path, path: &str) -> PathBuf {
+    if path.starts_with('/') {
+        PathBuf::from(path)
+    } else {
+        project_root.join(path)
+    }
+}
+
+/// Whether an asset path is a glob pattern such as `src/user/*.service.ts`
+pub fn is_pattern(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+/// Files one asset path stands for: the file itself, every file under a directory,
+/// or every file a glob pattern matches, sorted
+pub fn matched_files(project_root: &Path, path: &str) -> Result<Vec<PathBuf>> {
+    let resolved = resolve(project_root, path);
+
+    let pattern = if is_pattern(path) {
+        resolved.to_string_lossy().into_owned()
+    } else if resolved.is_dir() {
+        resolved.join("**").join("*").to_string_lossy().into_owned()
+    } else if resolved.is_file() {
+        return Ok(vec![resolved]);
+    } else {
+        return Ok(Vec::new());
+    };
+
+    let mut files: Vec<PathBuf> = glob::glob(&pattern)
+        .map_err(|e| anyhow!("Invalid glob pattern '{}': {}", path, e))?
+        .filter_map(|entry| entry.ok())
+        .filter(|file| file.is_file())
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
+
+/// Every file that makes up an asset across all of its paths, sorted and without duplicates
+pub fn asset_files(project_root: &Path, asset: &AssetReference) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in asset.paths() {
+        files.extend(matched_files(project_root, path)?);
+    }
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+/// Hash of every file that makes up an asset, or None when it matches no files
+///
+/// A single file hashes like `filesystem::get_file_hash`. Several files hash their paths and
+/// hashes together, so adding, removing or renaming one of them changes the hash too.
+pub fn content_hash(project_root: &Path, asset: &AssetReference) -> Result<Option<String>> {
+    let files = asset_files(project_root, asset)?;
+    match files.as_slice() {
+        [] => Ok(None),
+        [file] => Ok(Some(filesystem::get_file_hash(file)?)),
+        _ => {
+            let mut listing = String::new();
+            for file in &files {
+                listing.push_str(&format!("{}:{}\n", display(project_root, file), filesystem::get_file_hash(file)?));
+            }
+            Ok(Some(format!("{:x}", md5::compute(listing))))
+        },
+    }
+}
+
+/// Paths of an asset that stand for no files: missing files and directories, or patterns that match nothing
+pub fn missing_paths<'a>(project_root: &Path, asset: &'a AssetReference) -> Result<Vec<&'a str>> {
+    let mut missing = Vec::new();
+    for path in asset.paths() {
+        if matched_files(project_root, path)?.is_empty() && !resolve(project_root, path).is_dir() {
+            missing.push(path);
+        }
+    }
+
+    Ok(missing)
+}
+
+/// Check that each path names an existing file or directory, or is a glob that matches at least one file
+pub fn validate_paths(project_root: &Path, paths: &[&str]) -> Result<()> {
+    if paths.is_empty() {
+        return Err(anyhow!("An asset needs at least one path"));
+    }
+
+    for path in paths {
+        if is_pattern(path) {
+            if matched_files(project_root, path)?.is_empty() {
+                return Err(anyhow!("Pattern matches no files: {}. Patterns are resolved relative to the VQL directory's parent.", path));
+            }
+        } else if !resolve(project_root, path).exists() {
+            return Err(anyhow!("File not found: {}. The file or directory must exist to be added as an asset reference. Path is resolved relative to the VQL directory's parent.", path));
+        }
+    }
+
+    Ok(())
+}
+
+/// A file for display, relative to the project root where possible
+pub fn display(project_root: &Path, file: &Path) -> String {
+    file.strip_prefix(project_root).unwrap_or(file).to_string_lossy().into_owned()
+}
@@ -0,0 +1,147 @@
+use std::fmt;
+use std::str::FromStr;
+use anyhow::{Result, anyhow};
+use serde::{Serialize, Deserialize};
+
+use crate::models::json_storage::Review;
+
+/// Environment variable naming who stores reviews when no author is given
+pub const AUTHOR_ENV: &str = "VQL_AUTHOR";
+
+/// Environment variable overriding where reviews come from (cli, mcp or llm)
+pub const SOURCE_ENV: &str = "VQL_SOURCE";
+
+/// Environment variable naming the AI model or session that writes reviews
+pub const MODEL_ENV: &str = "VQL_MODEL";
+
+/// Attribution fields that can be set on a review, as named in flags and `:uc.st(...)` arguments
+pub const ATTRIBUTION_FIELDS: [&str; 4] = ["author", "source", "model", "confidence"];
+
+/// Interface a review was stored through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewSource {
+    /// Typed at the command line (`vql -st`, `vql -sc`)
+    Cli,
+    /// Stored by the MCP server
+    Mcp,
+    /// Stored through LLM syntax (`:uc.st(...)`)
+    Llm,
+}
+
+impl fmt::Display for ReviewSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewSource::Cli => write!(f, "cli"),
+            ReviewSource::Mcp => write!(f, "mcp"),
+            ReviewSource::Llm => write!(f, "llm"),
+        }
+    }
+}
+
+impl FromStr for ReviewSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "cli" => Ok(ReviewSource::Cli),
+            "mcp" => Ok(ReviewSource::Mcp),
+            "llm" => Ok(ReviewSource::Llm),
+            _ => Err(anyhow!("Invalid review source '{}': use cli, mcp or llm", s.trim())),
+        }
+    }
+}
+
+/// Who wrote a review, through which interface and with which model, and how confident it was
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Attribution {
+    pub author: Option<String>,
+    pub source: Option<ReviewSource>,
+    pub model: Option<String>,
+    pub confidence: Option<f64>,
+}
+
+impl Attribution {
+    /// Attribution for a review stored through `source`, with defaults from VQL_AUTHOR, VQL_SOURCE and VQL_MODEL
+    pub fn from_env(source: ReviewSource) -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+
+        Ok(Attribution {
+            author: var(AUTHOR_ENV),
+            source: Some(match var(SOURCE_ENV) {
+                Some(value) => value.parse()?,
+                None => source,
+            }),
+            model: var(MODEL_ENV),
+            confidence: None,
+        })
+    }
+
+    /// Set one field by name (author, source, model or confidence) from a flag or named argument
+    pub fn set(&mut self, field: &str, value: &str) -> Result<()> {
+        let value = value.trim().trim_matches('"').trim();
+        if value.is_empty() {
+            return Err(anyhow!("No value given for {}", field));
+        }
+
+        match field {
+            "author" => self.author = Some(value.to_string()),
+            "source" => self.source = Some(value.parse()?),
+            "model" => self.model = Some(value.to_string()),
+            "confidence" => self.confidence = Some(parse_confidence(value)?),
+            _ => return Err(anyhow!("Unknown review attribute '{}': use {}", field, ATTRIBUTION_FIELDS.join(", "))),
+        }
+
+        Ok(())
+    }
+
+    /// Copy the attribution onto a review
+    pub fn apply(&self, review: &mut Review) {
+        review.author = self.author.clone();
+        review.source = self.source;
+        review.model = self.model.clone();
+        review.confidence = self.confidence;
+    }
+}
+
+/// Parse a confidence given as a fraction ("0.8") or a percentage ("80%")
+pub fn parse_confidence(value: &str) -> Result<f64> {
+    let value = value.trim();
+    let parsed = match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|p| p / 100.0),
+        None => value.parse::<f64>(),
+    };
+
+    match parsed {
+        Ok(confidence) if (0.0..=1.0).contains(&confidence) => Ok(confidence),
+        _ => Err(anyhow!("Invalid confidence '{}': use a number from 0 to 1 or a percentage, e.g. 0.8 or 80%", value)),
+    }
+}
+
+/// A review's attribution for display, e.g. "dom via llm (gpt-4o), confidence 80%", or None if it has none
+pub fn describe(review: &Review) -> Option<String> {
+    let mut text = review.author.clone().unwrap_or_default();
+
+    if let Some(source) = review.source {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(&format!("via {}", source));
+    }
+    if let Some(model) = &review.model {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(&format!("({})", model));
+    }
+    if let Some(confidence) = review.confidence {
+        if !text.is_empty() {
+            text.push_str(", ");
+        }
+        text.push_str(&format!("confidence {:.0}%", confidence * 100.0));
+    }
+
+    if text.is_empty() { None } else { Some(text) }
+}
@@ -0,0 +1,561 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use anyhow::{Result, Context};
+use rusqlite::{params, Connection};
+use serde_json::{Map, Value};
+
+use crate::models::json_storage::JsonStorage;
+use crate::models::layout::{self, StorageLayout, STORAGE_FILE_NAME};
+use crate::utils::lock::{StorageLock, write_atomic};
+
+/// Database file used by the SQLite backend
+pub const SQLITE_FILE_NAME: &str = "vql_storage.db";
+
+/// Where and how VQL storage is persisted
+///
+/// Backends exchange the storage as a JSON document so schema migrations apply
+/// the same way no matter where the data lives.
+pub trait StorageBackend: Debug + Send + Sync {
+    /// Name of the backend as shown to the user and accepted by `-layout`
+    fn name(&self) -> &'static str;
+
+    /// Human-readable location of the storage
+    fn location(&self) -> String;
+
+    /// VQL directory the storage lives in, if it lives on disk
+    fn directory(&self) -> Option<&Path>;
+
+    /// VQL directory asset paths resolve against, which may differ from where storage lives
+    ///
+    /// Unlike `directory`, this never decides what gets locked or journaled.
+    fn paths_directory(&self) -> Option<&Path> {
+        self.directory()
+    }
+
+    /// Read the raw storage document, which may still need migrating
+    fn load(&self) -> Result<String>;
+
+    /// Persist the storage, replacing what was stored before
+    fn save(&self, storage: &JsonStorage) -> Result<()>;
+
+    /// Remove everything this backend stored (used after converting to another backend)
+    fn remove(&self) -> Result<()>;
+
+    /// Take the lock guarding a load-modify-save cycle, if the backend needs one
+    fn lock(&self) -> Result<Option<StorageLock>> {
+        self.directory().map(StorageLock::acquire).transpose()
+    }
+}
+
+thread_local! {
+    /// Backend used instead of the VQL directory on this thread, if any
+    static THREAD_BACKEND: RefCell<Option<Arc<dyn StorageBackend>>> = const { RefCell::new(None) };
+}
+
+/// Route all storage access on the current thread to `backend` instead of the VQL directory
+///
+/// Pass `None` to go back to the VQL directory. Returns the previous override.
+pub fn set_thread_backend(backend: Option<Arc<dyn StorageBackend>>) -> Option<Arc<dyn StorageBackend>> {
+    THREAD_BACKEND.with(|current| current.replace(backend))
+}
+
+/// Backend overriding the VQL directory on the current thread, if any
+pub fn thread_backend() -> Option<Arc<dyn StorageBackend>> {
+    THREAD_BACKEND.with(|current| current.borrow().clone())
+}
+
+/// Find the backend holding storage in a VQL directory
+///
+/// A SQLite database wins over JSON files, and the sharded manifest over the single file,
+/// because conversions write the new backend before removing the old one.
+pub fn detect_backend(vql_dir: &Path) -> Option<Arc<dyn StorageBackend>> {
+    if vql_dir.join(SQLITE_FILE_NAME).is_file() {
+        return Some(Arc::new(SqliteBackend::new(vql_dir)));
+    }
+
+    StorageLayout::detect(vql_dir)
+        .map(|layout| Arc::new(JsonFileBackend::new(vql_dir, layout)) as Arc<dyn StorageBackend>)
+}
+
+/// Create a backend for a VQL directory by name (single, sharded or sqlite)
+pub fn backend_by_name(vql_dir: &Path, name: &str) -> Result<Arc<dyn StorageBackend>> {
+    match name.to_lowercase().as_str() {
+        "sqlite" => Ok(Arc::new(SqliteBackend::new(vql_dir))),
+        _ => {
+            let layout = StorageLayout::from_name(name)
+                .map_err(|_| anyhow::anyhow!("Unknown storage layout: {}. Use single, sharded or sqlite", name))?;
+            Ok(Arc::new(JsonFileBackend::new(vql_dir, layout)))
+        }
+    }
+}
+
+/// JSON files in the VQL directory, either as one file or sharded per item
+#[derive(Debug)]
+pub struct JsonFileBackend {
+    vql_dir: PathBuf,
+    layout: StorageLayout,
+}
+
+impl JsonFileBackend {
+    /// Create a JSON backend for a VQL directory
+    pub fn new(vql_dir: &Path, layout: StorageLayout) -> Self {
+        JsonFileBackend {
+            vql_dir: vql_dir.to_path_buf(),
+            layout,
+        }
+    }
+}
+
+impl StorageBackend for JsonFileBackend {
+    fn name(&self) -> &'static str {
+        self.layout.name()
+    }
+
+    fn location(&self) -> String {
+        self.vql_dir.display().to_string()
+    }
+
+    fn directory(&self) -> Option<&Path> {
+        Some(&self.vql_dir)
+    }
+
+    fn load(&self) -> Result<String> {
+        layout::read_document(&self.vql_dir, self.layout)
+    }
+
+    fn save(&self, storage: &JsonStorage) -> Result<()> {
+        match self.layout {
+            StorageLayout::Single => {
+                let json_file_path = self.vql_dir.join(STORAGE_FILE_NAME);
+
+                // Serialize with pretty printing
+                let json_content = serde_json::to_string_pretty(storage)
+                    .context("Failed to serialize VQL storage to JSON")?;
+
+                write_atomic(&json_file_path, json_content.as_bytes())
+                    .context(format!("Failed to write VQL storage to {}", json_file_path.display()))
+            },
+            StorageLayout::Sharded => {
+                let document = serde_json::to_value(storage)
+                    .context("Failed to serialize VQL storage to JSON")?;
+
+                layout::write_sharded(&self.vql_dir, &document)
+            },
+        }
+    }
+
+    fn remove(&self) -> Result<()> {
+        layout::remove_layout(&self.vql_dir, self.layout)
+    }
+}
+
+/// Item sections stored as rows of the SQLite `items` table
+const SQLITE_SECTIONS: [&str; 5] = ["commands", "principles", "entities", "asset_types", "asset_references"];
+
+/// A single-file SQLite database in the VQL directory
+///
+/// Every item is a row holding its JSON, and review history gets one row per review,
+/// so saving touches only the rows that changed.
+#[derive(Debug)]
+pub struct SqliteBackend {
+    vql_dir: PathBuf,
+    path: PathBuf,
+}
+
+impl SqliteBackend {
+    /// Create a SQLite backend for a VQL directory
+    pub fn new(vql_dir: &Path) -> Self {
+        SqliteBackend {
+            vql_dir: vql_dir.to_path_buf(),
+            path: vql_dir.join(SQLITE_FILE_NAME),
+        }
+    }
+
+    /// Open the database, creating the schema if needed
+    fn open(&self) -> Result<Connection> {
+        let conn = Connection::open(&self.path)
+            .context(format!("Failed to open VQL database at {}", self.path.display()))?;
+
+        conn.busy_timeout(std::time::Duration::from_secs(10))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS items (
+                kind TEXT NOT NULL,
+                name TEXT NOT NULL,
+                data TEXT NOT NULL,
+                PRIMARY KEY (kind, name)
+            );
+            CREATE TABLE IF NOT EXISTS review_history (
+                asset TEXT NOT NULL,
+                principle TEXT NOT NULL,
+                seq INTEGER NOT NULL,
+                data TEXT NOT NULL,
+                PRIMARY KEY (asset, principle, seq)
+            );"
+        ).context(format!("Failed to create VQL database schema in {}", self.path.display()))?;
+
+        Ok(conn)
+    }
+}
+
+impl StorageBackend for SqliteBackend {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn directory(&self) -> Option<&Path> {
+        Some(&self.vql_dir)
+    }
+
+    fn load(&self) -> Result<String> {
+        let conn = self.open()?;
+        let mut root = Map::new();
+
+        let mut stmt = conn.prepare("SELECT key, value FROM meta")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (key, value) = row?;
+            // Values are stored as JSON; older databases kept plain strings
+            let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+            root.insert(key, value);
+        }
+
+        for section in SQLITE_SECTIONS {
+            root.insert(section.to_string(), Value::Object(Map::new()));
+        }
+
+        let mut stmt = conn.prepare("SELECT kind, name, data FROM items ORDER BY kind, name")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?;
+        for row in rows {
+            let (kind, name, data) = row?;
+            let item: Value = serde_json::from_str(&data)
+                .context(format!("Corrupt {} row '{}' in {}", kind, name, self.path.display()))?;
+
+            if let Some(Value::Object(section)) = root.get_mut(&kind) {
+                section.insert(name, item);
+            }
+        }
+
+        let mut stmt = conn.prepare("SELECT asset, principle, data FROM review_history ORDER BY asset, principle, seq")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?;
+        for row in rows {
+            let (asset, principle, data) = row?;
+            let review: Value = serde_json::from_str(&data)
+                .context(format!("Corrupt review history row for {}/{} in {}", asset, principle, self.path.display()))?;
+
+            let history = root.get_mut("asset_references")
+                .and_then(|assets| assets.get_mut(&asset))
+                .and_then(|asset| asset.as_object_mut())
+                .map(|asset| asset.entry("review_history").or_insert_with(|| Value::Object(Map::new())))
+                .and_then(|history| history.as_object_mut());
+
+            if let Some(history) = history {
+                let entries = history.entry(principle).or_insert_with(|| Value::Array(Vec::new()));
+                if let Value::Array(entries) = entries {
+                    entries.push(review);
+                }
+            }
+        }
+
+        serde_json::to_string(&Value::Object(root))
+            .context("Failed to assemble VQL storage from the database")
+    }
+
+    fn save(&self, storage: &JsonStorage) -> Result<()> {
+        let mut document = serde_json::to_value(storage)
+            .context("Failed to serialize VQL storage to JSON")?;
+        let root = document.as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("VQL storage is not a JSON object"))?;
+
+        // Review history goes to its own table, one row per review
+        let mut history_rows: BTreeMap<(String, String, i64), String> = BTreeMap::new();
+        if let Some(Value::Object(assets)) = root.get_mut("asset_references") {
+            for (asset_name, asset) in assets.iter_mut() {
+                let history = match asset.as_object_mut().and_then(|a| a.remove("review_history")) {
+                    Some(Value::Object(history)) => history,
+                    _ => continue,
+                };
+
+                for (principle, entries) in history {
+                    for (seq, review) in entries.as_array().into_iter().flatten().enumerate() {
+                        history_rows.insert((asset_name.clone(), principle.clone(), seq as i64), review.to_string());
+                    }
+                }
+            }
+        }
+
+        let mut conn = self.open()?;
+        let tx = conn.transaction()?;
+
+        // Every top-level field that is not a section of rows lives in meta as JSON,
+        // so fields added to the schema are stored without changes here
+        tx.execute("DELETE FROM meta", [])?;
+        for (key, value) in root.iter().filter(|(key, _)| !SQLITE_SECTIONS.contains(&key.as_str())) {
+            tx.execute("INSERT INTO meta (key, value) VALUES (?1, ?2)", params![key, value.to_string()])?;
+        }
+
+        // Only write rows whose content changed, and delete rows of removed items
+        let mut existing: BTreeMap<(String, String), String> = BTreeMap::new();
+        {
+            let mut stmt = tx.prepare("SELECT kind, name, data FROM items")?;
+            let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?;
+            for row in rows {
+                let (kind, name, data) = row?;
+                existing.insert((kind, name), data);
+            }
+        }
+
+        for section in SQLITE_SECTIONS {
+            let empty = Map::new();
+            let items = root.get(section).and_then(|v| v.as_object()).unwrap_or(&empty);
+
+            for (name, item) in items {
+                let data = item.to_string();
+                if existing.remove(&(section.to_string(), name.clone())).as_deref() != Some(data.as_str()) {
+                    tx.execute("INSERT OR REPLACE INTO items (kind, name, data) VALUES (?1, ?2, ?3)", params![section, name, data])?;
+                }
+            }
+        }
+
+        for (kind, name) in existing.keys() {
+            tx.execute("DELETE FROM items WHERE kind = ?1 AND name = ?2", params![kind, name])?;
+        }
+
+        let mut existing_history: BTreeMap<(String, String, i64), String> = BTreeMap::new();
+        {
+            let mut stmt = tx.prepare("SELECT asset, principle, seq, data FROM review_history")?;
+            let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?, row.get::<_, String>(3)?)))?;
+            for row in rows {
+                let (asset, principle, seq, data) = row?;
+                existing_history.insert((asset, principle, seq), data);
+            }
+        }
+
+        for (key, data) in &history_rows {
+            if existing_history.remove(key).as_deref() != Some(data.as_str()) {
+                tx.execute("INSERT OR REPLACE INTO review_history (asset, principle, seq, data) VALUES (?1, ?2, ?3, ?4)",
+                    params![key.0, key.1, key.2, data])?;
+            }
+        }
+
+        for (asset, principle, seq) in existing_history.keys() {
+            tx.execute("DELETE FROM review_history WHERE asset = ?1 AND principle = ?2 AND seq = ?3", params![asset, principle, seq])?;
+        }
+
+        tx.commit()
+            .context(format!("Failed to write VQL database at {}", self.path.display()))
+    }
+
+    fn remove(&self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)
+                .context(format!("Failed to remove {}", self.path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Storage kept in memory only, for library users, tests and batch scripts
+///
+/// Starts empty; the first load creates fresh storage.
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    document: Mutex<Option<String>>,
+
+    /// VQL directory the document was taken from, used to resolve asset paths
+    vql_dir: Option<PathBuf>,
+}
+
+impl MemoryBackend {
+    /// Create an empty in-memory backend
+    #[cfg(test)]
+    pub fn new() -> Self {
+        MemoryBackend::default()
+    }
+
+    /// Create an in-memory backend holding an existing storage document
+    #[cfg(test)]
+    pub fn with_document(document: &str) -> Self {
+        MemoryBackend {
+            document: Mutex::new(Some(document.to_string())),
+            vql_dir: None,
+        }
+    }
+
+    /// Stage changes to storage from `vql_dir` in memory
+    ///
+    /// Commands see `vql_dir` as their VQL directory (asset paths resolve against it),
+    /// but nothing is locked, written or journaled there.
+    pub fn staging(vql_dir: &Path, document: &str) -> Self {
+        MemoryBackend {
+            document: Mutex::new(Some(document.to_string())),
+            vql_dir: Some(vql_dir.to_path_buf()),
+        }
+    }
+
+    /// The stored document as JSON, if anything has been saved
+    pub fn document(&self) -> Option<String> {
+        self.document.lock().ok().and_then(|doc| doc.clone())
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn location(&self) -> String {
+        match &self.vql_dir {
+            Some(vql_dir) => vql_dir.display().to_string(),
+            None => "<memory>".to_string(),
+        }
+    }
+
+    fn directory(&self) -> Option<&Path> {
+        None
+    }
+
+    fn paths_directory(&self) -> Option<&Path> {
+        self.vql_dir.as_deref()
+    }
+
+    fn load(&self) -> Result<String> {
+        match self.document() {
+            Some(document) => Ok(document),
+            None => serde_json::to_string(&JsonStorage::new())
+                .context("Failed to serialize VQL storage to JSON"),
+        }
+    }
+
+    fn save(&self, storage: &JsonStorage) -> Result<()> {
+        let json_content = serde_json::to_string_pretty(storage)
+            .context("Failed to serialize VQL storage to JSON")?;
+
+        let mut document = self.document.lock()
+            .map_err(|_| anyhow::anyhow!("In-memory VQL storage is poisoned"))?;
+        *document = Some(json_content);
+        Ok(())
+    }
+
+    fn remove(&self) -> Result<()> {
+        let mut document = self.document.lock()
+            .map_err(|_| anyhow::anyhow!("In-memory VQL storage is poisoned"))?;
+        *document = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::json_storage::find_vql_storage;
+    use crate::models::rating::RatingScale;
+
+    #[test]
+    fn memory_backend_round_trips_storage() {
+        let backend = MemoryBackend::new();
+        assert!(backend.document().is_none());
+
+        // An empty backend loads as fresh storage
+        let (mut storage, _) = JsonStorage::from_json(&backend.load().unwrap()).unwrap();
+        assert!(storage.principles.is_empty());
+
+        storage.add_principle("a", "Architecture", Some("Keep layers apart")).unwrap();
+        backend.save(&storage).unwrap();
+
+        let (loaded, report) = JsonStorage::from_json(&backend.load().unwrap()).unwrap();
+        assert!(!report.migrated());
+        assert_eq!(loaded.principles["a"].long_name, "Architecture");
+        assert_eq!(loaded.principles["a"].guidance.as_deref(), Some("Keep layers apart"));
+
+        backend.remove().unwrap();
+        assert!(backend.document().is_none());
+    }
+
+    #[test]
+    fn memory_backend_keeps_mutations_out_of_the_file_system() {
+        let mut original = JsonStorage::new();
+        original.add_entity("u", "User").unwrap();
+        let memory = Arc::new(MemoryBackend::with_document(&serde_json::to_string(&original).unwrap()));
+
+        let previous = set_thread_backend(Some(memory.clone()));
+        let result = find_vql_storage().and_then(|(vql_dir, mut storage)| {
+            assert!(storage.entities.contains_key("u"));
+            storage.add_entity("o", "Order")?;
+            storage.save(&vql_dir)
+        });
+        set_thread_backend(previous);
+        result.unwrap();
+
+        let (saved, _) = JsonStorage::from_json(&memory.document().unwrap()).unwrap();
+        assert_eq!(saved.entities["o"].description, "Order");
+        assert!(saved.entities.contains_key("u"));
+    }
+
+    /// Save `storage` to a fresh SQLite database and load it back
+    fn sqlite_round_trip(storage: &JsonStorage) -> JsonStorage {
+        let vql_dir = tempfile::tempdir().unwrap();
+        let backend = SqliteBackend::new(vql_dir.path());
+        backend.save(storage).unwrap();
+        JsonStorage::from_json(&backend.load().unwrap()).unwrap().0
+    }
+
+    #[test]
+    fn sqlite_backend_round_trips_storage() {
+        let mut storage = JsonStorage::new();
+        storage.add_principle("a", "Architecture", None).unwrap();
+        storage.add_entity("u", "User").unwrap();
+        storage.add_asset_type("c", "Controller").unwrap();
+        storage.add_asset_reference("uc", "u", "c", "uc.js").unwrap();
+
+        let loaded = sqlite_round_trip(&storage);
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&storage).unwrap());
+    }
+
+    #[test]
+    fn sqlite_backend_reads_plain_string_meta() {
+        let vql_dir = tempfile::tempdir().unwrap();
+        let backend = SqliteBackend::new(vql_dir.path());
+        backend.save(&JsonStorage::new()).unwrap();
+
+        // Databases written before meta held JSON stored strings as they were
+        let conn = backend.open().unwrap();
+        conn.execute("UPDATE meta SET value = 'a plain string' WHERE key = 'created'", []).unwrap();
+
+        let (loaded, _) = JsonStorage::from_json(&backend.load().unwrap()).unwrap();
+        assert_eq!(loaded.created, "a plain string");
+    }
+
+    #[test]
+    fn sqlite_backend_keeps_rating_scales() {
+        let mut storage = JsonStorage::new();
+        let five = RatingScale::from_specs(&["1", "2", "3", "4", "5"]).unwrap();
+        storage.add_rating_scale("five", five.clone()).unwrap();
+        storage.set_default_rating_scale(Some("five")).unwrap();
+
+        let loaded = sqlite_round_trip(&storage);
+        assert_eq!(loaded.find_rating_scale("five"), Some(&five));
+        assert_eq!(loaded.default_rating_scale.as_deref(), Some("five"));
+    }
+
+    #[test]
+    fn sqlite_backend_keeps_discovery_ignore_patterns() {
+        let mut storage = JsonStorage::new();
+        storage.set_discovery_ignore(&["vendor/*"]).unwrap();
+
+        let loaded = sqlite_round_trip(&storage);
+        assert_eq!(loaded.discovery_ignore, ["vendor/*"]);
+    }
+}
+
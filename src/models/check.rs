@@ -0,0 +1,325 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use anyhow::Result;
+
+use crate::models::asset_paths;
+use crate::models::filter::AssetFilter;
+use crate::models::json_storage::{JsonStorage, Review};
+use crate::utils::git::{self, Changes};
+
+/// Exit status of `vql -check` when reviews are stale but every asset's files exist
+pub const EXIT_STALE: i32 = 2;
+
+/// Exit status of `vql -check` when an asset's files are missing (whether or not reviews are stale)
+pub const EXIT_MISSING: i32 = 3;
+
+/// Why a review no longer describes its asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Staleness {
+    /// The asset's files have changed since the review was stored
+    ContentChanged,
+
+    /// The principle's guidance has been revised since the review was written
+    GuidanceRevised { written: u32, current: u32 },
+}
+
+impl fmt::Display for Staleness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Staleness::ContentChanged => write!(f, "files changed since the review"),
+            Staleness::GuidanceRevised { written, current } => write!(f, "written for guidance revision {}, now {}", written, current),
+        }
+    }
+}
+
+/// A review that no longer describes its asset, with every reason why
+#[derive(Debug, Clone)]
+pub struct StaleReview<'a> {
+    pub asset: &'a str,
+    pub principle: &'a str,
+    pub review: &'a Review,
+    pub reasons: Vec<Staleness>,
+}
+
+/// What `vql -check` found
+#[derive(Debug, Clone, Default)]
+pub struct CheckReport<'a> {
+    /// Assets with paths that stand for no files, and those paths
+    pub missing: Vec<(&'a str, Vec<&'a str>)>,
+
+    pub stale: Vec<StaleReview<'a>>,
+
+    /// Reviews stored before content hashes were recorded, whose freshness cannot be told
+    pub unhashed: usize,
+
+    /// Reviews left out because an active waiver covers them
+    pub waived: usize,
+
+    /// Assets and reviews looked at
+    pub assets: usize,
+    pub reviews: usize,
+}
+
+impl CheckReport<'_> {
+    /// Exit status for CI: 0 when everything is current, otherwise `EXIT_MISSING` or `EXIT_STALE`
+    pub fn exit_code(&self) -> i32 {
+        if !self.missing.is_empty() {
+            EXIT_MISSING
+        } else if !self.stale.is_empty() {
+            EXIT_STALE
+        } else {
+            0
+        }
+    }
+}
+
+/// Check the selected assets' files and current reviews for the selected principles (all when empty)
+pub fn check_storage<'a>(storage: &'a JsonStorage, project_root: &Path, filter: &AssetFilter, principles: &[String]) -> Result<CheckReport<'a>> {
+    let mut report = CheckReport::default();
+
+    for (asset_name, asset) in storage.asset_references.iter().filter(|(_, asset)| filter.matches(storage, asset)) {
+        report.assets += 1;
+
+        let missing = asset_paths::missing_paths(project_root, asset)?;
+        if !missing.is_empty() {
+            report.missing.push((asset_name.as_str(), missing));
+        }
+        let current_hash = asset_paths::content_hash(project_root, asset)?;
+
+        for (principle_name, review) in &asset.principle_reviews {
+            if !principles.is_empty() && !principles.contains(principle_name) {
+                continue;
+            }
+            report.reviews += 1;
+
+            if asset.active_waiver(principle_name).is_some() {
+                report.waived += 1;
+                continue;
+            }
+
+            let mut reasons = Vec::new();
+            match (&review.content_hash, &current_hash) {
+                (Some(reviewed), Some(current)) if reviewed != current => reasons.push(Staleness::ContentChanged),
+                (None, _) => report.unhashed += 1,
+                _ => {},
+            }
+            if let Some(principle) = storage.principles.get(principle_name).filter(|p| p.is_stale(review)) {
+                reasons.push(Staleness::GuidanceRevised {
+                    written: review.principle_revision.unwrap_or(1),
+                    current: principle.revision,
+                });
+            }
+
+            if !reasons.is_empty() {
+                report.stale.push(StaleReview {
+                    asset: asset_name,
+                    principle: principle_name,
+                    review,
+                    reasons,
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Current reviews of one asset stored at the same commit, and how much its paths changed since
+#[derive(Debug, Clone)]
+pub struct ReviewChanges<'a> {
+    pub asset: &'a str,
+    pub commit: &'a str,
+    pub principles: Vec<&'a str>,
+
+    /// When the oldest of the reviews was stored
+    pub reviewed: &'a str,
+
+    pub changes: Changes,
+}
+
+/// What `vql -changed-since-review` found
+#[derive(Debug, Clone, Default)]
+pub struct ChangesReport<'a> {
+    /// Reviews whose asset changed since their commit, most changed lines first
+    pub changed: Vec<ReviewChanges<'a>>,
+
+    /// Reviews whose asset is untouched since their commit
+    pub unchanged: usize,
+
+    /// Reviews stored without a commit, whose changes cannot be told
+    pub untracked: usize,
+
+    /// Reviews left out because an active waiver covers them
+    pub waived: usize,
+
+    /// Assets with reviews git could not compare, and why (for example a commit lost to a rebase)
+    pub unavailable: Vec<(&'a str, String)>,
+}
+
+/// Ask git how much each selected asset changed since the commits its current reviews were stored at
+///
+/// `project_root` must be inside a git working tree. Reviews of an asset stored at the same commit
+/// are compared once and reported together.
+pub fn changes_since_reviews<'a>(storage: &'a JsonStorage, project_root: &Path, filter: &AssetFilter, principles: &[String]) -> ChangesReport<'a> {
+    let mut report = ChangesReport::default();
+
+    for (asset_name, asset) in storage.asset_references.iter().filter(|(_, asset)| filter.matches(storage, asset)) {
+        // Group the asset's reviews by the commit they were stored at
+        let mut by_commit: BTreeMap<&str, Vec<(&str, &Review)>> = BTreeMap::new();
+        for (principle_name, review) in &asset.principle_reviews {
+            if !principles.is_empty() && !principles.contains(principle_name) {
+                continue;
+            }
+            if asset.active_waiver(principle_name).is_some() {
+                report.waived += 1;
+                continue;
+            }
+            match &review.commit {
+                Some(commit) => by_commit.entry(commit.as_str()).or_default().push((principle_name, review)),
+                None => report.untracked += 1,
+            }
+        }
+
+        let paths = asset.paths();
+        for (commit, reviews) in by_commit {
+            if !git::has_commit(project_root, commit) {
+                report.unavailable.push((asset_name, format!("commit {} is not in the repository history", git::short(commit))));
+                continue;
+            }
+            let changes = match git::changes_since(project_root, commit, &paths) {
+                Ok(changes) => changes,
+                Err(e) => {
+                    report.unavailable.push((asset_name, e.to_string()));
+                    continue;
+                },
+            };
+
+            if changes.is_empty() {
+                report.unchanged += reviews.len();
+                continue;
+            }
+            report.changed.push(ReviewChanges {
+                asset: asset_name,
+                commit,
+                principles: reviews.iter().map(|(principle, _)| *principle).collect(),
+                reviewed: reviews.iter().map(|(_, review)| review.last_modified.as_str()).min().unwrap_or_default(),
+                changes,
+            });
+        }
+    }
+
+    report.changed.sort_by(|a, b| b.changes.lines().cmp(&a.changes.lines())
+        .then(b.changes.commits.cmp(&a.changes.commits))
+        .then(a.asset.cmp(b.asset)));
+
+    report
+}
+
+/// A failed check, carrying the exit status the process should end with
+#[derive(Debug)]
+pub struct CheckFailure {
+    pub code: i32,
+    pub message: String,
+}
+
+impl fmt::Display for CheckFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CheckFailure {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::models::attribution::Attribution;
+    use crate::models::waiver::Waiver;
+
+    /// Storage with `uc` reviewed against `a` and `s` as `uc.js` reads now, and `gone` pointing at no file
+    fn reviewed_storage(project_root: &Path) -> JsonStorage {
+        fs::write(project_root.join("uc.js"), "class UserController {}").unwrap();
+
+        let mut storage = JsonStorage::new();
+        storage.add_principle("a", "Architecture", Some("Keep controllers thin")).unwrap();
+        storage.add_principle("s", "Security", Some("Validate input")).unwrap();
+        storage.add_entity("u", "User").unwrap();
+        storage.add_asset_type("c", "Controller").unwrap();
+        storage.add_asset_reference("uc", "u", "c", "uc.js").unwrap();
+        storage.add_asset_reference("gone", "u", "c", "gone.js").unwrap();
+        for principle in ["a", "s"] {
+            storage.store_asset_review("uc", principle, Some("H"), "Fine", &Attribution::default(), Vec::new()).unwrap();
+        }
+
+        let hash = asset_paths::content_hash(project_root, &storage.asset_references["uc"]).unwrap();
+        for review in storage.asset_references.get_mut("uc").unwrap().principle_reviews.values_mut() {
+            review.content_hash = hash.clone();
+        }
+        storage
+    }
+
+    fn reasons<'a>(report: &'a CheckReport, principle: &str) -> &'a [Staleness] {
+        report.stale.iter().find(|stale| stale.principle == principle).map_or(&[], |stale| &stale.reasons)
+    }
+
+    #[test]
+    fn current_reviews_pass_the_check() {
+        let project = tempfile::tempdir().unwrap();
+        let mut storage = reviewed_storage(project.path());
+        storage.delete_asset_reference("gone").unwrap();
+
+        let report = check_storage(&storage, project.path(), &AssetFilter::default(), &[]).unwrap();
+        assert!(report.stale.is_empty());
+        assert_eq!((report.assets, report.reviews, report.unhashed), (1, 2, 0));
+        assert_eq!(report.exit_code(), 0);
+    }
+
+    #[test]
+    fn edited_files_and_revised_guidance_make_reviews_stale() {
+        let project = tempfile::tempdir().unwrap();
+        let mut storage = reviewed_storage(project.path());
+        storage.delete_asset_reference("gone").unwrap();
+
+        fs::write(project.path().join("uc.js"), "class UserController { save() {} }").unwrap();
+        storage.update_principle("s", None, Some("Validate and escape input")).unwrap();
+
+        let report = check_storage(&storage, project.path(), &AssetFilter::default(), &[]).unwrap();
+        assert_eq!(reasons(&report, "a"), [Staleness::ContentChanged]);
+        assert_eq!(reasons(&report, "s"), [Staleness::ContentChanged, Staleness::GuidanceRevised { written: 1, current: 2 }]);
+        assert_eq!(report.exit_code(), EXIT_STALE);
+
+        // Only the selected principles are checked
+        let report = check_storage(&storage, project.path(), &AssetFilter::default(), &["a".to_string()]).unwrap();
+        assert_eq!(report.reviews, 1);
+        assert_eq!(report.stale.len(), 1);
+    }
+
+    #[test]
+    fn missing_files_outrank_stale_reviews() {
+        let project = tempfile::tempdir().unwrap();
+        let storage = reviewed_storage(project.path());
+        fs::write(project.path().join("uc.js"), "").unwrap();
+
+        let report = check_storage(&storage, project.path(), &AssetFilter::default(), &[]).unwrap();
+        assert_eq!(report.missing, [("gone", vec!["gone.js"])]);
+        assert_eq!(report.stale.len(), 2);
+        assert_eq!(report.exit_code(), EXIT_MISSING);
+    }
+
+    #[test]
+    fn unhashed_and_waived_reviews_are_counted_not_reported() {
+        let project = tempfile::tempdir().unwrap();
+        let mut storage = reviewed_storage(project.path());
+        storage.delete_asset_reference("gone").unwrap();
+        storage.asset_references.get_mut("uc").unwrap().principle_reviews.get_mut("a").unwrap().content_hash = None;
+        storage.waive("uc", "s", Waiver::new("Legacy, rewrite planned", None, "2999-12-31").unwrap()).unwrap();
+        fs::write(project.path().join("uc.js"), "class UserController { save() {} }").unwrap();
+
+        let report = check_storage(&storage, project.path(), &AssetFilter::default(), &[]).unwrap();
+        assert!(report.stale.is_empty());
+        assert_eq!((report.unhashed, report.waived), (1, 1));
+        assert_eq!(report.exit_code(), 0);
+    }
+}
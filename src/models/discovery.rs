@@ -0,0 +1,549 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context, anyhow};
+use regex::Regex;
+
+use crate::models::asset_paths;
+use crate::models::config::VqlConfig;
+use crate::models::json_storage::JsonStorage;
+
+/// A filename or directory pattern that finds assets of one type
+///
+/// Patterns use glob syntax with `{a,b}` alternatives. A pattern without a slash matches a name
+/// in any directory (`*Controller.{js,ts}`), one with a slash matches the path from the project
+/// root (`server/models/*.js`, `**/services/*.ts`), and one ending in a slash matches directories
+/// (`client/components/*/`). The text the first `*` matches names the entity, so
+/// `UserController.ts` belongs to `User`.
+#[derive(Debug, Clone)]
+pub struct AssetPattern {
+    pub pattern: String,
+    regex: Regex,
+
+    /// Characters the pattern always matches literally, used to prefer the most specific pattern
+    literals: usize,
+    anchored: bool,
+    directories: bool,
+}
+
+impl AssetPattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let trimmed = pattern.trim().trim_start_matches("./").trim_start_matches('/');
+        let directories = trimmed.ends_with('/');
+        let body = trimmed.trim_end_matches('/');
+        if body.is_empty() {
+            return Err(anyhow!("Empty discovery pattern"));
+        }
+
+        let regex = Regex::new(&format!("^{}$", glob_to_regex(body)?))
+            .map_err(|e| anyhow!("Invalid discovery pattern '{}': {}", pattern, e))?;
+
+        Ok(AssetPattern {
+            pattern: pattern.trim().to_string(),
+            regex,
+            literals: literal_length(body)?,
+            anchored: body.contains('/'),
+            directories,
+        })
+    }
+
+    /// Whether the pattern matches a file or directory, given its path relative to the project root
+    pub fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        self.capture(relative, is_dir).is_some()
+    }
+
+    /// The entity name a match implies: the first non-empty `*` capture, or the file stem
+    pub fn entity_name(&self, relative: &str, is_dir: bool) -> Option<String> {
+        let captures = self.capture(relative, is_dir)?;
+        let captured = captures.iter().skip(1).flatten()
+            .map(|m| m.as_str())
+            .find(|name| !name.is_empty());
+
+        let name = captured.unwrap_or_else(|| {
+            let file_name = relative.rsplit('/').next().unwrap_or(relative);
+            file_name.split('.').next().unwrap_or(file_name)
+        });
+        Some(name.to_string()).filter(|name| !words(name).is_empty())
+    }
+
+    fn capture<'a>(&self, relative: &'a str, is_dir: bool) -> Option<regex::Captures<'a>> {
+        if is_dir != self.directories {
+            return None;
+        }
+        let subject = if self.anchored {
+            relative
+        } else {
+            relative.rsplit('/').next().unwrap_or(relative)
+        };
+        self.regex.captures(subject)
+    }
+}
+
+/// Translate a glob pattern into a regular expression, capturing what each `*` matches
+fn glob_to_regex(pattern: &str) -> Result<String> {
+    let mut regex = String::new();
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    // "**/" is any number of directories, including none
+                    chars.next();
+                    regex.push_str("(?:[^/]*/)*");
+                } else {
+                    regex.push_str(".*");
+                }
+            },
+            '*' => regex.push_str("([^/]*)"),
+            '?' => regex.push_str("[^/]"),
+            '{' => {
+                // Each alternative is a glob of its own, so `{*.js,*.ts}` works like `*.{js,ts}`
+                let alternatives = take_alternatives(&mut chars, pattern)?.iter()
+                    .map(|alternative| glob_to_regex(alternative))
+                    .collect::<Result<Vec<_>>>()?;
+                regex.push_str(&format!("(?:{})", alternatives.join("|")));
+            },
+            '[' => {
+                let mut class = String::from("[");
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    class.push('^');
+                }
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some('\\') => class.push_str("\\\\"),
+                        Some(c) => class.push(c),
+                        None => return Err(anyhow!("Unclosed '[' in discovery pattern '{}'", pattern)),
+                    }
+                }
+                class.push(']');
+                regex.push_str(&class);
+            },
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    Ok(regex)
+}
+
+/// Read the alternatives of a `{a,b}` group up to its closing brace, which may nest further groups
+fn take_alternatives(chars: &mut std::iter::Peekable<std::str::Chars>, pattern: &str) -> Result<Vec<String>> {
+    let mut alternatives = vec![String::new()];
+    let mut depth = 0;
+    loop {
+        match chars.next() {
+            Some('}') if depth == 0 => return Ok(alternatives),
+            Some(',') if depth == 0 => alternatives.push(String::new()),
+            Some(c) => {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {},
+                }
+                alternatives.last_mut().unwrap().push(c);
+            },
+            None => return Err(anyhow!("Unclosed '{{' in discovery pattern '{}'", pattern)),
+        }
+    }
+}
+
+/// Number of characters a pattern always matches literally, the shortest alternative of a `{a,b}` group counting
+///
+/// Patterns with more literal text are more specific: `*Controller.ts` beats `*.ts`.
+fn literal_length(pattern: &str) -> Result<usize> {
+    let mut length = 0;
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' | '?' => {},
+            '[' => {
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                }
+            },
+            '{' => {
+                length += take_alternatives(&mut chars, pattern)?.iter()
+                    .map(|alternative| literal_length(alternative))
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter()
+                    .min()
+                    .unwrap_or(0);
+            },
+            _ => length += 1,
+        }
+    }
+    Ok(length)
+}
+
+/// Words of a name, split at separators and where lower case turns to upper case
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            words.extend(Some(std::mem::take(&mut current)).filter(|w| !w.is_empty()));
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.push(c);
+    }
+    words.extend(Some(current).filter(|w| !w.is_empty()));
+
+    words
+}
+
+/// Initials of a name in lower case, e.g. "OrderItem" becomes "oi"
+fn initials(name: &str) -> String {
+    words(name).iter()
+        .filter_map(|w| w.chars().next())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// A name for comparing entities: lower case letters and digits only
+fn comparable(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Entity description for a discovered name, e.g. "user_profile" becomes "UserProfile"
+fn entity_description(name: &str) -> String {
+    words(name).iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect()
+}
+
+/// An asset `vql -discover` would register
+#[derive(Debug, Clone)]
+pub struct Proposal {
+    pub short_name: String,
+    pub entity: String,
+
+    /// Description of the entity when it does not exist yet and would be created
+    pub new_entity: Option<String>,
+
+    pub asset_type: String,
+
+    /// Path relative to the project root, as `-ar -add` takes it
+    pub path: String,
+
+    /// Pattern that matched the path
+    pub pattern: String,
+}
+
+/// What `vql -discover` found
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryPlan {
+    pub proposals: Vec<Proposal>,
+
+    /// Matching files and directories that already belong to an asset
+    pub tracked: usize,
+
+    /// Files looked at
+    pub scanned: usize,
+}
+
+/// Walk the project (or one directory of it) and propose an asset for every untracked file or
+/// directory that matches an asset type's patterns
+///
+/// When several patterns match, the most specific wins: the one with the most literal text,
+/// then the longest. Entities are matched to existing ones by short
+/// name or description, otherwise proposed with their initials as short name. Asset short names
+/// join the entity and asset type short names (`uc`), numbered when taken.
+pub fn plan(storage: &JsonStorage, vql_dir: &Path, within: Option<&Path>) -> Result<DiscoveryPlan> {
+    let project_root = asset_paths::project_root(vql_dir);
+
+    let mut patterns = Vec::new();
+    for (type_name, asset_type) in &storage.asset_types {
+        for pattern in &asset_type.patterns {
+            patterns.push((type_name.as_str(), AssetPattern::new(pattern)?));
+        }
+    }
+    patterns.sort_by_key(|(_, p)| std::cmp::Reverse((p.literals, p.pattern.len())));
+
+    let mut ignore = Vec::new();
+    for pattern in VqlConfig::default().ignore_patterns.iter().chain(&storage.discovery_ignore) {
+        ignore.push(Regex::new(&format!("^{}$", glob_to_regex(pattern.trim().trim_start_matches("./"))?))
+            .map_err(|e| anyhow!("Invalid ignore pattern '{}': {}", pattern, e))?);
+    }
+
+    // Files and directories that already belong to an asset
+    let mut tracked: BTreeSet<PathBuf> = BTreeSet::new();
+    for asset in storage.asset_references.values() {
+        tracked.extend(asset_paths::asset_files(&project_root, asset)?);
+        tracked.extend(asset.paths().iter().map(|path| asset_paths::resolve(&project_root, path)));
+    }
+
+    let start = match within {
+        Some(dir) => asset_paths::resolve(&project_root, &dir.to_string_lossy()),
+        None => project_root.clone(),
+    };
+    if !start.is_dir() {
+        return Err(anyhow!("Directory not found: {}", start.display()));
+    }
+
+    let mut walker = Walker {
+        project_root: &project_root,
+        vql_dir: fs::canonicalize(vql_dir).unwrap_or_else(|_| vql_dir.to_path_buf()),
+        ignore,
+        found: Vec::new(),
+        scanned: 0,
+    };
+    walker.walk(&start)?;
+
+    let mut plan = DiscoveryPlan { scanned: walker.scanned, ..DiscoveryPlan::default() };
+
+    // Untracked matches with the pattern and entity name of each
+    let mut matches = Vec::new();
+    for (path, is_dir) in walker.found {
+        let relative = asset_paths::display(&project_root, &path).replace('\\', "/");
+        let Some((type_name, pattern)) = patterns.iter().find(|(_, p)| p.matches(&relative, is_dir)) else {
+            continue;
+        };
+        if tracked.contains(&path) {
+            plan.tracked += 1;
+            continue;
+        }
+        if let Some(name) = pattern.entity_name(&relative, is_dir) {
+            matches.push((relative, *type_name, pattern, name));
+        }
+    }
+
+    // Asset names the matches will most likely get, which new entities should not take:
+    // "UserCard" must not become "uc" when UserController wants that name
+    let mut likely_assets: BTreeSet<String> = storage.entities.keys()
+        .flat_map(|entity| storage.asset_types.keys().map(move |t| format!("{}{}", entity, t)))
+        .collect();
+    likely_assets.extend(matches.iter().map(|(_, type_name, _, name)| format!("{}{}", initials(name), type_name)));
+
+    let mut taken: BTreeSet<String> = BTreeSet::new();
+    let mut new_entities: BTreeMap<String, String> = BTreeMap::new();
+
+    for (relative, type_name, pattern, name) in matches {
+        // Use an existing entity (or one already proposed) when the name matches, otherwise propose one
+        let key = comparable(&name);
+        let existing = storage.entities.values()
+            .find(|e| comparable(&e.short_name) == key || comparable(&e.description) == key)
+            .map(|e| e.short_name.clone());
+        let (entity, new_entity) = match existing {
+            Some(entity) => (entity, None),
+            None => match new_entities.get(&key) {
+                Some(entity) => (entity.clone(), Some(entity_description(&name))),
+                None => {
+                    let mut candidates = vec![initials(&name)];
+                    candidates.retain(|c| !likely_assets.contains(c));
+                    candidates.push(key.clone());
+                    let entity = free_name(storage, &taken, &candidates);
+                    taken.insert(entity.clone());
+                    new_entities.insert(key, entity.clone());
+                    (entity, Some(entity_description(&name)))
+                },
+            },
+        };
+
+        let short_name = free_name(storage, &taken, &[format!("{}{}", entity, type_name)]);
+        taken.insert(short_name.clone());
+
+        plan.proposals.push(Proposal {
+            short_name,
+            entity,
+            new_entity,
+            asset_type: type_name.to_string(),
+            path: relative,
+            pattern: pattern.pattern.clone(),
+        });
+    }
+
+    Ok(plan)
+}
+
+/// The first candidate short name that is free, or the last one numbered from 2 until one is
+fn free_name(storage: &JsonStorage, taken: &BTreeSet<String>, candidates: &[String]) -> String {
+    let is_free = |name: &str| !name.is_empty() && !taken.contains(name) && storage.check_name_availability(name).is_ok();
+
+    if let Some(name) = candidates.iter().find(|name| is_free(name)) {
+        return name.clone();
+    }
+    let base = candidates.last().map(String::as_str).unwrap_or("asset");
+    (2..).map(|n| format!("{}{}", base, n)).find(|name| is_free(name)).unwrap_or_default()
+}
+
+/// Register proposals: create their new entities, then their assets
+pub fn register(storage: &mut JsonStorage, proposals: &[&Proposal]) -> Result<()> {
+    for proposal in proposals {
+        if let Some(description) = &proposal.new_entity {
+            if !storage.entities.contains_key(&proposal.entity) {
+                storage.add_entity(&proposal.entity, description)?;
+            }
+        }
+        storage.add_asset_reference(&proposal.short_name, &proposal.entity, &proposal.asset_type, &proposal.path)
+            .context(format!("Failed to register {}", proposal.path))?;
+    }
+
+    Ok(())
+}
+
+/// Collects the files and directories under a directory, in a stable order
+struct Walker<'a> {
+    project_root: &'a Path,
+    vql_dir: PathBuf,
+    ignore: Vec<Regex>,
+    found: Vec<(PathBuf, bool)>,
+    scanned: usize,
+}
+
+impl Walker<'_> {
+    fn walk(&mut self, dir: &Path) -> Result<()> {
+        let mut entries: Vec<_> = fs::read_dir(dir)
+            .context(format!("Failed to read directory {}", dir.display()))?
+            .filter_map(|entry| entry.ok())
+            .collect();
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+            // Hidden files, symlinks and the VQL directory are never assets
+            if entry.file_name().to_string_lossy().starts_with('.') || path == self.vql_dir {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else { continue };
+            if file_type.is_symlink() {
+                continue;
+            }
+
+            let relative = asset_paths::display(self.project_root, &path).replace('\\', "/");
+            if file_type.is_dir() {
+                if self.is_ignored(&format!("{}/", relative)) {
+                    continue;
+                }
+                self.found.push((path.clone(), true));
+                self.walk(&path)?;
+            } else if !self.is_ignored(&relative) {
+                self.scanned += 1;
+                self.found.push((path, false));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_ignored(&self, relative: &str) -> bool {
+        let name = relative.trim_end_matches('/').rsplit('/').next().unwrap_or(relative);
+        self.ignore.iter().any(|pattern| pattern.is_match(relative) || pattern.is_match(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternatives_can_hold_wildcards() {
+        let pattern = AssetPattern::new("{*.controller,*Controller}.{js,ts}").unwrap();
+        assert!(pattern.matches("server/UserController.ts", false));
+        assert!(pattern.matches("order.controller.js", false));
+        assert!(!pattern.matches("UserService.ts", false));
+        assert_eq!(pattern.entity_name("order.controller.js", false).as_deref(), Some("order"));
+        assert_eq!(pattern.entity_name("UserController.ts", false).as_deref(), Some("User"));
+
+        assert!(AssetPattern::new("{*.js,*.ts").is_err());
+    }
+
+    #[test]
+    fn specificity_counts_literal_text() {
+        assert_eq!(literal_length("*.ts").unwrap(), 3);
+        assert_eq!(literal_length("*Controller.ts").unwrap(), 13);
+        assert_eq!(literal_length("*.{js,tsx}").unwrap(), 3);
+        // A long wildcard-heavy pattern is not more specific than a short literal one
+        assert!(literal_length("**/*/**/*.ts").unwrap() < literal_length("*Controller.ts").unwrap());
+    }
+
+    /// A project with controllers and services to discover, some already tracked or ignored
+    fn project() -> (tempfile::TempDir, PathBuf, JsonStorage) {
+        let project = tempfile::tempdir().unwrap();
+        let vql_dir = project.path().join("VQL");
+        fs::create_dir_all(&vql_dir).unwrap();
+        for file in [
+            "server/AccountController.ts", "server/AuditController.ts", "server/OrderController.ts",
+            "server/PaymentController.ts", "server/UserController.ts", "server/UserService.ts",
+            "server/README.md", "vendor/LegacyController.js", "node_modules/x/PluginController.js",
+        ] {
+            let path = project.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let mut storage = JsonStorage::new();
+        storage.add_entity("o", "Order").unwrap();
+        storage.add_entity("p", "Payment").unwrap();
+        storage.add_asset_type("c", "Controller").unwrap();
+        storage.add_asset_type("s", "Service").unwrap();
+        storage.set_asset_type_patterns("c", &["*Controller.{js,ts}"]).unwrap();
+        storage.set_asset_type_patterns("s", &["*Service.ts"]).unwrap();
+        storage.add_asset_reference("pc", "p", "c", "server/PaymentController.ts").unwrap();
+        storage.set_discovery_ignore(&["vendor/**"]).unwrap();
+        (project, vql_dir, storage)
+    }
+
+    #[test]
+    fn plan_names_entities_and_assets() {
+        let (_project, vql_dir, storage) = project();
+        let plan = plan(&storage, &vql_dir, None).unwrap();
+
+        let proposals: Vec<(&str, &str, bool, &str)> = plan.proposals.iter()
+            .map(|p| (p.short_name.as_str(), p.entity.as_str(), p.new_entity.is_some(), p.path.as_str()))
+            .collect();
+        assert_eq!(proposals, [
+            ("ac", "a", true, "server/AccountController.ts"),
+            // Initials already taken fall back to the whole name
+            ("auditc", "audit", true, "server/AuditController.ts"),
+            // Existing entities are matched by description
+            ("oc", "o", false, "server/OrderController.ts"),
+            ("uc", "u", true, "server/UserController.ts"),
+            ("us", "u", true, "server/UserService.ts"),
+        ]);
+        assert_eq!(plan.proposals[3].new_entity.as_deref(), Some("User"));
+        assert_eq!(plan.tracked, 1);
+    }
+
+    #[test]
+    fn plan_skips_ignored_directories() {
+        let (_project, vql_dir, mut storage) = project();
+        let paths = |storage: &JsonStorage| -> Vec<String> {
+            plan(storage, &vql_dir, None).unwrap().proposals.into_iter().map(|p| p.path).collect()
+        };
+
+        let found = paths(&storage);
+        assert!(!found.iter().any(|path| path.starts_with("vendor/") || path.contains("node_modules")));
+
+        storage.set_discovery_ignore(&[]).unwrap();
+        assert!(paths(&storage).contains(&"vendor/LegacyController.js".to_string()));
+
+        storage.set_discovery_ignore(&["*Service.ts"]).unwrap();
+        assert!(!paths(&storage).contains(&"server/UserService.ts".to_string()));
+    }
+
+    #[test]
+    fn registering_a_plan_creates_entities_once() {
+        let (_project, vql_dir, mut storage) = project();
+        let plan = plan(&storage, &vql_dir, None).unwrap();
+        register(&mut storage, &plan.proposals.iter().collect::<Vec<_>>()).unwrap();
+
+        assert_eq!(storage.entities["u"].description, "User");
+        assert_eq!(storage.asset_references["us"].path, "server/UserService.ts");
+        assert!(super::plan(&storage, &vql_dir, None).unwrap().proposals.is_empty());
+    }
+}
@@ -0,0 +1,495 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::models::asset_paths;
+use crate::models::json_storage::{AssetReference, JsonStorage, Review};
+use crate::models::migration::LEGACY_FIELDS;
+
+/// How serious a storage problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Storage is inconsistent and commands may fail or give wrong answers
+    Error,
+
+    /// Data is suspect or out of date, but storage is usable
+    Warning,
+
+    /// Harmless leftovers worth knowing about
+    Info,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Severity::Error => "ERROR",
+            Severity::Warning => "WARNING",
+            Severity::Info => "INFO",
+        };
+        f.pad(label)
+    }
+}
+
+/// A repair that can be applied without losing data that is still referenced
+#[derive(Debug, Clone)]
+pub enum Repair {
+    /// Set an item's short name to the key it is stored under
+    SyncShortName { section: &'static str, key: String },
+
+    /// Create a placeholder entity for assets that refer to a missing one
+    CreateEntity(String),
+
+    /// Move an entity with a broken parent link to the top level
+    ClearEntityParent(String),
+
+    /// Create a placeholder asset type for assets that refer to a missing one
+    CreateAssetType(String),
+
+    /// Remove an asset's review and history for a principle that no longer exists
+    DropOrphanReviews { asset: String, principle: String },
+
+    /// Remove an asset's dependency on an asset that no longer exists
+    DropDependency { asset: String, dependency: String },
+
+    /// Append the current review to the end of its history
+    AppendCurrentToHistory { asset: String, principle: String },
+
+    /// Make the latest history entry the current review
+    RestoreCurrentFromHistory { asset: String, principle: String },
+
+    /// Carry a leftover legacy review into `principle_reviews`
+    AdoptLegacyReview { asset: String, principle: String, review: Box<Review> },
+
+    /// Drop leftover legacy fields; saving storage is enough to remove them
+    DropLegacyFields { asset: String },
+}
+
+/// One problem found in storage
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub severity: Severity,
+
+    /// Item the problem is about, e.g. "asset uc"
+    pub subject: String,
+
+    pub message: String,
+
+    /// How `--fix` repairs it, if it can be repaired safely
+    pub repair: Option<Repair>,
+}
+
+impl Problem {
+    fn new(severity: Severity, subject: String, message: String, repair: Option<Repair>) -> Self {
+        Problem { severity, subject, message, repair }
+    }
+}
+
+/// Check storage for consistency problems, most severe first
+///
+/// `raw` is the document as stored, which may still hold fields the current schema
+/// ignores. Asset paths are resolved against `project_root`, the VQL directory's parent.
+pub fn diagnose(storage: &JsonStorage, raw: &Value, project_root: &Path) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    check_short_names(storage, &mut problems);
+    check_namespaces(storage, &mut problems);
+    check_sub_principles(storage, &mut problems);
+    check_entity_parents(storage, &mut problems);
+    check_rating_scales(storage, &mut problems);
+    check_assets(storage, project_root, &mut problems);
+    check_legacy_fields(storage, raw, &mut problems);
+
+    problems.sort_by(|a, b| a.severity.cmp(&b.severity).then_with(|| a.subject.cmp(&b.subject)));
+    problems
+}
+
+/// Items whose `short_name` differs from the key they are stored under
+fn check_short_names(storage: &JsonStorage, problems: &mut Vec<Problem>) {
+    let mut mismatched: Vec<(&'static str, &str, &String, &String)> = Vec::new();
+    mismatched.extend(storage.principles.iter().map(|(k, v)| ("principles", "principle", k, &v.short_name)));
+    mismatched.extend(storage.entities.iter().map(|(k, v)| ("entities", "entity", k, &v.short_name)));
+    mismatched.extend(storage.asset_types.iter().map(|(k, v)| ("asset_types", "asset type", k, &v.short_name)));
+    mismatched.extend(storage.asset_references.iter().map(|(k, v)| ("asset_references", "asset", k, &v.short_name)));
+    mismatched.retain(|(_, _, key, short_name)| key != short_name);
+
+    for (section, label, key, short_name) in mismatched {
+        problems.push(Problem::new(
+            Severity::Error,
+            format!("{} {}", label, key),
+            format!("stored under '{}' but its short name is '{}'", key, short_name),
+            Some(Repair::SyncShortName { section, key: key.clone() }),
+        ));
+    }
+}
+
+/// Short names used by more than one kind of item
+fn check_namespaces(storage: &JsonStorage, problems: &mut Vec<Problem>) {
+    let mut owners: BTreeMap<&String, Vec<&str>> = BTreeMap::new();
+    for (key, principle) in &storage.principles {
+        owners.entry(key).or_default().push("principle");
+        for alias in &principle.aliases {
+            owners.entry(alias).or_default().push("principle alias");
+        }
+    }
+    for key in storage.entities.keys() {
+        owners.entry(key).or_default().push("entity");
+    }
+    for key in storage.asset_types.keys() {
+        owners.entry(key).or_default().push("asset type");
+    }
+    for key in storage.asset_references.keys() {
+        owners.entry(key).or_default().push("asset");
+    }
+
+    for (key, labels) in owners {
+        if labels.len() > 1 {
+            problems.push(Problem::new(
+                Severity::Error,
+                format!("short name {}", key),
+                format!("used by more than one item ({}); commands cannot tell them apart - rename one with vql -rn", labels.join(", ")),
+                None,
+            ));
+        }
+    }
+}
+
+/// Principles or a project default that name a rating scale which does not exist
+fn check_rating_scales(storage: &JsonStorage, problems: &mut Vec<Problem>) {
+    let mut missing: Vec<(String, &str)> = storage.principles.values()
+        .filter_map(|p| p.rating_scale.as_deref().map(|scale| (format!("principle {}", p.short_name), scale)))
+        .collect();
+    missing.extend(storage.default_rating_scale.as_deref().map(|scale| ("default rating scale".to_string(), scale)));
+    missing.retain(|(_, scale)| storage.find_rating_scale(scale).is_none());
+
+    for (subject, scale) in missing {
+        problems.push(Problem::new(
+            Severity::Warning,
+            subject,
+            format!("uses rating scale '{}', which does not exist; ratings fall back to H/M/L - add it with vql -scale -add", scale),
+            None,
+        ));
+    }
+}
+
+/// Sub-principles whose parent principle no longer exists
+fn check_sub_principles(storage: &JsonStorage, problems: &mut Vec<Problem>) {
+    for principle in storage.principles.values() {
+        if let Some(parent) = principle.parent().filter(|p| !storage.principles.contains_key(*p)) {
+            problems.push(Problem::new(
+                Severity::Warning,
+                format!("principle {}", principle.short_name),
+                format!("sub-principle of '{}', which does not exist; it is hidden from vql -pr - rename it with vql -rn or add the parent", parent),
+                None,
+            ));
+        }
+    }
+}
+
+/// Entity parents that are missing or lead back to the entity itself
+fn check_entity_parents(storage: &JsonStorage, problems: &mut Vec<Problem>) {
+    for (name, entity) in &storage.entities {
+        let Some(parent) = entity.parent.as_deref() else { continue };
+
+        let problem = if !storage.entities.contains_key(parent) {
+            format!("sits under entity '{}', which does not exist", parent)
+        } else if storage.entity_in_context(parent, name) {
+            format!("sits under entity '{}', which sits under it in turn", parent)
+        } else {
+            continue;
+        };
+
+        problems.push(Problem::new(
+            Severity::Warning,
+            format!("entity {}", name),
+            problem,
+            Some(Repair::ClearEntityParent(name.clone())),
+        ));
+    }
+}
+
+/// Asset references, reviews, history and paths
+fn check_assets(storage: &JsonStorage, project_root: &Path, problems: &mut Vec<Problem>) {
+    let mut missing_entities: BTreeMap<&String, Vec<&String>> = BTreeMap::new();
+    let mut missing_types: BTreeMap<&String, Vec<&String>> = BTreeMap::new();
+
+    for (name, asset) in &storage.asset_references {
+        let subject = format!("asset {}", name);
+
+        if !storage.entities.contains_key(&asset.entity) {
+            missing_entities.entry(&asset.entity).or_default().push(name);
+        }
+        if !storage.asset_types.contains_key(&asset.asset_type) {
+            missing_types.entry(&asset.asset_type).or_default().push(name);
+        }
+
+        // Reviews and history kept for principles that were deleted
+        let mut orphaned: Vec<&String> = asset.principle_reviews.keys()
+            .chain(asset.review_history.keys())
+            .filter(|principle| !storage.principles.contains_key(*principle))
+            .collect();
+        orphaned.sort();
+        orphaned.dedup();
+        for principle in orphaned {
+            problems.push(Problem::new(
+                Severity::Warning,
+                subject.clone(),
+                format!("has reviews for principle '{}', which does not exist", principle),
+                Some(Repair::DropOrphanReviews { asset: name.clone(), principle: principle.clone() }),
+            ));
+        }
+
+        for dependency in asset.depends_on.iter().filter(|dep| !storage.asset_references.contains_key(*dep)) {
+            problems.push(Problem::new(
+                Severity::Warning,
+                subject.clone(),
+                format!("depends on asset '{}', which does not exist", dependency),
+                Some(Repair::DropDependency { asset: name.clone(), dependency: dependency.clone() }),
+            ));
+        }
+
+        for (principle, review) in &asset.principle_reviews {
+            if let Some(rating) = review.rating.as_deref() {
+                let scale = storage.rating_scale_for(principle);
+                if scale.level(rating).is_none() {
+                    problems.push(Problem::new(
+                        Severity::Warning,
+                        subject.clone(),
+                        format!("review for '{}' has invalid rating '{}' (must be one of {}) - store a new review", principle, rating, scale.describe()),
+                        None,
+                    ));
+                }
+            }
+        }
+
+        // History is optional (reviews stored before it was tracked), but when present
+        // its latest entry must be the current review
+        for (principle, history) in &asset.review_history {
+            let Some(latest) = history.last() else { continue };
+            if !storage.principles.contains_key(principle) {
+                continue;
+            }
+
+            match asset.principle_reviews.get(principle) {
+                Some(current) if current == latest => {},
+                Some(_) => problems.push(Problem::new(
+                    Severity::Warning,
+                    subject.clone(),
+                    format!("current review for '{}' is missing from its history", principle),
+                    Some(Repair::AppendCurrentToHistory { asset: name.clone(), principle: principle.clone() }),
+                )),
+                None => problems.push(Problem::new(
+                    Severity::Warning,
+                    subject.clone(),
+                    format!("has review history for '{}' but no current review", principle),
+                    Some(Repair::RestoreCurrentFromHistory { asset: name.clone(), principle: principle.clone() }),
+                )),
+            }
+        }
+
+        for path in asset.paths() {
+            let problem = if asset_paths::is_pattern(path) {
+                let matched = asset_paths::matched_files(project_root, path).is_ok_and(|files| !files.is_empty());
+                (!matched).then(|| format!("pattern {} matches no files", path))
+            } else {
+                (!asset_paths::resolve(project_root, path).exists()).then(|| format!("file {} does not exist", path))
+            };
+            if let Some(problem) = problem {
+                problems.push(Problem::new(
+                    Severity::Warning,
+                    subject.clone(),
+                    format!("{} - update it with vql -ar -paths {} <path>...", problem, name),
+                    None,
+                ));
+            }
+        }
+    }
+
+    for (entity, assets) in missing_entities {
+        // A placeholder can only be created if the name is free in every namespace
+        let repair = storage.check_name_availability(entity).ok()
+            .map(|_| Repair::CreateEntity(entity.clone()));
+        problems.push(Problem::new(
+            Severity::Error,
+            format!("entity {}", entity),
+            format!("does not exist but is used by {}", list_names(&assets)),
+            repair,
+        ));
+    }
+
+    for (asset_type, assets) in missing_types {
+        let repair = (asset_type.chars().count() == 1 && storage.check_name_availability(asset_type).is_ok())
+            .then(|| Repair::CreateAssetType(asset_type.clone()));
+        problems.push(Problem::new(
+            Severity::Error,
+            format!("asset type {}", asset_type),
+            format!("does not exist but is used by {}", list_names(&assets)),
+            repair,
+        ));
+    }
+}
+
+/// Legacy per-aspect fields still present in the stored document
+fn check_legacy_fields(storage: &JsonStorage, raw: &Value, problems: &mut Vec<Problem>) {
+    let Some(assets) = raw.get("asset_references").and_then(|v| v.as_object()) else { return };
+
+    for (name, raw_asset) in assets {
+        let Some(asset) = storage.asset_references.get(name) else { continue };
+        let subject = format!("asset {}", name);
+
+        for (prefix, principle, _) in LEGACY_FIELDS {
+            let rating = raw_asset.get(format!("{}_rating", prefix)).filter(|v| !v.is_null());
+            let analysis = raw_asset.get(format!("{}_analysis", prefix)).filter(|v| !v.is_null());
+            if rating.is_none() && analysis.is_none() {
+                continue;
+            }
+
+            let legacy = Review {
+                rating: rating.and_then(|v| v.as_str()).map(str::to_string),
+                analysis: analysis.and_then(|v| v.as_str()).map(str::to_string),
+                last_modified: asset.last_modified.clone(),
+                principle_revision: None,
+                author: None,
+                source: None,
+                model: None,
+                confidence: None,
+                findings: Vec::new(),
+                content_hash: None,
+                commit: None,
+            };
+
+            // A review restored from history takes precedence over the legacy copy
+            let current = asset.principle_reviews.get(principle)
+                .or_else(|| asset.review_history.get(principle).and_then(|history| history.last()));
+
+            let problem = match current {
+                Some(current) if current.rating == legacy.rating && current.analysis == legacy.analysis => Problem::new(
+                    Severity::Info,
+                    subject.clone(),
+                    format!("leftover legacy {} fields match the '{}' review and will be dropped", prefix, principle),
+                    Some(Repair::DropLegacyFields { asset: name.clone() }),
+                ),
+                Some(current) => Problem::new(
+                    Severity::Warning,
+                    subject.clone(),
+                    format!("legacy {} rating {} is out of sync with the '{}' review ({}); the review is kept and the legacy fields dropped",
+                        prefix,
+                        legacy.rating.as_deref().unwrap_or("-"),
+                        principle,
+                        current.rating.as_deref().unwrap_or("-")),
+                    Some(Repair::DropLegacyFields { asset: name.clone() }),
+                ),
+                None if storage.principles.contains_key(principle) => Problem::new(
+                    Severity::Warning,
+                    subject.clone(),
+                    format!("legacy {} review is missing from principle reviews", prefix),
+                    Some(Repair::AdoptLegacyReview { asset: name.clone(), principle: principle.to_string(), review: Box::new(legacy) }),
+                ),
+                None => Problem::new(
+                    Severity::Warning,
+                    subject.clone(),
+                    format!("legacy {} review has no principle '{}' to go to - add it with vql -pr -add, then run vql -doctor --fix", prefix, principle),
+                    None,
+                ),
+            };
+            problems.push(problem);
+        }
+    }
+}
+
+/// Apply a repair, returning a description of what was done
+///
+/// The caller records the repairs as one operation for the journal.
+pub fn apply_repair(storage: &mut JsonStorage, repair: &Repair) -> Result<String> {
+    let description = match repair {
+        Repair::SyncShortName { section, key } => {
+            let short_name = match *section {
+                "principles" => storage.principles.get_mut(key).map(|item| &mut item.short_name),
+                "entities" => storage.entities.get_mut(key).map(|item| &mut item.short_name),
+                "asset_types" => storage.asset_types.get_mut(key).map(|item| &mut item.short_name),
+                _ => storage.asset_references.get_mut(key).map(|item| &mut item.short_name),
+            };
+            let short_name = short_name.ok_or_else(|| anyhow::anyhow!("Item {} no longer exists", key))?;
+            *short_name = key.clone();
+            format!("set short name of {} to match its key", key)
+        },
+        Repair::CreateEntity(name) => {
+            storage.add_entity(name, &capitalize(name))?;
+            format!("created placeholder entity {}", name)
+        },
+        Repair::ClearEntityParent(name) => {
+            let entity = storage.entities.get_mut(name)
+                .ok_or_else(|| anyhow::anyhow!("Entity {} no longer exists", name))?;
+            entity.parent = None;
+            format!("moved entity {} to the top level", name)
+        },
+        Repair::CreateAssetType(name) => {
+            storage.add_asset_type(name, &capitalize(name))?;
+            format!("created placeholder asset type {}", name)
+        },
+        Repair::DropOrphanReviews { asset, principle } => {
+            let asset_ref = asset_mut(storage, asset)?;
+            asset_ref.principle_reviews.remove(principle);
+            asset_ref.review_history.remove(principle);
+            format!("removed reviews of {} for deleted principle {}", asset, principle)
+        },
+        Repair::DropDependency { asset, dependency } => {
+            asset_mut(storage, asset)?.depends_on.retain(|dep| dep != dependency);
+            format!("removed dependency of {} on missing asset {}", asset, dependency)
+        },
+        Repair::AppendCurrentToHistory { asset, principle } => {
+            let asset_ref = asset_mut(storage, asset)?;
+            let current = asset_ref.principle_reviews.get(principle).cloned()
+                .ok_or_else(|| anyhow::anyhow!("Asset {} has no review for {}", asset, principle))?;
+            asset_ref.review_history.entry(principle.clone()).or_default().push(current);
+            format!("added the current {} review of {} to its history", principle, asset)
+        },
+        Repair::RestoreCurrentFromHistory { asset, principle } => {
+            let asset_ref = asset_mut(storage, asset)?;
+            let latest = asset_ref.review_history.get(principle).and_then(|h| h.last()).cloned()
+                .ok_or_else(|| anyhow::anyhow!("Asset {} has no history for {}", asset, principle))?;
+            asset_ref.principle_reviews.insert(principle.clone(), latest);
+            format!("restored the latest {} review of {} from its history", principle, asset)
+        },
+        Repair::AdoptLegacyReview { asset, principle, review } => {
+            let asset_ref = asset_mut(storage, asset)?;
+            asset_ref.review_history.entry(principle.clone()).or_default().push((**review).clone());
+            asset_ref.principle_reviews.insert(principle.clone(), (**review).clone());
+            format!("moved the legacy {} review of {} into principle reviews", principle, asset)
+        },
+        Repair::DropLegacyFields { asset } => {
+            format!("dropped leftover legacy fields of {}", asset)
+        },
+    };
+
+    storage.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    Ok(description)
+}
+
+/// Asset to repair, which must still exist
+fn asset_mut<'a>(storage: &'a mut JsonStorage, name: &str) -> Result<&'a mut AssetReference> {
+    storage.asset_references.get_mut(name)
+        .ok_or_else(|| anyhow::anyhow!("Asset {} no longer exists", name))
+}
+
+/// "a, b and 3 more" style list of names
+fn list_names(names: &[&String]) -> String {
+    const SHOWN: usize = 5;
+    let mut list = names.iter().take(SHOWN).map(|n| n.as_str()).collect::<Vec<_>>().join(", ");
+    if names.len() > SHOWN {
+        list.push_str(&format!(" and {} more", names.len() - SHOWN));
+    }
+    list
+}
+
+/// Placeholder description for a created item
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,229 @@
+use anyhow::{Result, anyhow};
+
+use crate::models::attribution::{self, ReviewSource};
+use crate::models::json_storage::{AssetReference, JsonStorage, Review};
+use crate::models::tags;
+
+/// Prefix that marks a tag filter among command arguments, e.g. `tag:critical`
+pub const TAG_FILTER_PREFIX: &str = "tag:";
+
+/// Prefix that marks a bounded context among command arguments, e.g. `@billing`
+pub const CONTEXT_FILTER_PREFIX: &str = "@";
+
+/// Prefixes that mark review filters among command arguments, e.g. `author:dom` or `min-confidence:0.7`
+pub const REVIEW_FILTER_PREFIXES: [&str; 4] = ["author:", "source:", "model:", "min-confidence:"];
+
+/// Restricts a command that works on many assets to some of them
+///
+/// Written as `tag:critical` arguments, which require every tag given (`tag:critical,legacy`
+/// and repeated `tag:` arguments alike), and `@billing` arguments, which require the asset's
+/// entity to be the context or to sit anywhere under it. Several contexts select assets in any of them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssetFilter {
+    pub tags: Vec<String>,
+    pub contexts: Vec<String>,
+}
+
+impl AssetFilter {
+    /// Whether a command argument is part of a filter rather than something else
+    pub fn is_filter_arg(arg: &str) -> bool {
+        arg.starts_with(TAG_FILTER_PREFIX) || arg.starts_with(CONTEXT_FILTER_PREFIX)
+    }
+
+    /// Split filter arguments from the rest of a command's whitespace-separated arguments
+    pub fn from_args<'a>(args: &[&'a str]) -> Result<(Vec<&'a str>, AssetFilter)> {
+        let mut filter = AssetFilter::default();
+        let mut rest = Vec::new();
+
+        for arg in args {
+            if !filter.add(arg)? {
+                rest.push(*arg);
+            }
+        }
+
+        Ok((rest, filter))
+    }
+
+    /// Split filter entries from a comma-separated LLM argument list, e.g. "a, c, tag:critical, @billing"
+    ///
+    /// A `tag:` entry keeps the names that follow it without a space, so in "a, tag:critical,legacy"
+    /// both are tags; "a, tag:critical, legacy" makes `legacy` an ordinary entry again.
+    pub fn from_list(args: &str) -> Result<(String, AssetFilter)> {
+        let mut filter = AssetFilter::default();
+        let mut rest = Vec::new();
+        let mut in_tags = false;
+
+        for piece in args.split(',') {
+            let arg = piece.trim();
+            if arg.is_empty() {
+                continue;
+            }
+
+            if in_tags && !piece.starts_with(char::is_whitespace) && !Self::is_filter_arg(arg) {
+                filter.add(&format!("{}{}", TAG_FILTER_PREFIX, arg))?;
+                continue;
+            }
+
+            in_tags = arg.starts_with(TAG_FILTER_PREFIX);
+            if !filter.add(arg)? {
+                rest.push(arg);
+            }
+        }
+
+        Ok((rest.join(", "), filter))
+    }
+
+    /// Add a filter argument, returning false if the argument is not one
+    fn add(&mut self, arg: &str) -> Result<bool> {
+        if let Some(tags) = arg.strip_prefix(TAG_FILTER_PREFIX) {
+            for tag in tags.split(',').filter(|t| !t.trim().is_empty()) {
+                let tag = tags::normalize_tag(tag)?;
+                if !self.tags.contains(&tag) {
+                    self.tags.push(tag);
+                }
+            }
+            return Ok(true);
+        }
+
+        if let Some(context) = arg.strip_prefix(CONTEXT_FILTER_PREFIX) {
+            if !context.is_empty() && !self.contexts.iter().any(|c| c == context) {
+                self.contexts.push(context.to_string());
+            }
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.contexts.is_empty()
+    }
+
+    /// Check that every context in the filter is an entity
+    pub fn check(&self, storage: &JsonStorage) -> Result<()> {
+        match self.contexts.iter().find(|context| !storage.entities.contains_key(*context)) {
+            Some(context) => Err(anyhow!("Unknown context '{}{}': no entity has that name", CONTEXT_FILTER_PREFIX, context)),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether an asset passes the filter (always true for an empty filter)
+    pub fn matches(&self, storage: &JsonStorage, asset: &AssetReference) -> bool {
+        self.tags.iter().all(|tag| asset.tags.contains(tag))
+            && (self.contexts.is_empty()
+                || self.contexts.iter().any(|context| storage.entity_in_context(&asset.entity, context)))
+    }
+
+    /// The filter for messages, e.g. "tag:critical,legacy @billing"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.tags.is_empty() {
+            parts.push(format!("{}{}", TAG_FILTER_PREFIX, self.tags.join(",")));
+        }
+        parts.extend(self.contexts.iter().map(|context| format!("{}{}", CONTEXT_FILTER_PREFIX, context)));
+        parts.join(" ")
+    }
+}
+
+/// Restricts a command to reviews with a given attribution
+///
+/// Written as `author:dom` and `source:llm`, which match exactly (ignoring case), `model:gpt-4`,
+/// which matches models starting with it, and `min-confidence:0.7`. Reviews that do not record
+/// the attribute a filter asks about never match it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReviewFilter {
+    pub author: Option<String>,
+    pub source: Option<ReviewSource>,
+    pub model: Option<String>,
+    pub min_confidence: Option<f64>,
+}
+
+impl ReviewFilter {
+    /// Split review filter arguments from the rest of a command's whitespace-separated arguments
+    pub fn from_args<'a>(args: &[&'a str]) -> Result<(Vec<&'a str>, ReviewFilter)> {
+        let mut filter = ReviewFilter::default();
+        let mut rest = Vec::new();
+
+        for arg in args {
+            let Some((field, value)) = arg.split_once(':').filter(|_| REVIEW_FILTER_PREFIXES.iter().any(|p| arg.starts_with(p))) else {
+                rest.push(*arg);
+                continue;
+            };
+
+            let value = value.trim();
+            if value.is_empty() {
+                return Err(anyhow!("No value given for {}:", field));
+            }
+            match field {
+                "author" => filter.author = Some(value.to_string()),
+                "source" => filter.source = Some(value.parse()?),
+                "model" => filter.model = Some(value.to_string()),
+                _ => filter.min_confidence = Some(attribution::parse_confidence(value)?),
+            }
+        }
+
+        Ok((rest, filter))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == ReviewFilter::default()
+    }
+
+    /// Whether a review passes the filter (always true for an empty filter)
+    pub fn matches(&self, review: &Review) -> bool {
+        let author = match &self.author {
+            Some(author) => review.author.as_deref().is_some_and(|a| a.eq_ignore_ascii_case(author)),
+            None => true,
+        };
+        let source = self.source.is_none() || review.source == self.source;
+        let model = match &self.model {
+            Some(model) => review.model.as_deref().is_some_and(|m| m.to_lowercase().starts_with(&model.to_lowercase())),
+            None => true,
+        };
+        let confidence = match self.min_confidence {
+            Some(min) => review.confidence.is_some_and(|c| c >= min),
+            None => true,
+        };
+
+        author && source && model && confidence
+    }
+
+    /// The filter for messages, e.g. "author:dom min-confidence:0.7"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(author) = &self.author {
+            parts.push(format!("author:{}", author));
+        }
+        if let Some(source) = self.source {
+            parts.push(format!("source:{}", source));
+        }
+        if let Some(model) = &self.model {
+            parts.push(format!("model:{}", model));
+        }
+        if let Some(min) = self.min_confidence {
+            parts.push(format!("min-confidence:{}", min));
+        }
+        parts.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_lists_in_llm_arguments_stay_together() {
+        let (rest, filter) = AssetFilter::from_list("a, tag:critical,legacy, s").unwrap();
+        assert_eq!(rest, "a, s");
+        assert_eq!(filter.tags, ["critical", "legacy"]);
+
+        let (rest, filter) = AssetFilter::from_list("tag:critical, legacy").unwrap();
+        assert_eq!(rest, "legacy");
+        assert_eq!(filter.tags, ["critical"]);
+
+        let (rest, filter) = AssetFilter::from_list("a,tag:critical,@billing").unwrap();
+        assert_eq!(rest, "a");
+        assert_eq!(filter.tags, ["critical"]);
+        assert_eq!(filter.contexts, ["billing"]);
+    }
+}
@@ -0,0 +1,260 @@
+use std::fmt;
+use std::str::FromStr;
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+
+/// How serious a finding is, most serious first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Critical,
+    High,
+    Medium,
+    Low,
+    Info,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Severity::Critical => "critical",
+            Severity::High => "high",
+            Severity::Medium => "medium",
+            Severity::Low => "low",
+            Severity::Info => "info",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Severity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "critical" | "c" => Ok(Severity::Critical),
+            "high" | "h" => Ok(Severity::High),
+            "medium" | "m" => Ok(Severity::Medium),
+            "low" | "l" => Ok(Severity::Low),
+            "info" | "i" => Ok(Severity::Info),
+            _ => Err(anyhow!("Invalid severity '{}': use critical, high, medium, low or info", s.trim())),
+        }
+    }
+}
+
+/// Whether a finding still needs fixing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FindingStatus {
+    Open,
+    Resolved,
+}
+
+/// One specific problem a review points out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Number of the finding, unique within its asset
+    pub id: u32,
+
+    /// File the problem is in, relative to the project root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    /// First line of the problem
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_start: Option<u32>,
+
+    /// Last line of the problem, when it spans several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_end: Option<u32>,
+
+    pub severity: Severity,
+
+    /// Part of the principle the problem falls under, e.g. a sub-principle such as "a.dry"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub area: Option<String>,
+
+    pub description: String,
+
+    /// Suggested fix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+
+    pub status: FindingStatus,
+
+    /// When the finding was resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<String>,
+
+    /// How the finding was resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<String>,
+}
+
+impl Finding {
+    pub fn is_open(&self) -> bool {
+        self.status == FindingStatus::Open
+    }
+
+    /// Mark the finding resolved now, with an optional note on how
+    pub fn resolve(&mut self, note: Option<&str>) {
+        self.status = FindingStatus::Resolved;
+        self.resolved = Some(Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string());
+        self.resolution = note.map(str::to_string);
+    }
+
+    /// Where the problem is, e.g. "src/user.ts:40-62", or None if no file was given
+    pub fn location(&self) -> Option<String> {
+        let file = self.file.as_ref()?;
+        Some(match (self.line_start, self.line_end) {
+            (Some(start), Some(end)) if end != start => format!("{}:{}-{}", file, start, end),
+            (Some(start), _) => format!("{}:{}", file, start),
+            _ => file.clone(),
+        })
+    }
+}
+
+/// A finding as written in a `-st`/`:uc.st` findings payload
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FindingInput {
+    #[serde(default)]
+    file: Option<String>,
+
+    /// "40-62", "40" or 40
+    #[serde(default, alias = "line")]
+    lines: Option<serde_json::Value>,
+
+    #[serde(default)]
+    severity: Option<String>,
+
+    #[serde(default, alias = "principle")]
+    area: Option<String>,
+
+    description: String,
+
+    #[serde(default, alias = "suggestion", alias = "recommendation")]
+    fix: Option<String>,
+}
+
+/// Parse a findings payload: a JSON array of findings, or a single finding object
+///
+/// e.g. `[{"file": "src/user.ts", "lines": "40-62", "severity": "high", "area": "s.backend",
+/// "description": "No input validation in createUser", "fix": "Validate the request body"}]`.
+/// Severity defaults to medium. The findings are open and numbered when the review is stored.
+pub fn parse_findings(json: &str) -> Result<Vec<Finding>> {
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| anyhow!("Invalid findings JSON: {}", e))?;
+    let values = match value {
+        serde_json::Value::Array(values) => values,
+        value => vec![value],
+    };
+
+    values.into_iter().map(|value| {
+        let input: FindingInput = serde_json::from_value(value)
+            .map_err(|e| anyhow!("Invalid finding: {}", e))?;
+        if input.description.trim().is_empty() {
+            return Err(anyhow!("Invalid finding: the description is empty"));
+        }
+        let (line_start, line_end) = match &input.lines {
+            Some(lines) => parse_lines(lines)?,
+            None => (None, None),
+        };
+
+        Ok(Finding {
+            id: 0,
+            file: input.file.filter(|f| !f.trim().is_empty()),
+            line_start,
+            line_end,
+            severity: input.severity.as_deref().map(str::parse).transpose()?.unwrap_or(Severity::Medium),
+            area: input.area.filter(|a| !a.trim().is_empty()),
+            description: input.description.trim().to_string(),
+            fix: input.fix.filter(|f| !f.trim().is_empty()),
+            status: FindingStatus::Open,
+            resolved: None,
+            resolution: None,
+        })
+    }).collect()
+}
+
+/// Split the first JSON value off the start of `text`, returning it and the text after it
+pub fn split_json_prefix(text: &str) -> Result<(&str, &str)> {
+    let text = text.trim_start();
+    let mut values = serde_json::Deserializer::from_str(text).into_iter::<serde_json::Value>();
+    match values.next() {
+        Some(Ok(_)) => Ok(text.split_at(values.byte_offset())),
+        Some(Err(e)) => Err(anyhow!("Invalid findings JSON: {}", e)),
+        None => Err(anyhow!("No findings JSON given")),
+    }
+}
+
+/// Parse a line range given as "40-62", "40" or a number
+fn parse_lines(lines: &serde_json::Value) -> Result<(Option<u32>, Option<u32>)> {
+    let text = match lines {
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => s.trim().to_string(),
+        _ => return Err(anyhow!("Invalid finding lines {}: use a line number or a range such as \"40-62\"", lines)),
+    };
+
+    let parse = |n: &str| n.trim().parse::<u32>()
+        .map_err(|_| anyhow!("Invalid finding lines '{}': use a line number or a range such as \"40-62\"", text));
+    match text.split_once('-') {
+        Some((start, end)) => {
+            let (start, end) = (parse(start)?, parse(end)?);
+            if end < start {
+                return Err(anyhow!("Invalid finding lines '{}': the range ends before it starts", text));
+            }
+            Ok((Some(start), Some(end)))
+        },
+        None => Ok((Some(parse(&text)?), None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::attribution::Attribution;
+    use crate::models::json_storage::JsonStorage;
+
+    #[test]
+    fn findings_parse_with_defaults() {
+        let findings = parse_findings(r#"{"file": "uc.js", "lines": "40-62", "description": " No validation "}"#).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!((findings[0].line_start, findings[0].line_end), (Some(40), Some(62)));
+        assert_eq!(findings[0].severity, Severity::Medium);
+        assert_eq!(findings[0].description, "No validation");
+        assert!(findings[0].is_open());
+
+        assert!(parse_findings(r#"[{"description": ""}]"#).is_err());
+        assert!(parse_findings(r#"[{"description": "x", "severity": "urgent"}]"#).is_err());
+    }
+
+    #[test]
+    fn open_findings_carry_over_to_the_next_review() {
+        let mut storage = JsonStorage::new();
+        storage.add_principle("s", "Security", None).unwrap();
+        storage.add_entity("u", "User").unwrap();
+        storage.add_asset_type("c", "Controller").unwrap();
+        storage.add_asset_reference("uc", "u", "c", "uc.js").unwrap();
+
+        let first = parse_findings(r#"[{"description": "SQL built by concatenation"}, {"description": "Hard-coded key"}]"#).unwrap();
+        storage.store_asset_review("uc", "s", Some("L"), "Two problems", &Attribution::default(), first).unwrap();
+        storage.resolve_findings("uc", &[1], Some("Parameterised queries")).unwrap();
+
+        let second = parse_findings(r#"{"description": "No rate limiting", "severity": "low"}"#).unwrap();
+        storage.store_asset_review("uc", "s", Some("M"), "Better", &Attribution::default(), second).unwrap();
+
+        // The resolved finding stays with the review it was found in; the open one moves on
+        let asset = &storage.asset_references["uc"];
+        let current: Vec<(u32, &str)> = asset.principle_reviews["s"].findings.iter()
+            .map(|f| (f.id, f.description.as_str()))
+            .collect();
+        assert_eq!(current, [(2, "Hard-coded key"), (3, "No rate limiting")]);
+
+        let history = &asset.review_history["s"];
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].findings[0].resolution.as_deref(), Some("Parameterised queries"));
+        assert!(storage.resolve_findings("uc", &[1], None).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use std::fs;
+use std::path::Path;
+use anyhow::{Result, Context};
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+
+use crate::utils::lock::write_atomic;
+
+/// Journal file kept inside the VQL directory
+pub const JOURNAL_FILE_NAME: &str = "vql_journal.json";
+
+/// Number of operations kept for undo (and for redo)
+const MAX_JOURNAL_ENTRIES: usize = 100;
+
+/// Section name used for top-level storage fields such as `last_modified`
+const STORAGE_FIELDS: &str = "storage";
+
+/// Storage sections whose items are journaled one by one
+const JOURNALED_SECTIONS: [&str; 5] = ["commands", "asset_types", "entities", "principles", "asset_references"];
+
+/// A single item (or top-level field) as it was before and after an operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    /// Storage section, or "storage" for top-level fields
+    pub section: String,
+
+    /// Item short name, or field name for top-level fields
+    pub name: String,
+
+    /// Value before the operation; `None` if the item was created
+    pub before: Option<Value>,
+
+    /// Value after the operation; `None` if the item was deleted
+    pub after: Option<Value>,
+}
+
+/// One recorded operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Sequence number, unique within the journal
+    pub id: u64,
+
+    /// When the operation was saved
+    pub timestamp: String,
+
+    /// What the operation did, e.g. "delete principle a"
+    pub operation: String,
+
+    /// Items changed by the operation
+    pub changes: Vec<Change>,
+}
+
+/// Undo and redo stacks of storage operations, most recent last
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Journal {
+    /// Id given to the next recorded operation
+    #[serde(default)]
+    pub next_id: u64,
+
+    /// Operations that can be undone
+    #[serde(default)]
+    pub undo: Vec<JournalEntry>,
+
+    /// Undone operations that can be redone
+    #[serde(default)]
+    pub redo: Vec<JournalEntry>,
+}
+
+impl Journal {
+    /// Load the journal from a VQL directory, or start an empty one
+    pub fn load(vql_dir: &Path) -> Result<Self> {
+        let path = vql_dir.join(JOURNAL_FILE_NAME);
+        if !path.exists() {
+            return Ok(Journal::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .context(format!("Failed to read VQL journal at {}", path.display()))?;
+        serde_json::from_str(&content)
+            .context(format!("Failed to parse VQL journal at {}", path.display()))
+    }
+
+    /// Write the journal to a VQL directory
+    pub fn save(&self, vql_dir: &Path) -> Result<()> {
+        let path = vql_dir.join(JOURNAL_FILE_NAME);
+        let content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize VQL journal")?;
+
+        write_atomic(&path, content.as_bytes())
+            .context(format!("Failed to write VQL journal to {}", path.display()))
+    }
+
+    /// Record a new operation; this discards anything that could be redone
+    pub fn record(&mut self, operation: &str, changes: Vec<Change>) {
+        self.next_id += 1;
+        self.undo.push(JournalEntry {
+            id: self.next_id,
+            timestamp: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            operation: operation.to_string(),
+            changes,
+        });
+        self.redo.clear();
+
+        if self.undo.len() > MAX_JOURNAL_ENTRIES {
+            let excess = self.undo.len() - MAX_JOURNAL_ENTRIES;
+            self.undo.drain(..excess);
+        }
+    }
+}
+
+impl JournalEntry {
+    /// Revert (`undo = true`) or re-apply this operation to a storage document
+    ///
+    /// Returns notes about items that changed since the operation was recorded;
+    /// they are overwritten anyway.
+    pub fn apply(&self, document: &mut Value, undo: bool) -> Vec<String> {
+        let mut notes = Vec::new();
+
+        // Revert in reverse order so multi-step operations unwind cleanly
+        let changes: Vec<&Change> = if undo {
+            self.changes.iter().rev().collect()
+        } else {
+            self.changes.iter().collect()
+        };
+
+        for change in changes {
+            let (expected, target) = if undo {
+                (&change.after, &change.before)
+            } else {
+                (&change.before, &change.after)
+            };
+
+            let Some(container) = section_mut(document, &change.section) else { continue };
+
+            if change.section != STORAGE_FIELDS && container.get(&change.name) != expected.as_ref() {
+                notes.push(format!("{} '{}' changed since operation #{} - overwritten", section_label(&change.section), change.name, self.id));
+            }
+
+            match target {
+                Some(value) => {
+                    container.insert(change.name.clone(), value.clone());
+                },
+                None => {
+                    container.remove(&change.name);
+                },
+            }
+        }
+
+        notes
+    }
+}
+
+/// Item-level differences between two storage documents
+pub fn diff_documents(before: &Value, after: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let empty = Map::new();
+
+    let before_root = before.as_object().unwrap_or(&empty);
+    let after_root = after.as_object().unwrap_or(&empty);
+
+    for section in JOURNALED_SECTIONS {
+        let before_items = before_root.get(section).and_then(|v| v.as_object()).unwrap_or(&empty);
+        let after_items = after_root.get(section).and_then(|v| v.as_object()).unwrap_or(&empty);
+
+        diff_maps(section, before_items, after_items, &mut changes);
+    }
+
+    // Top-level fields go last so they are restored after the items on undo
+    let fields = |root: &Map<String, Value>| -> Map<String, Value> {
+        root.iter()
+            .filter(|(key, _)| !JOURNALED_SECTIONS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    };
+    diff_maps(STORAGE_FIELDS, &fields(before_root), &fields(after_root), &mut changes);
+
+    changes
+}
+
+/// Append a change for every key whose value differs between two maps
+fn diff_maps(section: &str, before: &Map<String, Value>, after: &Map<String, Value>, changes: &mut Vec<Change>) {
+    for (name, old) in before {
+        if after.get(name) != Some(old) {
+            changes.push(Change {
+                section: section.to_string(),
+                name: name.clone(),
+                before: Some(old.clone()),
+                after: after.get(name).cloned(),
+            });
+        }
+    }
+
+    for (name, new) in after {
+        if !before.contains_key(name) {
+            changes.push(Change {
+                section: section.to_string(),
+                name: name.clone(),
+                before: None,
+                after: Some(new.clone()),
+            });
+        }
+    }
+}
+
+/// The map a change applies to inside a storage document
+fn section_mut<'a>(document: &'a mut Value, section: &str) -> Option<&'a mut Map<String, Value>> {
+    let root = document.as_object_mut()?;
+    if section == STORAGE_FIELDS {
+        return Some(root);
+    }
+
+    root.entry(section.to_string())
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+}
+
+/// Singular, human-readable name of a section
+fn section_label(section: &str) -> &str {
+    match section {
+        "commands" => "Command",
+        "asset_types" => "Asset type",
+        "entities" => "Entity",
+        "principles" => "Principle",
+        "asset_references" => "Asset",
+        _ => "Field",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::models::json_storage::JsonStorage;
+
+    #[test]
+    fn entries_undo_and_redo_their_changes() {
+        let before = json!({ "version": "1", "principles": { "a": { "long_name": "Architecture" } }, "entities": {} });
+        let after = json!({ "version": "1", "principles": { "arch": { "long_name": "Architecture" } }, "entities": { "u": { "description": "User" } } });
+
+        let mut journal = Journal::default();
+        journal.record("rename a; add u", diff_documents(&before, &after));
+        let entry = &journal.undo[0];
+        assert_eq!(entry.changes.len(), 3);
+
+        let mut document = after.clone();
+        assert!(entry.apply(&mut document, true).is_empty());
+        assert_eq!(document, before);
+
+        assert!(entry.apply(&mut document, false).is_empty());
+        assert_eq!(document, after);
+    }
+
+    #[test]
+    fn undo_notes_items_changed_since_the_operation() {
+        let before = json!({ "entities": { "u": { "description": "User" } } });
+        let after = json!({ "entities": { "u": { "description": "Customer" } } });
+        let mut journal = Journal::default();
+        journal.record("update u", diff_documents(&before, &after));
+
+        let mut document = json!({ "entities": { "u": { "description": "Client" } } });
+        let notes = journal.undo[0].apply(&mut document, true);
+        assert_eq!(notes.len(), 1);
+        assert_eq!(document, before);
+    }
+
+    #[test]
+    fn recording_clears_redo_and_caps_the_history() {
+        let mut journal = Journal::default();
+        for i in 0..MAX_JOURNAL_ENTRIES + 5 {
+            journal.record(&format!("operation {}", i), Vec::new());
+        }
+        assert_eq!(journal.undo.len(), MAX_JOURNAL_ENTRIES);
+        assert_eq!(journal.undo[0].id, 6);
+
+        let undone = journal.undo.pop().unwrap();
+        journal.redo.push(undone);
+        journal.record("new operation", Vec::new());
+        assert!(journal.redo.is_empty());
+        assert_eq!(journal.undo.last().unwrap().id, MAX_JOURNAL_ENTRIES as u64 + 6);
+    }
+
+    #[test]
+    fn saving_storage_records_one_entry_per_save() {
+        let project = tempfile::tempdir().unwrap();
+        let vql_dir = project.path().join("VQL");
+
+        drop(JsonStorage::load_or_create(&vql_dir).unwrap());
+        let mut storage = JsonStorage::load_or_create(&vql_dir).unwrap();
+        storage.add_entity("u", "User").unwrap();
+        storage.add_asset_type("c", "Controller").unwrap();
+        storage.save(&vql_dir).unwrap();
+        drop(storage);
+
+        let journal = Journal::load(&vql_dir).unwrap();
+        assert_eq!(journal.undo.len(), 1);
+        assert!(journal.undo[0].changes.iter().any(|c| c.section == "entities" && c.name == "u" && c.before.is_none()));
+
+        let mut document = serde_json::to_value(JsonStorage::load_or_create(&vql_dir).unwrap()).unwrap();
+        journal.undo[0].apply(&mut document, true);
+        assert!(document["entities"].as_object().unwrap().is_empty());
+        assert!(document["asset_types"].as_object().unwrap().is_empty());
+    }
+}
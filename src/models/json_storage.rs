@@ -1,9 +1,26 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use anyhow::{Result, Context};
 use serde::{Serialize, Deserialize};
 use chrono::Utc;
+use colored::Colorize;
+
+use crate::models::asset_paths;
+use crate::models::attribution::{Attribution, ReviewSource};
+use crate::models::backend::{self, JsonFileBackend, StorageBackend};
+use crate::models::discovery::AssetPattern;
+use crate::models::findings::Finding;
+use crate::models::journal::{self, Journal};
+use crate::models::layout::{StorageLayout, STORAGE_FILE_NAME};
+use crate::models::migration::{self, CURRENT_VERSION};
+use crate::models::rating::{self, RatingScale, DEFAULT_SCALE_NAME};
+use crate::models::tags;
+use crate::models::waiver::Waiver;
+use crate::utils::git;
+use crate::utils::lock::StorageLock;
 
 /// Represents a command in the VQL system
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +52,10 @@ pub struct AssetType {
     
     /// When this asset type was created or last modified
     pub last_modified: String,
+    
+    /// Filename and directory patterns `vql -discover` finds assets of this type with, e.g. `*Controller.{js,ts}`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
 }
 
 /// Represents an entity in the VQL system
@@ -48,6 +69,10 @@ pub struct Entity {
     
     /// When this entity was created or last modified
     pub last_modified: String,
+    
+    /// Entity this one sits under, e.g. a bounded context or module such as "billing"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
 }
 
 /// Represents a specific asset reference in the VQL system
@@ -62,48 +87,86 @@ pub struct AssetReference {
     /// Type of asset
     pub asset_type: String,
     
-    /// File path to the asset
+    /// File path to the asset: a file, a directory or a glob pattern such as "src/user/*.service.ts"
     pub path: String,
     
+    /// Further files, directories or glob patterns that make up the asset along with `path`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_paths: Vec<String>,
+    
     /// When this asset was created or last modified
     pub last_modified: String,
     
     /// Is this an exemplar (best practice reference)?
     pub exemplar: bool,
     
-    /// Map of principle short names to their reviews
-    #[serde(default)]
-    pub principle_reviews: HashMap<String, Review>,
+    /// Free-form tags such as "critical" or "legacy", lowercase and sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     
-    /// Architecture quality rating (H/M/L) - kept for backward compatibility
-    pub arch_rating: Option<String>,
+    /// Custom key/value metadata, e.g. owner = "payments-team"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
     
-    /// Security quality rating (H/M/L) - kept for backward compatibility
-    pub sec_rating: Option<String>,
+    /// Short names of the assets this one depends on, sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
     
-    /// Performance quality rating (H/M/L) - kept for backward compatibility
-    pub perf_rating: Option<String>,
+    /// Map of principle short names to their reviews
+    #[serde(default)]
+    pub principle_reviews: BTreeMap<String, Review>,
     
-    /// UI quality rating (H/M/L) - kept for backward compatibility
-    pub ui_rating: Option<String>,
+    /// Map of principle short names to every review stored for them, oldest first.
+    /// The last entry always matches the current review in `principle_reviews`.
+    #[serde(default)]
+    pub review_history: BTreeMap<String, Vec<Review>>,
     
-    /// Architecture analysis details - kept for backward compatibility
-    pub arch_analysis: Option<String>,
+    /// Map of principle short names to waivers accepting the asset's rating for them
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub waivers: BTreeMap<String, Waiver>,
+}
+
+impl AssetReference {
+    /// Every path of the asset, the main one first
+    pub fn paths(&self) -> Vec<&str> {
+        std::iter::once(self.path.as_str())
+            .chain(self.extra_paths.iter().map(String::as_str))
+            .collect()
+    }
     
-    /// Security analysis details - kept for backward compatibility
-    pub sec_analysis: Option<String>,
+    /// Findings of the asset's current reviews, with the principle each belongs to
+    pub fn findings(&self) -> Vec<(&str, &Finding)> {
+        self.principle_reviews.iter()
+            .flat_map(|(principle, review)| review.findings.iter().map(move |finding| (principle.as_str(), finding)))
+            .collect()
+    }
     
-    /// Performance analysis details - kept for backward compatibility
-    pub perf_analysis: Option<String>,
+    /// The waiver for a principle if it is still active; a waiver on a principle covers its sub-principles
+    pub fn active_waiver(&self, principle: &str) -> Option<&Waiver> {
+        let mut name = principle;
+        loop {
+            if let Some(waiver) = self.waivers.get(name).filter(|w| w.is_active()) {
+                return Some(waiver);
+            }
+            name = name.rsplit_once('.')?.0;
+        }
+    }
     
-    /// UI analysis details - kept for backward compatibility
-    pub ui_analysis: Option<String>,
+    /// Number for the asset's next finding: one more than any it has had, in current reviews or history
+    fn next_finding_id(&self) -> u32 {
+        self.principle_reviews.values()
+            .chain(self.review_history.values().flatten())
+            .flat_map(|review| review.findings.iter().map(|finding| finding.id))
+            .max()
+            .unwrap_or(0) + 1
+    }
 }
 
 /// Represents a principle in the VQL system
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Principle {
-    /// Short name for the principle (single character)
+    /// Short name for the principle (letters, digits and underscores, e.g. "a" or "arch");
+    /// sub-principles are named after their parent, e.g. "a.dry"
     pub short_name: String,
     
     /// Long name for the principle
@@ -114,12 +177,52 @@ pub struct Principle {
     
     /// When this principle was created or last modified
     pub last_modified: String,
+    
+    /// Alternative names that resolve to this principle wherever one is accepted (e.g. "sec" for "s")
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    
+    /// Revision of the guidance, starting at 1 and bumped whenever the guidance text changes
+    #[serde(default = "first_revision")]
+    pub revision: u32,
+    
+    /// How much this principle counts in quality scores relative to others (1 when not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    
+    /// Rating scale for reviews of this principle; inherited from the parent or the project default when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating_scale: Option<String>,
+}
+
+impl Principle {
+    /// Weight used for scoring, defaulting to 1
+    pub fn effective_weight(&self) -> f64 {
+        self.weight.unwrap_or(1.0)
+    }
+    
+    /// Short name of the principle this one is a sub-principle of, if any
+    pub fn parent(&self) -> Option<&str> {
+        self.short_name.rsplit_once('.').map(|(parent, _)| parent)
+    }
+    
+    /// Whether a review was written against older guidance than this principle now has
+    ///
+    /// Reviews stored before revisions were tracked count as written against revision 1.
+    pub fn is_stale(&self, review: &Review) -> bool {
+        review.principle_revision.unwrap_or(1) < self.revision
+    }
+}
+
+/// Revision given to new principles and to principles stored before revisions were tracked
+fn first_revision() -> u32 {
+    1
 }
 
 /// Represents a review from a principle
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Review {
-    /// Rating for this review (H/M/L)
+    /// Rating for this review, a value of the principle's rating scale (H/M/L by default)
     pub rating: Option<String>,
     
     /// Analysis for this review
@@ -127,6 +230,38 @@ pub struct Review {
     
     /// When this review was last modified
     pub last_modified: String,
+    
+    /// Revision of the principle's guidance this review was written against
+    #[serde(default)]
+    pub principle_revision: Option<u32>,
+    
+    /// Person who wrote or accepted the review
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    
+    /// Interface the review was stored through
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<ReviewSource>,
+    
+    /// AI model or session that wrote the review
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    
+    /// How confident the reviewer was in the rating, from 0 to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    
+    /// Specific problems the review points out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<Finding>,
+    
+    /// Hash of the asset's files when the review was stored (see `asset_paths::content_hash`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    
+    /// Git commit checked out when the review was stored, if the project is a git repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
 }
 
 /// Main storage structure for VQL JSON data
@@ -142,20 +277,54 @@ pub struct JsonStorage {
     pub last_modified: String,
     
     /// Map of command names (without colon) to their configurations
-    pub commands: HashMap<String, CommandConfig>,
+    pub commands: BTreeMap<String, CommandConfig>,
     
     /// Map of asset type short names to their configurations
-    pub asset_types: HashMap<String, AssetType>,
+    pub asset_types: BTreeMap<String, AssetType>,
     
     /// Map of entity short names to their configurations
-    pub entities: HashMap<String, Entity>,
+    pub entities: BTreeMap<String, Entity>,
     
     /// Map of principle short names to their configurations
     #[serde(default)]
-    pub principles: HashMap<String, Principle>,
+    pub principles: BTreeMap<String, Principle>,
     
     /// Map of asset reference short names to their configurations
-    pub asset_references: HashMap<String, AssetReference>,
+    pub asset_references: BTreeMap<String, AssetReference>,
+    
+    /// Project rating scales by name, in addition to the built-in "hml"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rating_scales: BTreeMap<String, RatingScale>,
+    
+    /// Scale used by principles that do not set their own; "hml" when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_rating_scale: Option<String>,
+    
+    /// Patterns of files and directories `vql -discover` skips, on top of the built-in ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub discovery_ignore: Vec<String>,
+    
+    /// Advisory lock on the VQL directory, held from load until this storage is dropped
+    #[serde(skip)]
+    lock: Option<Arc<StorageLock>>,
+    
+    /// Backend this storage was loaded from and is saved back to
+    #[serde(skip)]
+    backend: Option<Arc<dyn StorageBackend>>,
+    
+    /// Snapshot taken at load and the operations applied since, journaled on save
+    #[serde(skip)]
+    pending: RefCell<PendingJournal>,
+}
+
+/// Changes made since storage was loaded, waiting to be written to the journal
+#[derive(Debug, Clone, Default)]
+struct PendingJournal {
+    /// Storage as loaded or last saved; `None` until storage has been loaded or saved once
+    baseline: Option<serde_json::Value>,
+    
+    /// Descriptions of the operations applied since the baseline
+    operations: Vec<String>,
 }
 
 impl JsonStorage {
@@ -164,56 +333,234 @@ impl JsonStorage {
         let now = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
         JsonStorage {
-            version: "1.0.0".to_string(),
+            version: CURRENT_VERSION.to_string(),
             created: now.clone(),
             last_modified: now,
-            commands: HashMap::new(),
-            asset_types: HashMap::new(),
-            entities: HashMap::new(),
-            principles: HashMap::new(),
-            asset_references: HashMap::new(),
+            commands: BTreeMap::new(),
+            asset_types: BTreeMap::new(),
+            entities: BTreeMap::new(),
+            principles: BTreeMap::new(),
+            asset_references: BTreeMap::new(),
+            rating_scales: BTreeMap::new(),
+            default_rating_scale: None,
+            discovery_ignore: Vec::new(),
+            lock: None,
+            backend: None,
+            pending: RefCell::new(PendingJournal::default()),
         }
     }
     
     /// Find and load JSON storage from the specified path or create a new one
+    ///
+    /// The returned storage holds the VQL directory lock until it is dropped.
     pub fn load_or_create(vql_path: &Path) -> Result<Self> {
-        let json_file_path = vql_path.join("vql_storage.json");
+        if let Some(backend) = backend::thread_backend() {
+            let lock = backend.lock()?;
+            let mut storage = Self::read_from_backend(backend)?;
+            storage.lock = lock.map(Arc::new);
+            return Ok(storage);
+        }
         
-        if json_file_path.exists() {
-            // Load existing storage
-            let content = fs::read_to_string(&json_file_path)
-                .context(format!("Failed to read VQL storage at {}", json_file_path.display()))?;
-                
-            let storage: JsonStorage = serde_json::from_str(&content)
-                .context("Failed to parse VQL JSON storage")?;
-                
-            Ok(storage)
-        } else {
-            // Create new storage
-            let storage = JsonStorage::new();
-            storage.save(vql_path)?;
-            Ok(storage)
+        if !vql_path.exists() {
+            fs::create_dir_all(vql_path)
+                .context(format!("Failed to create VQL directory at {}", vql_path.display()))?;
         }
+        
+        let lock = StorageLock::acquire(vql_path)?;
+        
+        let mut storage = match backend::detect_backend(vql_path) {
+            // Load existing storage
+            Some(backend) => Self::read_from_backend(backend)?,
+            None => {
+                // Create new storage
+                let mut storage = JsonStorage::new();
+                storage.backend = Some(Arc::new(JsonFileBackend::new(vql_path, StorageLayout::Single)));
+                storage.write()?;
+                storage
+            }
+        };
+        
+        storage.lock = Some(Arc::new(lock));
+        Ok(storage)
     }
     
     /// Save storage to the specified path
+    ///
+    /// Storage is written back to the backend it was loaded from; new storage goes to
+    /// whatever backend already holds the VQL directory, or a single JSON file.
+    /// If this storage was not loaded under a lock, the lock is taken for the duration of the write.
     pub fn save(&self, vql_path: &Path) -> Result<()> {
-        // Make sure the directory exists
-        if !vql_path.exists() {
-            fs::create_dir_all(vql_path)
-                .context(format!("Failed to create VQL directory at {}", vql_path.display()))?;
-        }
+        let backend = match (&self.backend, backend::thread_backend()) {
+            (Some(backend), _) => backend.clone(),
+            (None, Some(backend)) => backend,
+            (None, None) => {
+                // Make sure the directory exists
+                if !vql_path.exists() {
+                    fs::create_dir_all(vql_path)
+                        .context(format!("Failed to create VQL directory at {}", vql_path.display()))?;
+                }
+                
+                backend::detect_backend(vql_path)
+                    .unwrap_or_else(|| Arc::new(JsonFileBackend::new(vql_path, StorageLayout::Single)))
+            }
+        };
+        
+        let _write_lock = match self.lock {
+            Some(_) => None,
+            None => backend.lock()?,
+        };
+        
+        backend.save(self)?;
         
-        let json_file_path = vql_path.join("vql_storage.json");
+        // Record what changed so it can be undone
+        if let Some(vql_dir) = backend.directory() {
+            self.journal_changes(vql_dir)?;
+        }
         
-        // Serialize with pretty printing
-        let json_content = serde_json::to_string_pretty(self)
+        Ok(())
+    }
+    
+    /// Note an operation for the journal entry written on the next save
+    pub fn record_operation(&mut self, description: String) {
+        self.pending.get_mut().operations.push(description);
+    }
+    
+    /// Append everything changed since the baseline to the journal as one operation
+    fn journal_changes(&self, vql_dir: &Path) -> Result<()> {
+        let current = serde_json::to_value(self)
             .context("Failed to serialize VQL storage to JSON")?;
+        let mut pending = self.pending.borrow_mut();
+        
+        if let Some(baseline) = &pending.baseline {
+            let changes = journal::diff_documents(baseline, &current);
+            if !changes.is_empty() {
+                let operation = if pending.operations.is_empty() {
+                    "update".to_string()
+                } else {
+                    pending.operations.join("; ")
+                };
+                
+                let mut journal = Journal::load(vql_dir)?;
+                journal.record(&operation, changes);
+                journal.save(vql_dir)?;
+            }
+        }
+        
+        pending.baseline = Some(current);
+        pending.operations.clear();
+        Ok(())
+    }
+    
+    /// Replace the contents of this storage with a storage document, e.g. one produced by undo
+    ///
+    /// The document becomes the new journal baseline, so saving it records no new operation.
+    pub fn restore(&mut self, document: serde_json::Value) -> Result<()> {
+        self.replace_contents(document.clone())?;
+        
+        *self.pending.get_mut() = PendingJournal {
+            baseline: Some(document),
+            operations: Vec::new(),
+        };
+        Ok(())
+    }
+    
+    /// Replace the contents of this storage with a storage document built elsewhere, e.g. by a batch script
+    ///
+    /// Unlike `restore`, the replacement is journaled as `operation` on the next save.
+    pub fn apply_document(&mut self, document: serde_json::Value, operation: String) -> Result<()> {
+        self.replace_contents(document)?;
+        self.record_operation(operation);
+        Ok(())
+    }
+    
+    /// Copy every data field from a storage document, leaving lock, backend and journal state alone
+    fn replace_contents(&mut self, document: serde_json::Value) -> Result<()> {
+        let replacement: JsonStorage = serde_json::from_value(document)
+            .context("Failed to read replacement VQL storage")?;
+        
+        self.version = replacement.version;
+        self.created = replacement.created;
+        self.last_modified = replacement.last_modified;
+        self.commands = replacement.commands;
+        self.asset_types = replacement.asset_types;
+        self.entities = replacement.entities;
+        self.principles = replacement.principles;
+        self.asset_references = replacement.asset_references;
+        self.rating_scales = replacement.rating_scales;
+        self.default_rating_scale = replacement.default_rating_scale;
+        self.discovery_ignore = replacement.discovery_ignore;
+        Ok(())
+    }
+    
+    /// Backend this storage is saved to, if it has one yet
+    pub fn backend(&self) -> Option<Arc<dyn StorageBackend>> {
+        self.backend.clone()
+    }
+    
+    /// Change the backend used by the next save
+    ///
+    /// Data in the previous backend is left in place; see `StorageBackend::remove`.
+    pub fn set_backend(&mut self, backend: Arc<dyn StorageBackend>) {
+        self.backend = Some(backend);
+    }
+    
+    /// Parse storage from a JSON string, upgrading older schema versions in memory
+    pub fn from_json(content: &str) -> Result<(Self, migration::MigrationReport)> {
+        let mut document: serde_json::Value = serde_json::from_str(content)
+            .context("Failed to parse VQL JSON storage")?;
+        
+        let report = migration::migrate(&mut document)?;
+        
+        let storage: JsonStorage = serde_json::from_value(document)
+            .context("Failed to parse VQL JSON storage")?;
+            
+        Ok((storage, report))
+    }
+    
+    /// Read storage from a backend, migrating it to the current schema if needed
+    ///
+    /// Must be called while holding the storage lock: if a migration runs, the original
+    /// document is backed up in the VQL directory and storage is rewritten in the upgraded format.
+    fn read_from_backend(backend: Arc<dyn StorageBackend>) -> Result<Self> {
+        let content = backend.load()?;
             
-        fs::write(&json_file_path, json_content)
-            .context(format!("Failed to write VQL storage to {}", json_file_path.display()))?;
+        let (mut storage, report) = Self::from_json(&content)?;
+        storage.backend = Some(backend.clone());
+        storage.pending.get_mut().baseline = Some(serde_json::to_value(&storage)
+            .context("Failed to serialize VQL storage to JSON")?);
+        
+        if report.migrated() {
+            let backup_path = backend.directory()
+                .map(|dir| migration_backup_path(&dir.join(STORAGE_FILE_NAME), &report.from_version));
+            if let Some(backup_path) = &backup_path {
+                fs::write(backup_path, &content)
+                    .context(format!("Failed to back up VQL storage to {}", backup_path.display()))?;
+            }
             
-        Ok(())
+            storage.write()?;
+            
+            println!("{} Upgraded VQL storage from schema {} to {} (backup: {})", 
+                "MIGRATED:".yellow().bold(),
+                report.from_version,
+                CURRENT_VERSION,
+                backup_path.map(|p| p.display().to_string()).unwrap_or_else(|| "none".to_string()));
+            for step in &report.steps {
+                println!("  - {}", step);
+            }
+            for note in &report.notes {
+                println!("  {} {}", "NOTE:".cyan().bold(), note);
+            }
+        }
+            
+        Ok(storage)
+    }
+    
+    /// Write storage to its backend without taking the lock (the caller already holds it)
+    fn write(&self) -> Result<()> {
+        match &self.backend {
+            Some(backend) => backend.save(self),
+            None => Err(anyhow::anyhow!("VQL storage has no backend to write to")),
+        }
     }
     
     /// Find what type an item is by its name
@@ -242,6 +589,15 @@ impl JsonStorage {
             ));
         }
         
+        // Check principle aliases
+        if let Some(principle) = self.principles.values().find(|p| p.aliases.iter().any(|a| a == name)) {
+            return Err(anyhow::anyhow!(
+                "Short name '{}' already in use as an alias of principle '{}'", 
+                name, 
+                principle.short_name
+            ));
+        }
+        
         // Check entities
         if let Some(entity) = self.entities.get(name) {
             return Err(anyhow::anyhow!(
@@ -294,6 +650,7 @@ impl JsonStorage {
         // Add to commands map
         self.commands.insert(name.to_string(), command);
         
+        self.record_operation(format!("add command :{}", name));
         // Update last modified timestamp
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
@@ -339,6 +696,7 @@ impl JsonStorage {
             self.commands.insert(new_name.to_string(), updated_command);
         }
         
+        self.record_operation(format!("rename command :{} to :{}", old_name, new_name));
         // Update last modified timestamp
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
@@ -372,11 +730,47 @@ impl JsonStorage {
             short_name: short_name.to_string(),
             description: description.to_string(),
             last_modified: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            patterns: Vec::new(),
         };
         
         // Add to asset types map
         self.asset_types.insert(short_name.to_string(), asset_type);
         
+        self.record_operation(format!("add asset type {}", short_name));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Set the patterns `vql -discover` finds assets of a type with (none to stop discovering the type)
+    pub fn set_asset_type_patterns(&mut self, short_name: &str, patterns: &[&str]) -> Result<()> {
+        for pattern in patterns {
+            AssetPattern::new(pattern)?;
+        }
+        
+        let asset_type = self.asset_types.get_mut(short_name)
+            .ok_or_else(|| anyhow::anyhow!("Asset type {} does not exist", short_name))?;
+        
+        asset_type.patterns = patterns.iter().map(|p| p.trim().to_string()).collect();
+        asset_type.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        self.record_operation(format!("set discovery patterns {} {}", short_name, patterns.join(" ")));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Set the patterns `vql -discover` skips on top of the built-in ones
+    pub fn set_discovery_ignore(&mut self, patterns: &[&str]) -> Result<()> {
+        for pattern in patterns {
+            AssetPattern::new(pattern)?;
+        }
+        
+        self.discovery_ignore = patterns.iter().map(|p| p.trim().to_string()).collect();
+        
+        self.record_operation(format!("set discovery ignore patterns {}", patterns.join(" ")));
         // Update last modified timestamp
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
@@ -393,11 +787,13 @@ impl JsonStorage {
             short_name: short_name.to_string(),
             description: description.to_string(),
             last_modified: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            parent: None,
         };
         
         // Add to entities map
         self.entities.insert(short_name.to_string(), entity);
         
+        self.record_operation(format!("add entity {}", short_name));
         // Update last modified timestamp
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
@@ -431,29 +827,30 @@ impl JsonStorage {
             entity: entity.to_string(),
             asset_type: asset_type.to_string(),
             path: path.to_string(),
+            extra_paths: Vec::new(),
             last_modified: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
             exemplar: false,
-            principle_reviews: HashMap::new(),
-            arch_rating: None,
-            sec_rating: None,
-            perf_rating: None,
-            ui_rating: None,
-            arch_analysis: None,
-            sec_analysis: None,
-            perf_analysis: None,
-            ui_analysis: None,
+            tags: Vec::new(),
+            metadata: BTreeMap::new(),
+            depends_on: Vec::new(),
+            principle_reviews: BTreeMap::new(),
+            review_history: BTreeMap::new(),
+            waivers: BTreeMap::new(),
         };
         
         // Add to asset references map
         self.asset_references.insert(short_name.to_string(), asset_reference);
         
+        self.record_operation(format!("add asset {}", short_name));
         // Update last modified timestamp
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
         Ok(())
     }
     
-    /// Set review data for an asset
+    /// Set review data for an asset using a legacy aspect name (arch/sec/perf/ui)
+    ///
+    /// The aspect is mapped to its principle short name (a/s/p/u) and stored as a principle review.
     pub fn set_asset_review(
         &mut self,
         asset_name: &str,
@@ -461,45 +858,15 @@ impl JsonStorage {
         rating: &str,
         analysis: &str
     ) -> Result<()> {
-        // Find the asset
-        let asset = match self.asset_references.get_mut(asset_name) {
-            Some(asset) => asset,
-            None => return Err(anyhow::anyhow!("Asset {} not found", asset_name)),
-        };
-        
-        // Validate rating
-        if !["H", "M", "L"].contains(&rating) {
-            return Err(anyhow::anyhow!("Invalid rating: {}. Must be H, M, or L", rating));
-        }
-        
-        // Update the appropriate fields based on aspect
-        match aspect.to_lowercase().as_str() {
-            "arch" => {
-                asset.arch_rating = Some(rating.to_string());
-                asset.arch_analysis = Some(analysis.to_string());
-            },
-            "sec" => {
-                asset.sec_rating = Some(rating.to_string());
-                asset.sec_analysis = Some(analysis.to_string());
-            },
-            "perf" => {
-                asset.perf_rating = Some(rating.to_string());
-                asset.perf_analysis = Some(analysis.to_string());
-            },
-            "ui" => {
-                asset.ui_rating = Some(rating.to_string());
-                asset.ui_analysis = Some(analysis.to_string());
-            },
+        let principle = match aspect.to_lowercase().as_str() {
+            "arch" => "a",
+            "sec" => "s",
+            "perf" => "p",
+            "ui" => "u",
             _ => return Err(anyhow::anyhow!("Invalid aspect: {}. Must be arch, sec, perf, or ui", aspect)),
-        }
-        
-        // Update asset last modified
-        asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
-        
-        // Update storage last modified
-        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        };
         
-        Ok(())
+        self.store_asset_review(asset_name, principle, Some(rating), analysis, &Attribution::default(), Vec::new())
     }
     
     /// Set exemplar status for an asset
@@ -516,160 +883,916 @@ impl JsonStorage {
         // Update asset last modified
         asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
+        self.record_operation(format!("set exemplar {} {}", asset_name, status));
         // Update storage last modified
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
-        
+
         Ok(())
     }
-    
-    /// Add or update a principle
-    pub fn add_principle(&mut self, short_name: &str, long_name: &str, guidance: Option<&str>) -> Result<()> {
-        // Validate short name (single character)
-        if short_name.chars().count() != 1 {
-            return Err(anyhow::anyhow!("Principle short name must be a single character"));
-        }
-        
-        // Check name availability across all types
-        self.check_name_availability(short_name)?;
-        
-        // Create new principle
-        let principle = Principle {
-            short_name: short_name.to_string(),
-            long_name: long_name.to_string(),
-            guidance: guidance.map(|g| g.to_string()),
-            last_modified: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+
+    /// Replace the paths of an asset; the first becomes its main path
+    pub fn set_asset_paths(&mut self, asset_name: &str, paths: &[&str]) -> Result<()> {
+        let Some((first, rest)) = paths.split_first() else {
+            return Err(anyhow::anyhow!("An asset needs at least one path"));
         };
         
-        // Add to principles map
-        self.principles.insert(short_name.to_string(), principle);
+        let asset = self.asset_references.get_mut(asset_name)
+            .ok_or_else(|| anyhow::anyhow!("Asset {} not found", asset_name))?;
+        
+        asset.path = first.to_string();
+        asset.extra_paths = rest.iter().map(|p| p.to_string()).collect();
+        asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
+        self.record_operation(format!("set paths {} {}", asset_name, paths.join(" ")));
         // Update last modified timestamp
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
         Ok(())
     }
     
-    /// Store review data for an asset with a specific principle
-    pub fn store_asset_review(
-        &mut self,
-        asset_name: &str,
-        principle: &str,
-        rating: Option<&str>,
-        analysis: &str
-    ) -> Result<()> {
-        // Find the asset
-        let asset = match self.asset_references.get_mut(asset_name) {
-            Some(asset) => asset,
-            None => return Err(anyhow::anyhow!("Asset {} not found", asset_name)),
-        };
-        
-        // Validate principle exists
-        if !self.principles.contains_key(principle) {
-            return Err(anyhow::anyhow!("Principle {} does not exist", principle));
+    /// Record that an asset depends on others, returning the dependencies that were new
+    pub fn add_asset_dependencies(&mut self, asset_name: &str, dependencies: &[&str]) -> Result<Vec<String>> {
+        for dep in dependencies {
+            if !self.asset_references.contains_key(*dep) {
+                return Err(anyhow::anyhow!("Asset {} not found", dep));
+            }
+            if *dep == asset_name {
+                return Err(anyhow::anyhow!("Asset {} cannot depend on itself", asset_name));
+            }
         }
         
-        // Validate rating if provided
-        if let Some(r) = rating {
-            if !["H", "M", "L"].contains(&r) {
-                return Err(anyhow::anyhow!("Invalid rating: {}. Must be H, M, or L", r));
+        let asset = self.asset_references.get_mut(asset_name)
+            .ok_or_else(|| anyhow::anyhow!("Asset {} not found", asset_name))?;
+        
+        let mut added = Vec::new();
+        for dep in dependencies {
+            if !asset.depends_on.iter().any(|d| d == dep) && !added.iter().any(|d| d == dep) {
+                asset.depends_on.push(dep.to_string());
+                added.push(dep.to_string());
             }
         }
+        asset.depends_on.sort();
+        asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
-        // Create review
-        let review = Review {
-            rating: rating.map(|r| r.to_string()),
-            analysis: Some(analysis.to_string()),
-            last_modified: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
-        };
-        
-        // Add review to asset
-        asset.principle_reviews.insert(principle.to_string(), review);
+        self.record_operation(format!("add dependency {} -> {}", asset_name, added.join(" ")));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
-        // Update asset last modified
+        Ok(added)
+    }
+    
+    /// Remove dependencies of an asset, returning the ones it had
+    pub fn remove_asset_dependencies(&mut self, asset_name: &str, dependencies: &[&str]) -> Result<Vec<String>> {
+        let asset = self.asset_references.get_mut(asset_name)
+            .ok_or_else(|| anyhow::anyhow!("Asset {} not found", asset_name))?;
+        
+        let removed: Vec<String> = asset.depends_on.iter()
+            .filter(|dep| dependencies.contains(&dep.as_str()))
+            .cloned()
+            .collect();
+        asset.depends_on.retain(|dep| !removed.contains(dep));
         asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
-        // Update storage last modified
+        self.record_operation(format!("remove dependency {} -> {}", asset_name, removed.join(" ")));
+        // Update last modified timestamp
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
-        // For backward compatibility, also update the legacy fields
-        match principle {
-            "a" => {
-                asset.arch_rating = rating.map(|r| r.to_string());
-                asset.arch_analysis = Some(analysis.to_string());
-            },
-            "s" => {
-                asset.sec_rating = rating.map(|r| r.to_string());
-                asset.sec_analysis = Some(analysis.to_string());
-            },
-            "p" => {
-                asset.perf_rating = rating.map(|r| r.to_string());
-                asset.perf_analysis = Some(analysis.to_string());
-            },
-            "u" => {
-                asset.ui_rating = rating.map(|r| r.to_string());
-                asset.ui_analysis = Some(analysis.to_string());
-            },
-            _ => {
-                // For other principles, don't update legacy fields
-            }
-        }
-        
-        Ok(())
+        Ok(removed)
     }
     
-    /// Get review for an asset from a specific principle
-    pub fn get_asset_review(&self, asset_name: &str, principle: &str) -> Result<Option<&Review>> {
-        // Find the asset
-        let asset = match self.asset_references.get(asset_name) {
-            Some(asset) => asset,
-            None => return Err(anyhow::anyhow!("Asset {} not found", asset_name)),
-        };
-        
-        // Return the review if it exists
-        Ok(asset.principle_reviews.get(principle))
+    /// Assets that depend directly on the given one, sorted
+    pub fn asset_dependents(&self, asset_name: &str) -> Vec<&str> {
+        self.asset_references.iter()
+            .filter(|(_, asset)| asset.depends_on.iter().any(|dep| dep == asset_name))
+            .map(|(name, _)| name.as_str())
+            .collect()
     }
     
-    /// Get all reviews for an asset
-    pub fn get_asset_reviews(&self, asset_name: &str) -> Result<&HashMap<String, Review>> {
-        // Find the asset
-        let asset = match self.asset_references.get(asset_name) {
-            Some(asset) => asset,
-            None => return Err(anyhow::anyhow!("Asset {} not found", asset_name)),
-        };
+    /// The given assets plus everything that depends on them, directly or through other assets
+    pub fn downstream_assets(&self, assets: &[&str]) -> Vec<String> {
+        let mut selected: Vec<String> = assets.iter().map(|a| a.to_string()).collect();
+        let mut queue: Vec<String> = selected.clone();
+        
+        while let Some(asset) = queue.pop() {
+            for dependent in self.asset_dependents(&asset) {
+                if !selected.iter().any(|s| s == dependent) {
+                    selected.push(dependent.to_string());
+                    queue.push(dependent.to_string());
+                }
+            }
+        }
         
-        // Return all reviews
-        Ok(&asset.principle_reviews)
+        selected.sort();
+        selected
     }
     
-    /// Rename a principle
-    pub fn rename_principle(&mut self, old_name: &str, new_name: &str) -> Result<()> {
-        // Check if old principle exists
-        if !self.principles.contains_key(old_name) {
-            return Err(anyhow::anyhow!("Principle '{}' not found", old_name));
+    /// Add tags to an asset, returning the ones it did not already have
+    pub fn tag_asset(&mut self, asset_name: &str, tags: &[&str]) -> Result<Vec<String>> {
+        let tags = tags.iter().map(|t| tags::normalize_tag(t)).collect::<Result<Vec<_>>>()?;
+
+        let asset = self.asset_references.get_mut(asset_name)
+            .ok_or_else(|| anyhow::anyhow!("Asset {} not found", asset_name))?;
+
+        let mut added = Vec::new();
+        for tag in tags {
+            if !asset.tags.contains(&tag) {
+                asset.tags.push(tag.clone());
+                added.push(tag);
+            }
         }
+        asset.tags.sort();
+        asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        self.record_operation(format!("tag asset {} {}", asset_name, added.join(" ")));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        Ok(added)
+    }
+
+    /// Remove tags from an asset, returning the ones it had
+    pub fn untag_asset(&mut self, asset_name: &str, tags: &[&str]) -> Result<Vec<String>> {
+        let tags = tags.iter().map(|t| tags::normalize_tag(t)).collect::<Result<Vec<_>>>()?;
+
+        let asset = self.asset_references.get_mut(asset_name)
+            .ok_or_else(|| anyhow::anyhow!("Asset {} not found", asset_name))?;
+
+        let removed: Vec<String> = tags.into_iter().filter(|tag| asset.tags.contains(tag)).collect();
+        asset.tags.retain(|tag| !removed.contains(tag));
+        asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        self.record_operation(format!("untag asset {} {}", asset_name, removed.join(" ")));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        Ok(removed)
+    }
+
+    /// Waive an asset's rating for a principle, replacing any earlier waiver for the pair
+    pub fn waive(&mut self, asset_name: &str, principle: &str, waiver: Waiver) -> Result<()> {
+        let principle = self.resolve_principle(principle)
+            .ok_or_else(|| anyhow::anyhow!("Principle {} does not exist", principle))?
+            .to_string();
+        let asset = self.asset_references.get_mut(asset_name)
+            .ok_or_else(|| anyhow::anyhow!("Asset {} not found", asset_name))?;
+        
+        let expires = waiver.expires.clone();
+        asset.waivers.insert(principle.clone(), waiver);
+        asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
-        // Check if new name is available
-        self.check_name_availability(new_name)?;
-        
-        // Get the principle data
-        let principle = self.principles.remove(old_name)
-            .ok_or_else(|| anyhow::anyhow!("Failed to remove principle"))?;
-        
-        // Update principle with new name
-        let mut updated_principle = principle;
-        updated_principle.short_name = new_name.to_string();
-        updated_principle.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
-        
-        // Insert with new name
-        self.principles.insert(new_name.to_string(), updated_principle);
+        self.record_operation(format!("waive {} {} until {}", asset_name, principle, expires));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Remove the waiver for an asset and principle, returning it
+    pub fn unwaive(&mut self, asset_name: &str, principle: &str) -> Result<Waiver> {
+        let key = self.resolve_principle(principle).unwrap_or(principle).to_string();
+        let asset = self.asset_references.get_mut(asset_name)
+            .ok_or_else(|| anyhow::anyhow!("Asset {} not found", asset_name))?;
+        
+        let waiver = asset.waivers.remove(&key)
+            .ok_or_else(|| anyhow::anyhow!("Asset {} has no waiver for principle {}", asset_name, principle))?;
+        asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        self.record_operation(format!("remove waiver {} {}", asset_name, key));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(waiver)
+    }
+    
+    /// Every waiver as (asset, principle, waiver), sorted by asset then principle
+    pub fn waivers(&self) -> Vec<(&str, &str, &Waiver)> {
+        self.asset_references.iter()
+            .flat_map(|(asset, reference)| reference.waivers.iter()
+                .map(move |(principle, waiver)| (asset.as_str(), principle.as_str(), waiver)))
+            .collect()
+    }
+    
+    /// Set a metadata value on an asset, or remove the key when the value is None
+    pub fn set_asset_metadata(&mut self, asset_name: &str, key: &str, value: Option<&str>) -> Result<()> {
+        tags::validate_metadata_key(key)?;
+
+        let asset = self.asset_references.get_mut(asset_name)
+            .ok_or_else(|| anyhow::anyhow!("Asset {} not found", asset_name))?;
+
+        match value {
+            Some(value) => { asset.metadata.insert(key.to_string(), value.to_string()); },
+            None => {
+                if asset.metadata.remove(key).is_none() {
+                    return Err(anyhow::anyhow!("Asset {} has no metadata key {}", asset_name, key));
+                }
+            },
+        }
+        asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        self.record_operation(format!("set metadata {} {}={}", asset_name, key, value.unwrap_or_default()));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        Ok(())
+    }
+
+    /// Place an entity under a parent entity (a bounded context or module), or at the top level with None
+    pub fn set_entity_parent(&mut self, name: &str, parent: Option<&str>) -> Result<()> {
+        if !self.entities.contains_key(name) {
+            return Err(anyhow::anyhow!("Entity '{}' not found", name));
+        }
+        
+        if let Some(parent) = parent {
+            if !self.entities.contains_key(parent) {
+                return Err(anyhow::anyhow!("Entity '{}' not found", parent));
+            }
+            if self.entity_in_context(parent, name) {
+                return Err(anyhow::anyhow!("Cannot place entity '{}' under '{}' - '{}' is already under '{}'", name, parent, parent, name));
+            }
+        }
+        
+        if let Some(entity) = self.entities.get_mut(name) {
+            entity.parent = parent.map(str::to_string);
+            entity.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        }
+        
+        self.record_operation(format!("set entity parent {} {}", name, parent.unwrap_or("none")));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Entities directly under the given one, sorted
+    pub fn entity_children(&self, name: &str) -> Vec<&str> {
+        self.entities.iter()
+            .filter(|(_, entity)| entity.parent.as_deref() == Some(name))
+            .map(|(key, _)| key.as_str())
+            .collect()
+    }
+    
+    /// Whether an entity is the given context or sits anywhere under it
+    pub fn entity_in_context(&self, entity: &str, context: &str) -> bool {
+        let mut current = Some(entity);
+        // Bounded by the number of entities in case stored parent links form a loop
+        for _ in 0..=self.entities.len() {
+            match current {
+                Some(name) if name == context => return true,
+                Some(name) => current = self.entities.get(name).and_then(|e| e.parent.as_deref()),
+                None => return false,
+            }
+        }
+        false
+    }
+    
+    /// Add or update a principle
+    pub fn add_principle(&mut self, short_name: &str, long_name: &str, guidance: Option<&str>) -> Result<()> {
+        // Validate short name (same identifier rules as asset names, plus a parent for sub-principles)
+        self.validate_principle_path(short_name)?;
+        
+        // Check name availability across all types
+        self.check_name_availability(short_name)?;
+        
+        // Create new principle
+        let principle = Principle {
+            short_name: short_name.to_string(),
+            long_name: long_name.to_string(),
+            guidance: guidance.map(|g| g.to_string()),
+            last_modified: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            aliases: Vec::new(),
+            revision: first_revision(),
+            weight: None,
+            rating_scale: None,
+        };
+        
+        // Add to principles map
+        self.principles.insert(short_name.to_string(), principle);
+        
+        self.record_operation(format!("add principle {}", short_name));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Update a principle's long name and guidance, returning its revision afterwards
+    ///
+    /// The revision is bumped only when the guidance text actually changes, which makes
+    /// every review written against the old guidance stale.
+    pub fn update_principle(&mut self, short_name: &str, long_name: Option<&str>, guidance: Option<&str>) -> Result<u32> {
+        let key = self.resolve_principle(short_name)
+            .ok_or_else(|| anyhow::anyhow!("Principle '{}' not found", short_name))?
+            .to_string();
+        
+        let principle = self.principles.get_mut(&key)
+            .ok_or_else(|| anyhow::anyhow!("Principle '{}' not found", short_name))?;
+        
+        let mut changed = false;
+        if let Some(long_name) = long_name {
+            if principle.long_name != long_name {
+                principle.long_name = long_name.to_string();
+                changed = true;
+            }
+        }
+        
+        let guidance = guidance.map(str::to_string);
+        if guidance.is_some() && principle.guidance != guidance {
+            principle.guidance = guidance;
+            principle.revision += 1;
+            changed = true;
+        }
+        
+        let revision = principle.revision;
+        if changed {
+            principle.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            
+            self.record_operation(format!("update principle {}", key));
+            // Update last modified timestamp
+            self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        }
+        
+        Ok(revision)
+    }
+    
+    /// Set how much a principle counts in quality scores; None restores the default of 1
+    pub fn set_principle_weight(&mut self, short_name: &str, weight: Option<f64>) -> Result<()> {
+        if let Some(w) = weight {
+            if !w.is_finite() || w < 0.0 {
+                return Err(anyhow::anyhow!("Invalid weight {}: must be a number of 0 or more", w));
+            }
+        }
+        
+        let key = self.resolve_principle(short_name)
+            .ok_or_else(|| anyhow::anyhow!("Principle '{}' not found", short_name))?
+            .to_string();
+        
+        if let Some(principle) = self.principles.get_mut(&key) {
+            principle.weight = weight;
+            principle.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        }
+        
+        match weight {
+            Some(w) => self.record_operation(format!("set weight of principle {} to {}", key, w)),
+            None => self.record_operation(format!("reset weight of principle {}", key)),
+        }
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Every current review written against older guidance, as (asset, principle, review)
+    pub fn stale_reviews(&self) -> Vec<(&str, &str, &Review)> {
+        let mut stale = Vec::new();
+        
+        for (asset_name, asset) in &self.asset_references {
+            for (principle_name, review) in &asset.principle_reviews {
+                if let Some(principle) = self.principles.get(principle_name) {
+                    if principle.is_stale(review) {
+                        stale.push((asset_name.as_str(), principle_name.as_str(), review));
+                    }
+                }
+            }
+        }
+        
+        stale
+    }
+    
+    /// Check a principle short name, requiring the parent of a sub-principle (e.g. "a" for "a.dry") to exist
+    fn validate_principle_path(&self, short_name: &str) -> Result<()> {
+        match short_name.rsplit_once('.') {
+            Some((parent, name)) => {
+                validate_principle_name(name)?;
+                if !self.principles.contains_key(parent) {
+                    return Err(anyhow::anyhow!("Parent principle '{}' not found for sub-principle '{}'", parent, short_name));
+                }
+                Ok(())
+            },
+            None => validate_principle_name(short_name),
+        }
+    }
+    
+    /// Short names of the direct sub-principles of a principle, in name order
+    pub fn sub_principles(&self, principle: &str) -> Vec<&str> {
+        self.principles.values()
+            .filter(|p| p.parent() == Some(principle))
+            .map(|p| p.short_name.as_str())
+            .collect()
+    }
+    
+    /// Rating of a principle for an asset, rolled up from its sub-principles
+    ///
+    /// The roll-up is the lowest rating among the direct sub-principles, compared by where
+    /// each sits on its own scale; a sub-principle without a rating on its scale contributes
+    /// its own roll-up. Returns the sub-principle the rating belongs to and the rating, or
+    /// None when no sub-principle has been rated for the asset.
+    ///
+    /// `-score` rolls sub-principles up differently, as their weighted average (see
+    /// `score::score_asset`): a percentage can sit between levels, a rating shown here cannot.
+    pub fn rolled_up_rating(&self, asset_name: &str, principle: &str) -> Option<(&str, &str)> {
+        let asset = self.asset_references.get(asset_name)?;
+        
+        self.sub_principles(principle).into_iter()
+            .filter_map(|sub| {
+                let own = asset.principle_reviews.get(sub)
+                    .and_then(|review| review.rating.as_deref())
+                    .filter(|rating| self.rating_scale_for(sub).level(rating).is_some());
+                match own {
+                    Some(rating) => Some((sub, rating)),
+                    None => self.rolled_up_rating(asset_name, sub),
+                }
+            })
+            .filter_map(|(sub, rating)| self.rating_scale_for(sub).fraction(rating).map(|fraction| (sub, rating, fraction)))
+            .min_by(|(_, _, a), (_, _, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(sub, rating, _)| (sub, rating))
+    }
+    
+    /// Look up a rating scale by name, including the built-in "hml"
+    pub fn find_rating_scale(&self, name: &str) -> Option<&RatingScale> {
+        match self.rating_scales.get(name) {
+            Some(scale) => Some(scale),
+            None if name == DEFAULT_SCALE_NAME => Some(RatingScale::high_medium_low()),
+            None => None,
+        }
+    }
+    
+    /// Name of the rating scale that applies to a principle
+    ///
+    /// A principle's own scale wins, then its parent's, then the project default, then "hml".
+    pub fn rating_scale_name_for(&self, principle: &str) -> &str {
+        let mut current = self.resolve_principle(principle).and_then(|key| self.principles.get(key));
+        while let Some(p) = current {
+            if let Some(scale) = &p.rating_scale {
+                return scale;
+            }
+            current = p.parent().and_then(|parent| self.principles.get(parent));
+        }
+        
+        self.default_rating_scale.as_deref().unwrap_or(DEFAULT_SCALE_NAME)
+    }
+    
+    /// Rating scale that applies to a principle (see `rating_scale_name_for`)
+    ///
+    /// Falls back to "hml" if the configured scale has gone missing.
+    pub fn rating_scale_for(&self, principle: &str) -> &RatingScale {
+        self.find_rating_scale(self.rating_scale_name_for(principle))
+            .unwrap_or(RatingScale::high_medium_low())
+    }
+    
+    /// Add a project rating scale
+    pub fn add_rating_scale(&mut self, name: &str, scale: RatingScale) -> Result<()> {
+        rating::validate_scale_name(name)?;
+        if self.find_rating_scale(name).is_some() {
+            return Err(anyhow::anyhow!("Rating scale '{}' already exists", name));
+        }
+        
+        self.rating_scales.insert(name.to_string(), scale);
+        
+        self.record_operation(format!("add rating scale {}", name));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Remove a project rating scale that no principle and no default refers to
+    pub fn remove_rating_scale(&mut self, name: &str) -> Result<()> {
+        if !self.rating_scales.contains_key(name) {
+            return Err(anyhow::anyhow!("Rating scale '{}' not found", name));
+        }
+        
+        let users: Vec<&str> = self.principles.values()
+            .filter(|p| p.rating_scale.as_deref() == Some(name))
+            .map(|p| p.short_name.as_str())
+            .collect();
+        if !users.is_empty() {
+            return Err(anyhow::anyhow!("Rating scale '{}' is used by principle(s): {}", name, users.join(", ")));
+        }
+        if self.default_rating_scale.as_deref() == Some(name) {
+            return Err(anyhow::anyhow!("Rating scale '{}' is the project default; set another default first", name));
+        }
+        
+        self.rating_scales.remove(name);
+        
+        self.record_operation(format!("remove rating scale {}", name));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Set the project's default rating scale; None goes back to "hml"
+    pub fn set_default_rating_scale(&mut self, name: Option<&str>) -> Result<()> {
+        if let Some(name) = name {
+            if self.find_rating_scale(name).is_none() {
+                return Err(anyhow::anyhow!("Rating scale '{}' not found", name));
+            }
+        }
+        
+        self.default_rating_scale = name.filter(|n| *n != DEFAULT_SCALE_NAME).map(str::to_string);
+        
+        self.record_operation(format!("set default rating scale to {}", name.unwrap_or(DEFAULT_SCALE_NAME)));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Set the rating scale of a principle; None inherits from its parent or the project default
+    pub fn set_principle_rating_scale(&mut self, short_name: &str, scale: Option<&str>) -> Result<()> {
+        if let Some(name) = scale {
+            if self.find_rating_scale(name).is_none() {
+                return Err(anyhow::anyhow!("Rating scale '{}' not found", name));
+            }
+        }
+        
+        let key = self.resolve_principle(short_name)
+            .ok_or_else(|| anyhow::anyhow!("Principle '{}' not found", short_name))?
+            .to_string();
+        
+        if let Some(principle) = self.principles.get_mut(&key) {
+            principle.rating_scale = scale.map(str::to_string);
+            principle.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        }
+        
+        self.record_operation(format!("set rating scale of principle {} to {}", key, scale.unwrap_or("default")));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Current reviews whose rating is not on the scale their principle now uses, as (asset, principle, rating)
+    pub fn off_scale_ratings(&self) -> Vec<(&str, &str, &str)> {
+        let mut off_scale = Vec::new();
+        
+        for (asset_name, asset) in &self.asset_references {
+            for (principle, review) in &asset.principle_reviews {
+                if let Some(rating) = review.rating.as_deref() {
+                    if self.principles.contains_key(principle) && self.rating_scale_for(principle).level(rating).is_none() {
+                        off_scale.push((asset_name.as_str(), principle.as_str(), rating));
+                    }
+                }
+            }
+        }
+        
+        off_scale
+    }
+    
+    /// Add an alias that resolves to a principle
+    pub fn add_principle_alias(&mut self, principle: &str, alias: &str) -> Result<()> {
+        let principle = self.resolve_principle(principle)
+            .ok_or_else(|| anyhow::anyhow!("Principle '{}' not found", principle))?
+            .to_string();
+        
+        validate_principle_name(alias)?;
+        self.check_name_availability(alias)?;
+        
+        if let Some(p) = self.principles.get_mut(&principle) {
+            p.aliases.push(alias.to_string());
+            p.aliases.sort();
+            p.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        }
+        
+        self.record_operation(format!("alias {} to principle {}", alias, principle));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Remove a principle alias, returning the principle it pointed to
+    pub fn remove_principle_alias(&mut self, alias: &str) -> Result<String> {
+        let principle = self.principles.values_mut()
+            .find(|p| p.aliases.iter().any(|a| a == alias))
+            .ok_or_else(|| anyhow::anyhow!("'{}' is not a principle alias", alias))?;
+        
+        principle.aliases.retain(|a| a != alias);
+        principle.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let short_name = principle.short_name.clone();
+        
+        self.record_operation(format!("remove alias {} of principle {}", alias, short_name));
+        // Update last modified timestamp
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(short_name)
+    }
+    
+    /// Resolve a principle short name or alias to the principle's short name
+    pub fn resolve_principle(&self, name: &str) -> Option<&str> {
+        if let Some((key, _)) = self.principles.get_key_value(name) {
+            return Some(key.as_str());
+        }
+        
+        self.principles.iter()
+            .find(|(_, p)| p.aliases.iter().any(|a| a == name))
+            .map(|(key, _)| key.as_str())
+    }
+    
+    /// Store review data for an asset with a specific principle
+    pub fn store_asset_review(
+        &mut self,
+        asset_name: &str,
+        principle: &str,
+        rating: Option<&str>,
+        analysis: &str,
+        attribution: &Attribution,
+        findings: Vec<Finding>
+    ) -> Result<()> {
+        // Create review; the principle revision is filled in when it is inserted
+        let mut review = Review {
+            rating: rating.map(|r| r.to_string()),
+            analysis: Some(analysis.to_string()),
+            last_modified: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            principle_revision: None,
+            author: None,
+            source: None,
+            model: None,
+            confidence: None,
+            findings,
+            content_hash: None,
+            commit: None,
+        };
+        attribution.apply(&mut review);
+        
+        self.insert_asset_review(asset_name, principle, review)
+    }
+    
+    /// Store a fully built review for an asset with a specific principle
+    ///
+    /// The review becomes the current review and is appended to the principle's history.
+    /// Unless the review already names one, it is recorded against the principle's current revision.
+    /// New findings (numbered 0) are numbered, and the open findings of the review it replaces
+    /// carry over until they are resolved. For storage kept in a VQL directory, a review without
+    /// a content hash or commit records the hash of the asset's files and the git commit checked out now.
+    pub fn insert_asset_review(&mut self, asset_name: &str, principle: &str, mut review: Review) -> Result<()> {
+        // Resolve aliases to the principle's short name
+        let resolved = self.resolve_principle(principle)
+            .map(|p| (p.to_string(), self.principles[p].revision));
+        
+        // Validate the rating against the principle's scale, storing the scale's value (e.g. "high" as "H")
+        if let (Some((key, _)), Some(r)) = (&resolved, review.rating.as_deref()) {
+            review.rating = Some(self.rating_scale_for(key).parse(r)?.to_string());
+        }
+        
+        // Find the asset
+        let asset = match self.asset_references.get_mut(asset_name) {
+            Some(asset) => asset,
+            None => return Err(anyhow::anyhow!("Asset {} not found", asset_name)),
+        };
+        
+        // Validate principle exists
+        let principle = match &resolved {
+            Some((p, revision)) => {
+                review.principle_revision.get_or_insert(*revision);
+                p.as_str()
+            },
+            None => return Err(anyhow::anyhow!("Principle {} does not exist", principle)),
+        };
+        
+        // Record what the asset looked like when it was reviewed, so later changes show the review is stale
+        if let Some(vql_dir) = self.backend.as_ref().and_then(|b| b.paths_directory()) {
+            let project_root = asset_paths::project_root(vql_dir);
+            if review.content_hash.is_none() {
+                review.content_hash = asset_paths::content_hash(&project_root, asset).ok().flatten();
+            }
+            if review.commit.is_none() {
+                review.commit = git::head_commit(&project_root);
+            }
+        }
+        
+        // Number new findings and keep the previous review's open findings open
+        let next_id = asset.next_finding_id();
+        for (finding, id) in review.findings.iter_mut().filter(|f| f.id == 0).zip(next_id..) {
+            finding.id = id;
+        }
+        if let Some(previous) = asset.principle_reviews.get(principle) {
+            let carried: Vec<Finding> = previous.findings.iter()
+                .filter(|f| f.is_open() && !review.findings.iter().any(|n| n.id == f.id))
+                .cloned()
+                .collect();
+            review.findings.splice(0..0, carried);
+        }
+        
+        // Add review to asset and append it to the principle's history
+        asset.review_history
+            .entry(principle.to_string())
+            .or_default()
+            .push(review.clone());
+        asset.principle_reviews.insert(principle.to_string(), review);
+        
+        // Update asset last modified
+        asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        self.record_operation(format!("store review {} {}", asset_name, principle));
+        // Update storage last modified
+        self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        
+        Ok(())
+    }
+    
+    /// Resolve open findings of an asset's current reviews by number, returning the principle of each
+    ///
+    /// The resolution is recorded on the current review and on its entry in the review history.
+    pub fn resolve_findings(&mut self, asset_name: &str, ids: &[u32], note: Option<&str>) -> Result<Vec<(u32, String)>> {
+        let asset = self.asset_references.get_mut(asset_name)
+            .ok_or_else(|| anyhow::anyhow!("Asset {} not found", asset_name))?;
+        
+        let mut resolved = Vec::new();
+        for id in ids {
+            let Some((principle, review)) = asset.principle_reviews.iter_mut()
+                .find(|(_, review)| review.findings.iter().any(|f| f.id == *id)) else {
+                return Err(anyhow::anyhow!("Asset {} has no finding #{} in its current reviews", asset_name, id));
+            };
+            let finding = review.findings.iter_mut().find(|f| f.id == *id).unwrap();
+            if !finding.is_open() {
+                continue;
+            }
+            finding.resolve(note);
+            
+            // The current review is the last entry of its history
+            if let Some(latest) = asset.review_history.get_mut(principle).and_then(|history| history.last_mut()) {
+                latest.findings.clone_from(&review.findings);
+            }
+            resolved.push((*id, principle.clone()));
+        }
+        
+        if !resolved.is_empty() {
+            asset.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            
+            let ids: Vec<String> = resolved.iter().map(|(id, _)| format!("#{}", id)).collect();
+            self.record_operation(format!("resolve findings {} of {}", ids.join(", "), asset_name));
+            // Update last modified timestamp
+            self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        }
+        
+        Ok(resolved)
+    }
+    
+    /// Get review for an asset from a specific principle
+    pub fn get_asset_review(&self, asset_name: &str, principle: &str) -> Result<Option<&Review>> {
+        // Find the asset
+        let asset = match self.asset_references.get(asset_name) {
+            Some(asset) => asset,
+            None => return Err(anyhow::anyhow!("Asset {} not found", asset_name)),
+        };
+        
+        // Return the review if it exists, accepting aliases
+        let principle = self.resolve_principle(principle).unwrap_or(principle);
+        Ok(asset.principle_reviews.get(principle))
+    }
+    
+    /// Get all reviews for an asset
+    pub fn get_asset_reviews(&self, asset_name: &str) -> Result<&BTreeMap<String, Review>> {
+        // Find the asset
+        let asset = match self.asset_references.get(asset_name) {
+            Some(asset) => asset,
+            None => return Err(anyhow::anyhow!("Asset {} not found", asset_name)),
+        };
+        
+        // Return all reviews
+        Ok(&asset.principle_reviews)
+    }
+    
+    /// Get every review stored for an asset and principle, oldest first
+    ///
+    /// Reviews stored before history was tracked only exist as the current review,
+    /// so that review is returned on its own when no history is recorded.
+    pub fn get_review_history(&self, asset_name: &str, principle: &str) -> Result<Vec<&Review>> {
+        // Find the asset
+        let asset = match self.asset_references.get(asset_name) {
+            Some(asset) => asset,
+            None => return Err(anyhow::anyhow!("Asset {} not found", asset_name)),
+        };
+        
+        let principle = self.resolve_principle(principle).unwrap_or(principle);
+        match asset.review_history.get(principle) {
+            Some(history) if !history.is_empty() => Ok(history.iter().collect()),
+            _ => Ok(asset.principle_reviews.get(principle).into_iter().collect()),
+        }
+    }
+    
+    /// Get the review that was current for an asset and principle at a point in time
+    ///
+    /// `at` is either a date (YYYY-MM-DD, meaning the end of that day) or a full timestamp.
+    pub fn get_review_at(&self, asset_name: &str, principle: &str, at: &str) -> Result<Option<&Review>> {
+        let cutoff = normalize_cutoff(at)?;
+        let history = self.get_review_history(asset_name, principle)?;
+        
+        Ok(history.into_iter()
+            .rev()
+            .find(|review| review.last_modified.as_str() <= cutoff.as_str()))
+    }
+    
+    /// Compact review history, returning the number of entries removed
+    ///
+    /// Entries older than `before` are dropped, then at most `keep` of the most recent
+    /// entries are retained. The current review is always kept.
+    pub fn compact_review_history(&mut self, keep: Option<usize>, before: Option<&str>) -> Result<usize> {
+        if keep.is_none() && before.is_none() {
+            return Err(anyhow::anyhow!("Nothing to compact. Specify how many entries to keep and/or a date to drop entries before"));
+        }
+        
+        let cutoff = match before {
+            Some(date) => Some(normalize_start(date)?),
+            None => None,
+        };
+        let keep = keep.map(|k| k.max(1));
+        
+        let mut removed = 0;
         
-        // Cascade: Update principle keys in all asset reviews
         for asset in self.asset_references.values_mut() {
-            if let Some(review) = asset.principle_reviews.remove(old_name) {
-                asset.principle_reviews.insert(new_name.to_string(), review);
+            for history in asset.review_history.values_mut() {
+                let original_len = history.len();
+                if original_len <= 1 {
+                    continue;
+                }
+                
+                // Never drop the latest entry - it is the current review
+                let latest = history.pop();
+                
+                if let Some(cutoff) = &cutoff {
+                    history.retain(|review| review.last_modified.as_str() >= cutoff.as_str());
+                }
+                
+                if let Some(keep) = keep {
+                    let older_to_keep = keep - 1;
+                    if history.len() > older_to_keep {
+                        let excess = history.len() - older_to_keep;
+                        history.drain(..excess);
+                    }
+                }
+                
+                history.extend(latest);
+                removed += original_len - history.len();
+            }
+        }
+        
+        if removed > 0 {
+            self.record_operation("compact review history".to_string());
+            // Update storage last modified
+            self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        }
+        
+        Ok(removed)
+    }
+    
+    /// Rename a principle
+    pub fn rename_principle(&mut self, old_name: &str, new_name: &str) -> Result<()> {
+        // Check if old principle exists
+        if !self.principles.contains_key(old_name) {
+            return Err(anyhow::anyhow!("Principle '{}' not found", old_name));
+        }
+        
+        // Check the new name is a valid principle path
+        self.validate_principle_path(new_name)?;
+        if new_name.starts_with(&format!("{}.", old_name)) {
+            return Err(anyhow::anyhow!("Cannot move principle '{}' under itself", old_name));
+        }
+        
+        // Sub-principles move with their parent, e.g. a.dry becomes arch.dry
+        let descendant_prefix = format!("{}.", old_name);
+        let mut renames = vec![(old_name.to_string(), new_name.to_string())];
+        renames.extend(self.principles.keys()
+            .filter_map(|key| key.strip_prefix(&descendant_prefix)
+                .map(|rest| (key.clone(), format!("{}.{}", new_name, rest)))));
+        
+        // Check every new name is available before moving anything; a principle's own alias may be promoted
+        for (from, to) in &renames {
+            if !self.principles[from].aliases.iter().any(|a| a == to) {
+                self.check_name_availability(to)?;
+            }
+        }
+        
+        for (from, to) in &renames {
+            // Get the principle data
+            let principle = self.principles.remove(from)
+                .ok_or_else(|| anyhow::anyhow!("Failed to remove principle"))?;
+            
+            // Update principle with new name
+            let mut updated_principle = principle;
+            updated_principle.aliases.retain(|a| a != to);
+            updated_principle.short_name = to.clone();
+            updated_principle.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            
+            // Insert with new name
+            self.principles.insert(to.clone(), updated_principle);
+            
+            // Cascade: Update principle keys in all asset reviews and their history
+            for asset in self.asset_references.values_mut() {
+                if let Some(review) = asset.principle_reviews.remove(from) {
+                    asset.principle_reviews.insert(to.clone(), review);
+                }
+                if let Some(history) = asset.review_history.remove(from) {
+                    asset.review_history.insert(to.clone(), history);
+                }
+                if let Some(waiver) = asset.waivers.remove(from) {
+                    asset.waivers.insert(to.clone(), waiver);
+                }
             }
         }
         
+        self.record_operation(format!("rename principle {} to {}", old_name, new_name));
         // Update storage last modified
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
@@ -706,6 +1829,14 @@ impl JsonStorage {
             }
         }
         
+        // Cascade: Update parent links of child entities
+        for entity in self.entities.values_mut() {
+            if entity.parent.as_deref() == Some(old_name) {
+                entity.parent = Some(new_name.to_string());
+            }
+        }
+        
+        self.record_operation(format!("rename entity {} to {}", old_name, new_name));
         // Update storage last modified
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
@@ -742,6 +1873,7 @@ impl JsonStorage {
             }
         }
         
+        self.record_operation(format!("rename asset type {} to {}", old_name, new_name));
         // Update storage last modified
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
@@ -770,27 +1902,47 @@ impl JsonStorage {
         // Insert with new name
         self.asset_references.insert(new_name.to_string(), updated_asset);
         
+        // Point dependency edges at the new name
+        for asset in self.asset_references.values_mut() {
+            if let Some(dep) = asset.depends_on.iter_mut().find(|dep| *dep == old_name) {
+                *dep = new_name.to_string();
+                asset.depends_on.sort();
+            }
+        }
+        
+        self.record_operation(format!("rename asset {} to {}", old_name, new_name));
         // Update storage last modified
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
         Ok(())
     }
     
-    /// Delete a principle (cascades to remove from all asset reviews)
+    /// Delete a principle and its sub-principles (cascades to remove from all asset reviews)
     pub fn delete_principle(&mut self, name: &str) -> Result<()> {
         // Check if principle exists
         if !self.principles.contains_key(name) {
             return Err(anyhow::anyhow!("Principle '{}' not found", name));
         }
         
-        // Remove principle
-        self.principles.remove(name);
+        let descendant_prefix = format!("{}.", name);
+        let removed: Vec<String> = self.principles.keys()
+            .filter(|key| *key == name || key.starts_with(&descendant_prefix))
+            .cloned()
+            .collect();
         
-        // Cascade: Remove this principle from all asset reviews
-        for asset in self.asset_references.values_mut() {
-            asset.principle_reviews.remove(name);
+        for principle in &removed {
+            // Remove principle
+            self.principles.remove(principle);
+            
+            // Cascade: Remove this principle from all asset reviews, their history and waivers
+            for asset in self.asset_references.values_mut() {
+                asset.principle_reviews.remove(principle);
+                asset.review_history.remove(principle);
+                asset.waivers.remove(principle);
+            }
         }
         
+        self.record_operation(format!("delete principle {}", name));
         // Update storage last modified
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
@@ -820,9 +1972,22 @@ impl JsonStorage {
             ));
         }
         
+        // Check if any entities sit under this one
+        let children = self.entity_children(name);
+        if !children.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Cannot delete entity '{}' - it has {} child entit{}: {}", 
+                name,
+                children.len(),
+                if children.len() == 1 { "y" } else { "ies" },
+                children.join(", ")
+            ));
+        }
+        
         // Safe to remove
         self.entities.remove(name);
         
+        self.record_operation(format!("delete entity {}", name));
         // Update storage last modified
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
@@ -855,6 +2020,7 @@ impl JsonStorage {
         // Safe to remove
         self.asset_types.remove(name);
         
+        self.record_operation(format!("delete asset type {}", name));
         // Update storage last modified
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
@@ -868,9 +2034,13 @@ impl JsonStorage {
             return Err(anyhow::anyhow!("Asset '{}' not found", name));
         }
         
-        // Remove the asset (reviews are removed with it)
+        // Remove the asset (reviews are removed with it) and every dependency on it
         self.asset_references.remove(name);
+        for asset in self.asset_references.values_mut() {
+            asset.depends_on.retain(|dep| dep != name);
+        }
         
+        self.record_operation(format!("delete asset {}", name));
         // Update storage last modified
         self.last_modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
@@ -878,8 +2048,69 @@ impl JsonStorage {
     }
 }
 
+/// Pick a backup file name for a storage file about to be migrated, never overwriting an earlier backup
+fn migration_backup_path(json_file_path: &Path, from_version: &str) -> PathBuf {
+    let backup = json_file_path.with_extension(format!("json.v{}.bak", from_version));
+    if !backup.exists() {
+        return backup;
+    }
+    
+    let stamp = Utc::now().format("%Y%m%d%H%M%S");
+    json_file_path.with_extension(format!("json.v{}.{}.bak", from_version, stamp))
+}
+
+/// Turn a date or timestamp into an inclusive upper bound comparable with stored timestamps
+fn normalize_cutoff(at: &str) -> Result<String> {
+    let at = at.trim();
+    if chrono::NaiveDate::parse_from_str(at, "%Y-%m-%d").is_ok() {
+        Ok(format!("{}T23:59:59Z", at))
+    } else if let Ok(time) = chrono::DateTime::parse_from_rfc3339(at) {
+        // Stored timestamps are UTC, so convert any offset before comparing them as strings
+        Ok(time.with_timezone(&Utc).format("%Y-%m-%dT%H:%M:%SZ").to_string())
+    } else {
+        Err(anyhow::anyhow!("Invalid date: {}. Use YYYY-MM-DD or YYYY-MM-DDTHH:MM:SSZ", at))
+    }
+}
+
+/// Turn a date or timestamp into an inclusive lower bound comparable with stored timestamps
+fn normalize_start(at: &str) -> Result<String> {
+    let at = at.trim();
+    if chrono::NaiveDate::parse_from_str(at, "%Y-%m-%d").is_ok() {
+        Ok(format!("{}T00:00:00Z", at))
+    } else if let Ok(time) = chrono::DateTime::parse_from_rfc3339(at) {
+        Ok(time.with_timezone(&Utc).format("%Y-%m-%dT%H:%M:%SZ").to_string())
+    } else {
+        Err(anyhow::anyhow!("Invalid date: {}. Use YYYY-MM-DD or YYYY-MM-DDTHH:MM:SSZ", at))
+    }
+}
+
+/// Check that a principle short name or alias follows the identifier rules of asset names
+///
+/// Names are letters, digits and underscores, so they work unquoted in both command syntaxes.
+pub fn validate_principle_name(name: &str) -> Result<()> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(anyhow::anyhow!("Invalid principle name '{}': use letters, digits and underscores only", name));
+    }
+    Ok(())
+}
+
 /// Helper function to find the VQL JSON storage file in the current directory or ancestors
+///
+/// The storage is returned locked: other VQL processes wait (up to `VQL_LOCK_TIMEOUT`
+/// seconds) until it is dropped, so a load-modify-save cycle cannot interleave with another.
+/// If a backend is set for this thread (see `backend::set_thread_backend`), it is used instead.
 pub fn find_vql_storage() -> Result<(PathBuf, JsonStorage)> {
+    // Storage routed elsewhere (e.g. in memory) for this thread
+    if let Some(backend) = backend::thread_backend() {
+        let vql_dir = backend.paths_directory()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from(backend.location()));
+        let lock = backend.lock()?;
+        let mut storage = JsonStorage::read_from_backend(backend)?;
+        storage.lock = lock.map(Arc::new);
+        return Ok((vql_dir, storage));
+    }
+    
     let current_dir = std::env::current_dir()
         .context("Failed to get current directory")?;
     
@@ -890,16 +2121,17 @@ pub fn find_vql_storage() -> Result<(PathBuf, JsonStorage)> {
         let vql_dir = search_dir.join("VQL");
         
         if vql_dir.exists() && vql_dir.is_dir() {
-            // Check if storage file exists
-            let storage_path = vql_dir.join("vql_storage.json");
-            
-            if storage_path.exists() {
-                // Load the storage
-                let content = fs::read_to_string(&storage_path)
-                    .context(format!("Failed to read VQL storage at {}", storage_path.display()))?;
-                    
-                let storage: JsonStorage = serde_json::from_str(&content)
-                    .context("Failed to parse VQL JSON storage")?;
+            // Check if storage exists in any backend
+            if backend::detect_backend(&vql_dir).is_some() {
+                // Lock before reading so the snapshot we modify is the one we overwrite
+                let lock = StorageLock::acquire(&vql_dir)?;
+                
+                // Detect again under the lock in case a conversion just finished
+                let backend = backend::detect_backend(&vql_dir)
+                    .ok_or_else(|| anyhow::anyhow!("VQL storage disappeared from {}", vql_dir.display()))?;
+                
+                let mut storage = JsonStorage::read_from_backend(backend)?;
+                storage.lock = Some(Arc::new(lock));
                     
                 return Ok((vql_dir, storage));
             }
@@ -914,4 +2146,89 @@ pub fn find_vql_storage() -> Result<(PathBuf, JsonStorage)> {
     
     // If we get here, no VQL directory was found
     Err(anyhow::anyhow!("VQL directory not found in current directory or ancestors"))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::backend::MemoryBackend;
+
+    /// Storage with one asset `uc` pointing at `uc.js` in the project holding `vql_dir`
+    fn storage_with_asset(vql_dir: &Path) -> JsonStorage {
+        fs::create_dir_all(vql_dir).unwrap();
+        fs::write(vql_dir.parent().unwrap().join("uc.js"), "class UserController {}").unwrap();
+
+        let mut storage = JsonStorage::new();
+        storage.add_principle("a", "Architecture", None).unwrap();
+        storage.add_entity("u", "User").unwrap();
+        storage.add_asset_type("c", "Controller").unwrap();
+        storage.add_asset_reference("uc", "u", "c", "uc.js").unwrap();
+        storage
+    }
+
+    #[test]
+    fn batch_reviews_record_content_hash() {
+        let project = tempfile::tempdir().unwrap();
+        let vql_dir = project.path().join("VQL");
+        let document = serde_json::to_string(&storage_with_asset(&vql_dir)).unwrap();
+
+        // A batch stages storage in memory while asset paths still resolve against the VQL directory
+        let staging: Arc<dyn StorageBackend> = Arc::new(MemoryBackend::staging(&vql_dir, &document));
+        let previous = backend::set_thread_backend(Some(staging));
+        let (_, mut storage) = find_vql_storage().unwrap();
+        backend::set_thread_backend(previous);
+
+        storage.store_asset_review("uc", "a", Some("H"), "Thin controller", &Attribution::default(), Vec::new()).unwrap();
+
+        let review = &storage.asset_references["uc"].principle_reviews["a"];
+        let project_root = asset_paths::project_root(&vql_dir);
+        let expected = asset_paths::content_hash(&project_root, &storage.asset_references["uc"]).unwrap();
+        assert!(review.content_hash.is_some());
+        assert_eq!(review.content_hash, expected);
+    }
+
+    #[test]
+    fn batch_reviews_record_commit() {
+        let project = tempfile::tempdir().unwrap();
+        let vql_dir = project.path().join("VQL");
+        let document = serde_json::to_string(&storage_with_asset(&vql_dir)).unwrap();
+
+        git::run(project.path(), &["init", "-q"]).unwrap();
+        git::run(project.path(), &["add", "uc.js"]).unwrap();
+        git::run(project.path(), &["-c", "user.name=vql", "-c", "user.email=vql@example.com", "commit", "-q", "-m", "init"]).unwrap();
+
+        let staging: Arc<dyn StorageBackend> = Arc::new(MemoryBackend::staging(&vql_dir, &document));
+        let previous = backend::set_thread_backend(Some(staging));
+        let (_, mut storage) = find_vql_storage().unwrap();
+        backend::set_thread_backend(previous);
+
+        storage.store_asset_review("uc", "a", Some("H"), "Thin controller", &Attribution::default(), Vec::new()).unwrap();
+
+        let review = &storage.asset_references["uc"].principle_reviews["a"];
+        assert!(review.commit.is_some());
+        assert_eq!(review.commit, git::head_commit(project.path()));
+    }
+
+    #[test]
+    fn timestamps_with_offsets_are_compared_in_utc() {
+        assert_eq!(normalize_cutoff("2024-03-01T01:30:00+02:00").unwrap(), "2024-02-29T23:30:00Z");
+        assert_eq!(normalize_start("2024-03-01T10:00:00-05:00").unwrap(), "2024-03-01T15:00:00Z");
+        assert_eq!(normalize_start("2024-03-01").unwrap(), "2024-03-01T00:00:00Z");
+    }
+
+    #[test]
+    fn rename_checks_descendant_names_before_moving_anything() {
+        let mut storage = JsonStorage::new();
+        storage.add_principle("a", "Architecture", None).unwrap();
+        storage.add_principle("a.dry", "Don't repeat yourself", None).unwrap();
+        storage.add_principle("b", "Backend", None).unwrap();
+        storage.add_principle("b.dry", "Backend reuse", None).unwrap();
+        // e.g. a hand-edited file that dropped b but kept its sub-principle
+        storage.principles.remove("b");
+
+        assert!(storage.rename_principle("a", "b").is_err());
+        assert!(storage.principles.contains_key("a"));
+        assert_eq!(storage.principles["a.dry"].long_name, "Don't repeat yourself");
+        assert_eq!(storage.principles["b.dry"].long_name, "Backend reuse");
+    }
+}
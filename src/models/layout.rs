@@ -0,0 +1,288 @@
+use std::fs;
+use std::path::Path;
+use anyhow::{Result, Context};
+use serde_json::{Map, Value};
+
+use crate::utils::lock::write_atomic;
+
+/// File holding the whole storage in the single-file layout
+pub const STORAGE_FILE_NAME: &str = "vql_storage.json";
+
+/// File holding the version, creation time and commands in the sharded layout
+pub const MANIFEST_FILE_NAME: &str = "vql_manifest.json";
+
+/// Storage sections that get one file per item in the sharded layout, and their directories
+const SHARDED_SECTIONS: [(&str, &str); 4] = [
+    ("principles", "principles"),
+    ("entities", "entities"),
+    ("asset_types", "asset_types"),
+    ("asset_references", "assets"),
+];
+
+/// How VQL storage is laid out on disk inside the VQL directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageLayout {
+    /// Everything in `vql_storage.json`
+    #[default]
+    Single,
+
+    /// A manifest plus one file per principle, entity, asset type and asset
+    Sharded,
+}
+
+impl StorageLayout {
+    /// Find which layout a VQL directory uses, if it holds any storage
+    ///
+    /// The manifest is written last when converting, so it wins if both layouts are present.
+    pub fn detect(vql_dir: &Path) -> Option<Self> {
+        if vql_dir.join(MANIFEST_FILE_NAME).is_file() {
+            Some(StorageLayout::Sharded)
+        } else if vql_dir.join(STORAGE_FILE_NAME).is_file() {
+            Some(StorageLayout::Single)
+        } else {
+            None
+        }
+    }
+
+    /// Parse a layout name as typed on the command line
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "single" => Ok(StorageLayout::Single),
+            "sharded" => Ok(StorageLayout::Sharded),
+            _ => Err(anyhow::anyhow!("Unknown storage layout: {}. Use single or sharded", name)),
+        }
+    }
+
+    /// Name of the layout as shown to the user
+    pub fn name(&self) -> &'static str {
+        match self {
+            StorageLayout::Single => "single",
+            StorageLayout::Sharded => "sharded",
+        }
+    }
+}
+
+/// Read the raw storage document from a VQL directory
+///
+/// Sharded storage is assembled into the same JSON document the single-file layout holds,
+/// so both go through the same migration and parsing.
+pub fn read_document(vql_dir: &Path, layout: StorageLayout) -> Result<String> {
+    match layout {
+        StorageLayout::Single => {
+            let path = vql_dir.join(STORAGE_FILE_NAME);
+            fs::read_to_string(&path)
+                .context(format!("Failed to read VQL storage at {}", path.display()))
+        },
+        StorageLayout::Sharded => {
+            let document = read_sharded(vql_dir)?;
+            serde_json::to_string(&document)
+                .context("Failed to assemble sharded VQL storage")
+        },
+    }
+}
+
+/// Assemble the storage document from the manifest and shard files
+fn read_sharded(vql_dir: &Path) -> Result<Value> {
+    let mut root = match read_json_file(&vql_dir.join(MANIFEST_FILE_NAME))? {
+        Value::Object(map) => map,
+        _ => return Err(anyhow::anyhow!("VQL manifest is not a JSON object")),
+    };
+
+    // The storage-wide timestamp is derived rather than stored, so it never conflicts in git
+    let mut last_modified = root.get("created")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    for (section, dir_name) in SHARDED_SECTIONS {
+        let mut items = Map::new();
+        let dir = vql_dir.join(dir_name);
+
+        if dir.is_dir() {
+            for (name, path) in shard_files(&dir)? {
+                let item = read_json_file(&path)?;
+
+                if let Some(modified) = item.get("last_modified").and_then(|v| v.as_str()) {
+                    if modified > last_modified.as_str() {
+                        last_modified = modified.to_string();
+                    }
+                }
+
+                items.insert(name, item);
+            }
+        }
+
+        root.insert(section.to_string(), Value::Object(items));
+    }
+
+    root.insert("last_modified".to_string(), Value::String(last_modified));
+
+    Ok(Value::Object(root))
+}
+
+/// Write a storage document in the sharded layout
+///
+/// Only files whose content changed are rewritten, files for removed items are deleted,
+/// and the manifest is written last.
+pub fn write_sharded(vql_dir: &Path, document: &Value) -> Result<()> {
+    let root = document.as_object()
+        .ok_or_else(|| anyhow::anyhow!("VQL storage is not a JSON object"))?;
+
+    for (section, dir_name) in SHARDED_SECTIONS {
+        let dir = vql_dir.join(dir_name);
+        let empty = Map::new();
+        let items = root.get(section)
+            .and_then(|v| v.as_object())
+            .unwrap_or(&empty);
+
+        if !dir.exists() {
+            if items.is_empty() {
+                continue;
+            }
+            fs::create_dir_all(&dir)
+                .context(format!("Failed to create directory {}", dir.display()))?;
+        }
+
+        for (name, item) in items {
+            write_json_file(&dir.join(shard_file_name(name)), item)?;
+        }
+
+        // Drop files of items that no longer exist
+        for (name, path) in shard_files(&dir)? {
+            if !items.contains_key(&name) {
+                fs::remove_file(&path)
+                    .context(format!("Failed to remove {}", path.display()))?;
+            }
+        }
+    }
+
+    let manifest: Map<String, Value> = root.iter()
+        .filter(|(key, _)| key.as_str() != "last_modified")
+        .filter(|(key, _)| !SHARDED_SECTIONS.iter().any(|(section, _)| section == key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+
+    write_json_file(&vql_dir.join(MANIFEST_FILE_NAME), &Value::Object(manifest))
+}
+
+/// Remove the files of a layout after storage has been written in another one
+///
+/// For the sharded layout the manifest goes first, so an interrupted removal
+/// never leaves a manifest pointing at missing shards.
+pub fn remove_layout(vql_dir: &Path, layout: StorageLayout) -> Result<()> {
+    match layout {
+        StorageLayout::Single => {
+            let path = vql_dir.join(STORAGE_FILE_NAME);
+            if path.exists() {
+                fs::remove_file(&path)
+                    .context(format!("Failed to remove {}", path.display()))?;
+            }
+        },
+        StorageLayout::Sharded => {
+            let manifest = vql_dir.join(MANIFEST_FILE_NAME);
+            if manifest.exists() {
+                fs::remove_file(&manifest)
+                    .context(format!("Failed to remove {}", manifest.display()))?;
+            }
+
+            for (_, dir_name) in SHARDED_SECTIONS {
+                let dir = vql_dir.join(dir_name);
+                if !dir.is_dir() {
+                    continue;
+                }
+
+                for (_, path) in shard_files(&dir)? {
+                    fs::remove_file(&path)
+                        .context(format!("Failed to remove {}", path.display()))?;
+                }
+
+                // Leave the directory alone if anything else lives in it
+                let _ = fs::remove_dir(&dir);
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// List the shard files in a directory as (item name, path), sorted by name
+fn shard_files(dir: &Path) -> Result<Vec<(String, std::path::PathBuf)>> {
+    let entries = fs::read_dir(dir)
+        .context(format!("Failed to read directory {}", dir.display()))?;
+
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let stem = match path.file_name().and_then(|n| n.to_str()).and_then(|n| n.strip_suffix(".json")) {
+            Some(stem) if path.is_file() && !stem.starts_with('.') => stem.to_string(),
+            _ => continue,
+        };
+
+        files.push((decode_shard_name(&stem), path));
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// File name for an item, escaping anything that is not safe in a path on every platform
+fn shard_file_name(name: &str) -> String {
+    let mut file_name = String::new();
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-' {
+            file_name.push(byte as char);
+        } else {
+            file_name.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    file_name.push_str(".json");
+    file_name
+}
+
+/// Reverse the escaping applied by `shard_file_name`
+fn decode_shard_name(stem: &str) -> String {
+    let bytes = stem.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit()
+        {
+            if let Ok(byte) = u8::from_str_radix(&stem[i + 1..i + 3], 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Read and parse one JSON file
+fn read_json_file(path: &Path) -> Result<Value> {
+    let content = fs::read_to_string(path)
+        .context(format!("Failed to read {}", path.display()))?;
+
+    serde_json::from_str(&content)
+        .context(format!("Failed to parse {} (unresolved merge conflict?)", path.display()))
+}
+
+/// Write one JSON file, skipping the write when the content is unchanged
+fn write_json_file(path: &Path, value: &Value) -> Result<()> {
+    let mut content = serde_json::to_string_pretty(value)
+        .context("Failed to serialize VQL storage to JSON")?;
+    content.push('\n');
+
+    if fs::read_to_string(path).map(|existing| existing == content).unwrap_or(false) {
+        return Ok(());
+    }
+
+    write_atomic(path, content.as_bytes())
+        .context(format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,452 @@
+use std::collections::{BTreeMap, BTreeSet};
+use anyhow::Result;
+use serde_json::{Map, Value};
+
+use crate::models::migration;
+
+/// Storage sections holding named items, and how an item of each is described
+const ITEM_SECTIONS: [(&str, &str); 5] = [
+    ("commands", "Command"),
+    ("asset_types", "Asset type"),
+    ("entities", "Entity"),
+    ("principles", "Principle"),
+    ("asset_references", "Asset"),
+];
+
+/// Sections sharing the unified short-name namespace
+const NAMESPACE_SECTIONS: [(&str, &str); 4] = [
+    ("principles", "principle"),
+    ("entities", "entity"),
+    ("asset_types", "asset type"),
+    ("asset_references", "asset"),
+];
+
+/// List fields that hold a set of names, merged element by element rather than as one value
+const SET_FIELDS: [&str; 6] = ["aliases", "tags", "depends_on", "extra_paths", "patterns", "discovery_ignore"];
+
+/// Result of a three-way merge of VQL storage
+#[derive(Debug, Default)]
+pub struct MergeOutcome {
+    /// The merged document; where sides conflict it holds "ours"
+    pub merged: Value,
+
+    /// Changes that could not be reconciled and need a human decision
+    pub conflicts: Vec<String>,
+
+    /// Decisions taken automatically that are worth mentioning
+    pub notes: Vec<String>,
+}
+
+impl MergeOutcome {
+    /// Whether the merge finished without conflicts
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Three-way merge of a VQL storage file
+///
+/// Accepts a whole storage document (`vql_storage.json` or the sharded manifest) or a
+/// single sharded item file. `base` is `None` when both sides added the file.
+pub fn merge_documents(base: Option<Value>, ours: Value, theirs: Value) -> Result<MergeOutcome> {
+    let mut outcome = MergeOutcome::default();
+
+    let (base, ours, theirs) = (
+        base.map(as_object).transpose()?,
+        as_object(ours)?,
+        as_object(theirs)?,
+    );
+
+    let merged = if ours.contains_key("version") || theirs.contains_key("version") {
+        merge_storage(base, ours, theirs, &mut outcome)?
+    } else if ours.contains_key("principle_reviews") || theirs.contains_key("principle_reviews") {
+        let name = item_name(&ours);
+        merge_asset(&format!("Asset {}", name), base.as_ref(), &ours, &theirs, &mut outcome)
+    } else {
+        let name = item_name(&ours);
+        merge_fields(&format!("Item {}", name), base.as_ref(), &ours, &theirs, &[], &mut outcome)
+    };
+
+    outcome.merged = Value::Object(merged);
+    Ok(outcome)
+}
+
+/// Merge whole storage documents, section by section
+fn merge_storage(
+    base: Option<Map<String, Value>>,
+    ours: Map<String, Value>,
+    theirs: Map<String, Value>,
+    outcome: &mut MergeOutcome,
+) -> Result<Map<String, Value>> {
+    // Bring every side to the current schema first so they are comparable
+    let migrate = |doc: Map<String, Value>| -> Result<Map<String, Value>> {
+        let mut value = Value::Object(doc);
+        migration::migrate(&mut value)?;
+        as_object(value)
+    };
+    let base = base.map(migrate).transpose()?;
+    let ours = migrate(ours)?;
+    let theirs = migrate(theirs)?;
+
+    let section_names: Vec<&str> = ITEM_SECTIONS.iter().map(|(section, _)| *section).collect();
+    let mut merged = merge_fields("Storage", base.as_ref(), &ours, &theirs, &section_names, outcome);
+
+    for (section, label) in ITEM_SECTIONS {
+        if !ours.contains_key(section) && !theirs.contains_key(section) {
+            continue;
+        }
+
+        let items = merge_section(
+            label,
+            base.as_ref().and_then(|b| b.get(section)).and_then(|v| v.as_object()),
+            ours.get(section).and_then(|v| v.as_object()),
+            theirs.get(section).and_then(|v| v.as_object()),
+            outcome,
+        );
+        merged.insert(section.to_string(), Value::Object(items));
+    }
+
+    if merged.contains_key("asset_references") {
+        check_consistency(&merged, outcome);
+    }
+
+    Ok(merged)
+}
+
+/// Merge a map of named items, honouring additions and deletions on either side
+fn merge_section(
+    label: &str,
+    base: Option<&Map<String, Value>>,
+    ours: Option<&Map<String, Value>>,
+    theirs: Option<&Map<String, Value>>,
+    outcome: &mut MergeOutcome,
+) -> Map<String, Value> {
+    let empty = Map::new();
+    let ours = ours.unwrap_or(&empty);
+    let theirs = theirs.unwrap_or(&empty);
+
+    let mut merged = Map::new();
+    let names: BTreeSet<&String> = ours.keys().chain(theirs.keys()).collect();
+
+    for name in names {
+        let base_item = base.and_then(|b| b.get(name));
+        let context = format!("{} {}", label, name);
+
+        match (ours.get(name), theirs.get(name)) {
+            (Some(o), Some(t)) => {
+                let base_map = base_item.and_then(|b| b.as_object());
+                let item = match (o.as_object(), t.as_object()) {
+                    (Some(o), Some(t)) if label == "Asset" => Value::Object(merge_asset(&context, base_map, o, t, outcome)),
+                    (Some(o), Some(t)) => Value::Object(merge_fields(&context, base_map, o, t, &[], outcome)),
+                    _ => merge_value(&context, base_item, o, t, outcome),
+                };
+                merged.insert(name.clone(), item);
+            },
+            (Some(kept), None) | (None, Some(kept)) => {
+                let side = if ours.contains_key(name) { "theirs" } else { "ours" };
+                match base_item {
+                    // Added on one side only
+                    None => {
+                        merged.insert(name.clone(), kept.clone());
+                    },
+                    // Deleted on one side, untouched on the other
+                    Some(b) if b == kept => {},
+                    // Deleted on one side, changed on the other
+                    Some(_) => {
+                        outcome.conflicts.push(format!("{} was changed on one side and deleted on {} - kept the changed version", context, side));
+                        merged.insert(name.clone(), kept.clone());
+                    },
+                }
+            },
+            (None, None) => {},
+        }
+    }
+
+    merged
+}
+
+/// Three-way merge of an asset: newest review wins per principle, histories are unioned
+fn merge_asset(
+    context: &str,
+    base: Option<&Map<String, Value>>,
+    ours: &Map<String, Value>,
+    theirs: &Map<String, Value>,
+    outcome: &mut MergeOutcome,
+) -> Map<String, Value> {
+    let mut merged = merge_fields(context, base, ours, theirs, &["principle_reviews", "review_history"], outcome);
+
+    let reviews_of = |doc: Option<&Map<String, Value>>| doc
+        .and_then(|d| d.get("principle_reviews"))
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default();
+    let (base_reviews, our_reviews, their_reviews) = (reviews_of(base), reviews_of(Some(ours)), reviews_of(Some(theirs)));
+
+    let mut reviews = Map::new();
+    let principles: BTreeSet<&String> = our_reviews.keys().chain(their_reviews.keys()).collect();
+    for principle in principles {
+        match (our_reviews.get(principle), their_reviews.get(principle)) {
+            (Some(o), Some(t)) => {
+                let chosen = if o == t {
+                    o
+                } else {
+                    let (o_time, t_time) = (timestamp(o), timestamp(t));
+                    if t_time > o_time {
+                        t
+                    } else {
+                        if t_time == o_time {
+                            outcome.notes.push(format!("{}: both sides stored a different {} review at {} - kept ours", context, principle, o_time));
+                        }
+                        o
+                    }
+                };
+                reviews.insert(principle.clone(), chosen.clone());
+            },
+            (Some(kept), None) | (None, Some(kept)) => {
+                // Removed on one side (principle deleted) unless the other side reviewed it again
+                if base_reviews.get(principle) != Some(kept) {
+                    reviews.insert(principle.clone(), kept.clone());
+                }
+            },
+            (None, None) => {},
+        }
+    }
+
+    let history_of = |doc: Option<&Map<String, Value>>| doc
+        .and_then(|d| d.get("review_history"))
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default();
+    let (base_history, our_history, their_history) = (history_of(base), history_of(Some(ours)), history_of(Some(theirs)));
+
+    let mut history = Map::new();
+    for principle in reviews.keys() {
+        let entries_of = |h: &Map<String, Value>| h.get(principle)
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        let (base_entries, our_entries, their_entries) = (entries_of(&base_history), entries_of(&our_history), entries_of(&their_history));
+
+        let mut entries: Vec<Value> = Vec::new();
+        for entry in our_entries.iter().chain(their_entries.iter()) {
+            // Entries one side compacted away stay gone
+            let compacted = base_entries.contains(entry)
+                && (!our_entries.contains(entry) || !their_entries.contains(entry));
+            if !compacted && !entries.contains(entry) {
+                entries.push(entry.clone());
+            }
+        }
+        entries.sort_by(|a, b| timestamp(a).cmp(timestamp(b)));
+
+        // The current review is always the last history entry
+        let current = &reviews[principle];
+        entries.retain(|entry| entry != current);
+        entries.push(current.clone());
+
+        history.insert(principle.clone(), Value::Array(entries));
+    }
+
+    merged.insert("principle_reviews".to_string(), Value::Object(reviews));
+    merged.insert("review_history".to_string(), Value::Object(history));
+    merged
+}
+
+/// Field-by-field three-way merge of two objects, skipping the given keys
+fn merge_fields(
+    context: &str,
+    base: Option<&Map<String, Value>>,
+    ours: &Map<String, Value>,
+    theirs: &Map<String, Value>,
+    skip: &[&str],
+    outcome: &mut MergeOutcome,
+) -> Map<String, Value> {
+    let mut merged = Map::new();
+    let keys: BTreeSet<&String> = ours.keys().chain(theirs.keys()).collect();
+
+    for key in keys {
+        if skip.contains(&key.as_str()) {
+            continue;
+        }
+
+        let field_context = format!("{}: '{}'", context, key);
+        let value = match (ours.get(key), theirs.get(key)) {
+            // Sets keep what either side added and drop what either side removed
+            (o, t) if SET_FIELDS.contains(&key.as_str()) && [o, t].iter().all(|v| v.is_none_or(Value::is_array)) => {
+                let elements = |v: Option<&Value>| v.and_then(Value::as_array).cloned().unwrap_or_default();
+                let set = merge_set(&elements(base.and_then(|b| b.get(key))), &elements(o), &elements(t));
+                (!set.is_empty()).then_some(Value::Array(set))
+            },
+            // Timestamps resolve themselves: newest modification, oldest creation
+            (Some(o), Some(t)) if key == "last_modified" => Some(if t.as_str() > o.as_str() { t.clone() } else { o.clone() }),
+            (Some(o), Some(t)) if key == "created" => Some(if t.as_str() < o.as_str() { t.clone() } else { o.clone() }),
+            (Some(o), Some(t)) => Some(merge_value(&field_context, base.and_then(|b| b.get(key)), o, t, outcome)),
+            (Some(kept), None) | (None, Some(kept)) => {
+                // A field missing on one side was removed there only if the other side left it untouched
+                if base.and_then(|b| b.get(key)) == Some(kept) { None } else { Some(kept.clone()) }
+            },
+            (None, None) => None,
+        };
+
+        if let Some(value) = value {
+            merged.insert(key.clone(), value);
+        }
+    }
+
+    merged
+}
+
+/// Three-way merge of a set stored as a list: an element stays if both sides kept it or one side added it
+///
+/// Our order comes first, then the elements only they added; lists both sides kept sorted stay sorted.
+fn merge_set(base: &[Value], ours: &[Value], theirs: &[Value]) -> Vec<Value> {
+    let mut merged: Vec<Value> = ours.iter()
+        .filter(|v| theirs.contains(v) || !base.contains(v))
+        .cloned()
+        .collect();
+    for value in theirs {
+        if !base.contains(value) && !merged.contains(value) {
+            merged.push(value.clone());
+        }
+    }
+
+    let sorted = |list: &[Value]| list.windows(2).all(|pair| display_value(&pair[0]) <= display_value(&pair[1]));
+    if sorted(ours) && sorted(theirs) {
+        merged.sort_by_key(display_value);
+    }
+
+    merged
+}
+
+/// Three-way merge of a single value; a change on both sides is a conflict
+fn merge_value(context: &str, base: Option<&Value>, ours: &Value, theirs: &Value, outcome: &mut MergeOutcome) -> Value {
+    if ours == theirs || base == Some(theirs) {
+        return ours.clone();
+    }
+    if base == Some(ours) {
+        return theirs.clone();
+    }
+
+    // Both sides changed it differently
+    outcome.conflicts.push(format!("{} differs (ours: {}, theirs: {}) - kept ours", context, display_value(ours), display_value(theirs)));
+    ours.clone()
+}
+
+/// Report references and short names that only became invalid by combining both sides
+fn check_consistency(merged: &Map<String, Value>, outcome: &mut MergeOutcome) {
+    let section = |name: &str| merged.get(name).and_then(|v| v.as_object());
+
+    let mut owners: BTreeMap<&String, Vec<&str>> = BTreeMap::new();
+    for (name, label) in NAMESPACE_SECTIONS {
+        for key in section(name).into_iter().flat_map(|items| items.keys()) {
+            owners.entry(key).or_default().push(label);
+        }
+    }
+    for (key, labels) in owners {
+        if labels.len() > 1 {
+            outcome.conflicts.push(format!("Short name '{}' is used by more than one item ({})", key, labels.join(", ")));
+        }
+    }
+
+    let Some(assets) = section("asset_references") else { return };
+    for (asset_name, asset) in assets {
+        let references = [("entity", "entities"), ("asset_type", "asset_types")];
+        for (field, target) in references {
+            if let Some(value) = asset.get(field).and_then(|v| v.as_str()) {
+                if !section(target).map(|items| items.contains_key(value)).unwrap_or(false) {
+                    outcome.conflicts.push(format!("Asset {} refers to {} '{}', which was deleted on the other side", asset_name, field.replace('_', " "), value));
+                }
+            }
+        }
+
+        for principle in asset.get("principle_reviews").and_then(|v| v.as_object()).into_iter().flat_map(|r| r.keys()) {
+            if !section("principles").map(|items| items.contains_key(principle)).unwrap_or(false) {
+                outcome.conflicts.push(format!("Asset {} has a review for principle '{}', which was deleted on the other side", asset_name, principle));
+            }
+        }
+    }
+}
+
+/// Require a JSON object
+fn as_object(value: Value) -> Result<Map<String, Value>> {
+    match value {
+        Value::Object(map) => Ok(map),
+        _ => Err(anyhow::anyhow!("VQL storage file is not a JSON object")),
+    }
+}
+
+/// Short name of an item, for messages
+fn item_name(item: &Map<String, Value>) -> String {
+    item.get("short_name")
+        .or_else(|| item.get("name"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("?")
+        .to_string()
+}
+
+/// Timestamp of a review, for ordering
+fn timestamp(review: &Value) -> &str {
+    review.get("last_modified").and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// Compact rendering of a value for conflict messages
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn asset(tags: &[&str], path: &str) -> Value {
+        json!({ "short_name": "uc", "path": path, "tags": tags, "principle_reviews": {}, "review_history": {} })
+    }
+
+    #[test]
+    fn sets_merge_element_by_element() {
+        let base = asset(&["critical", "legacy"], "uc.js");
+        let ours = asset(&["critical", "legacy", "payments"], "uc.js");
+        let theirs = asset(&["api", "critical"], "uc.js");
+
+        let outcome = merge_documents(Some(base), ours, theirs).unwrap();
+        assert!(outcome.is_clean(), "{:?}", outcome.conflicts);
+        assert_eq!(outcome.merged["tags"], json!(["api", "critical", "payments"]));
+    }
+
+    #[test]
+    fn both_sides_changing_a_value_is_a_conflict_that_keeps_ours() {
+        let outcome = merge_documents(Some(asset(&[], "uc.js")), asset(&[], "ours.js"), asset(&[], "theirs.js")).unwrap();
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.merged["path"], "ours.js");
+
+        let outcome = merge_documents(Some(asset(&[], "uc.js")), asset(&[], "uc.js"), asset(&[], "theirs.js")).unwrap();
+        assert!(outcome.is_clean());
+        assert_eq!(outcome.merged["path"], "theirs.js");
+    }
+
+    #[test]
+    fn newest_review_wins_and_histories_are_unioned() {
+        let review = |rating: &str, at: &str| json!({ "rating": rating, "analysis": rating, "last_modified": at });
+        let with_review = |current: Value, history: Vec<Value>| json!({
+            "short_name": "uc",
+            "principle_reviews": { "a": current },
+            "review_history": { "a": history },
+        });
+        let first = review("L", "2024-01-01T00:00:00Z");
+        let ours_review = review("M", "2024-02-01T00:00:00Z");
+        let theirs_review = review("H", "2024-03-01T00:00:00Z");
+
+        let outcome = merge_documents(
+            Some(with_review(first.clone(), vec![first.clone()])),
+            with_review(ours_review.clone(), vec![first.clone(), ours_review.clone()]),
+            with_review(theirs_review.clone(), vec![first.clone(), theirs_review.clone()]),
+        ).unwrap();
+
+        assert!(outcome.is_clean());
+        assert_eq!(outcome.merged["principle_reviews"]["a"], theirs_review);
+        assert_eq!(outcome.merged["review_history"]["a"], json!([first, ours_review, theirs_review]));
+    }
+}
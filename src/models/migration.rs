@@ -0,0 +1,357 @@
+use anyhow::{Result, Context};
+use serde_json::{Map, Value};
+
+/// Schema version written by this build of VQL
+pub const CURRENT_VERSION: &str = "2.13.0";
+
+/// A single ordered upgrade step of the storage schema
+struct Migration {
+    /// Version this step upgrades from
+    from: &'static str,
+
+    /// Version this step produces
+    to: &'static str,
+
+    /// Human-readable summary shown when the step runs
+    description: &'static str,
+
+    /// Transformation applied to the raw JSON document; returns notes for anything worth reporting
+    apply: fn(&mut Value) -> Result<Vec<String>>,
+}
+
+/// All known migrations, in the order they must be applied
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: "1.0.0",
+        to: "2.0.0",
+        description: "Move legacy arch/sec/perf/ui ratings into principle reviews",
+        apply: migrate_legacy_ratings,
+    },
+    Migration {
+        from: "2.0.0",
+        to: "2.1.0",
+        description: "Add principle aliases and review revisions",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.1.0",
+        to: "2.2.0",
+        description: "Add principle weights",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.2.0",
+        to: "2.3.0",
+        description: "Add rating scales and the default rating scale",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.3.0",
+        to: "2.4.0",
+        description: "Add asset tags and metadata",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.4.0",
+        to: "2.5.0",
+        description: "Add extra asset paths",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.5.0",
+        to: "2.6.0",
+        description: "Add asset dependencies",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.6.0",
+        to: "2.7.0",
+        description: "Add entity parents",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.7.0",
+        to: "2.8.0",
+        description: "Add review attribution",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.8.0",
+        to: "2.9.0",
+        description: "Add review findings",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.9.0",
+        to: "2.10.0",
+        description: "Add review waivers",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.10.0",
+        to: "2.11.0",
+        description: "Add review content hashes",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.11.0",
+        to: "2.12.0",
+        description: "Add review commits",
+        apply: add_optional_fields,
+    },
+    Migration {
+        from: "2.12.0",
+        to: "2.13.0",
+        description: "Add asset type discovery patterns and discovery ignore patterns",
+        apply: add_optional_fields,
+    },
+];
+
+/// Per-aspect fields of 1.0.0 assets: field prefix, principle short name and long name.
+/// Each prefix had a `<prefix>_rating` and a `<prefix>_analysis` field.
+pub const LEGACY_FIELDS: [(&str, &str, &str); 4] = [
+    ("arch", "a", "Architecture"),
+    ("sec", "s", "Security"),
+    ("perf", "p", "Performance"),
+    ("ui", "u", "UI"),
+];
+
+/// Outcome of migrating a storage document
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    /// Version found in the document before migration
+    pub from_version: String,
+
+    /// Descriptions of the steps that were applied, in order
+    pub steps: Vec<String>,
+
+    /// Notes about data that could not be carried over cleanly
+    pub notes: Vec<String>,
+}
+
+impl MigrationReport {
+    /// Whether any migration step was applied
+    pub fn migrated(&self) -> bool {
+        !self.steps.is_empty()
+    }
+}
+
+/// Parse a "major.minor.patch" version string
+fn parse_version(version: &str) -> Result<(u64, u64, u64)> {
+    let parts: Vec<&str> = version.trim().split('.').collect();
+    if parts.len() != 3 {
+        return Err(anyhow::anyhow!("Invalid storage version: {}", version));
+    }
+
+    let parse = |s: &str| s.parse::<u64>()
+        .map_err(|_| anyhow::anyhow!("Invalid storage version: {}", version));
+
+    Ok((parse(parts[0])?, parse(parts[1])?, parse(parts[2])?))
+}
+
+/// Upgrade a raw storage document to the current schema version in place
+///
+/// Fails if the document was written by a newer version of VQL, or if there is
+/// no known upgrade path from its version.
+pub fn migrate(document: &mut Value) -> Result<MigrationReport> {
+    let stored_version = document.get("version")
+        .and_then(|v| v.as_str())
+        .unwrap_or("1.0.0")
+        .to_string();
+
+    let mut report = MigrationReport {
+        from_version: stored_version.clone(),
+        ..Default::default()
+    };
+
+    let current = parse_version(CURRENT_VERSION)?;
+    if parse_version(&stored_version)? > current {
+        return Err(anyhow::anyhow!(
+            "VQL storage was written by a newer version of VQL (schema {}, this build supports up to {}). Please upgrade VQL",
+            stored_version,
+            CURRENT_VERSION
+        ));
+    }
+
+    let mut version = stored_version;
+    while version != CURRENT_VERSION {
+        let step = MIGRATIONS.iter()
+            .find(|m| m.from == version)
+            .ok_or_else(|| anyhow::anyhow!("No migration path from VQL storage schema {} to {}", version, CURRENT_VERSION))?;
+
+        let notes = (step.apply)(document)
+            .context(format!("Failed to migrate VQL storage from {} to {}", step.from, step.to))?;
+
+        if let Some(obj) = document.as_object_mut() {
+            obj.insert("version".to_string(), Value::String(step.to.to_string()));
+        }
+
+        report.steps.push(format!("{} -> {}: {}", step.from, step.to, step.description));
+        report.notes.extend(notes);
+        version = step.to.to_string();
+    }
+
+    Ok(report)
+}
+
+/// 1.0.0 -> 2.0.0: fold the legacy per-aspect fields into `principle_reviews` and drop them
+fn migrate_legacy_ratings(document: &mut Value) -> Result<Vec<String>> {
+    let mut notes = Vec::new();
+    let root = document.as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("VQL storage is not a JSON object"))?;
+
+    // Collect every short name in use so placeholder principles never collide
+    let mut taken_names: Vec<String> = Vec::new();
+    for section in ["entities", "asset_types", "asset_references"] {
+        if let Some(Value::Object(items)) = root.get(section) {
+            taken_names.extend(items.keys().cloned());
+        }
+    }
+
+    let mut principles = match root.remove("principles") {
+        Some(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+
+    if let Some(Value::Object(assets)) = root.get_mut("asset_references") {
+        for (asset_name, asset) in assets.iter_mut() {
+            let asset = match asset.as_object_mut() {
+                Some(asset) => asset,
+                None => continue,
+            };
+
+            let asset_modified = asset.get("last_modified").cloned()
+                .unwrap_or(Value::Null);
+
+            let mut reviews = match asset.remove("principle_reviews") {
+                Some(Value::Object(map)) => map,
+                _ => Map::new(),
+            };
+
+            for (prefix, principle, long_name) in LEGACY_FIELDS {
+                let rating = asset.remove(&format!("{}_rating", prefix)).unwrap_or(Value::Null);
+                let analysis = asset.remove(&format!("{}_analysis", prefix)).unwrap_or(Value::Null);
+
+                if rating.is_null() && analysis.is_null() {
+                    continue;
+                }
+
+                // A newer principle review already supersedes the legacy copy
+                if reviews.contains_key(principle) {
+                    continue;
+                }
+
+                if !principles.contains_key(principle) {
+                    if taken_names.iter().any(|n| n == principle) {
+                        notes.push(format!(
+                            "Asset {}: dropped legacy {} review - short name '{}' is used by another item",
+                            asset_name, prefix, principle
+                        ));
+                        continue;
+                    }
+
+                    principles.insert(principle.to_string(), serde_json::json!({
+                        "short_name": principle,
+                        "long_name": long_name,
+                        "guidance": null,
+                        "last_modified": asset_modified.clone(),
+                    }));
+                    notes.push(format!("Created principle '{}' ({}) for legacy reviews", principle, long_name));
+                }
+
+                reviews.insert(principle.to_string(), serde_json::json!({
+                    "rating": rating,
+                    "analysis": analysis,
+                    "last_modified": asset_modified.clone(),
+                }));
+            }
+
+            asset.insert("principle_reviews".to_string(), Value::Object(reviews));
+        }
+    }
+
+    root.insert("principles".to_string(), Value::Object(principles));
+
+    Ok(notes)
+}
+
+/// Steps that only add optional fields: older documents already parse, so nothing changes.
+/// The version bump itself is what matters - it stops older builds from loading the file
+/// and dropping the fields they don't know about on their next save.
+fn add_optional_fields(_document: &mut Value) -> Result<Vec<String>> {
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use super::*;
+    use crate::models::json_storage::JsonStorage;
+
+    #[test]
+    fn every_version_upgrades_to_current() {
+        for step in MIGRATIONS.iter().filter(|m| m.from != "1.0.0") {
+            let mut document = serde_json::json!({ "version": step.from, "principles": {} });
+            let report = migrate(&mut document).unwrap();
+            assert!(report.migrated());
+            assert_eq!(document["version"], CURRENT_VERSION);
+        }
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let mut document = serde_json::json!({ "version": "2.14.0" });
+        assert!(migrate(&mut document).is_err());
+    }
+
+    #[test]
+    fn legacy_ratings_become_principle_reviews() {
+        let mut document = serde_json::json!({
+            "version": "1.0.0",
+            "entities": { "s": { "short_name": "s", "description": "Session" } },
+            "asset_references": {
+                "uc": {
+                    "arch_rating": "H",
+                    "arch_analysis": "Thin controller",
+                    "sec_rating": "L",
+                    "last_modified": "2024-01-01T00:00:00Z",
+                },
+            },
+        });
+
+        let report = migrate(&mut document).unwrap();
+        assert_eq!(report.from_version, "1.0.0");
+        assert_eq!(report.steps.len(), MIGRATIONS.len());
+        assert_eq!(document["version"], CURRENT_VERSION);
+
+        let asset = &document["asset_references"]["uc"];
+        assert!(asset.get("arch_rating").is_none());
+        assert_eq!(asset["principle_reviews"]["a"]["rating"], "H");
+        assert_eq!(asset["principle_reviews"]["a"]["analysis"], "Thin controller");
+        assert_eq!(document["principles"]["a"]["long_name"], "Architecture");
+
+        // "s" is taken by an entity, so the security review cannot get a principle of its own
+        assert!(asset["principle_reviews"].get("s").is_none());
+        assert!(report.notes.iter().any(|note| note.contains("dropped legacy sec review")));
+    }
+
+    #[test]
+    fn loading_an_old_file_backs_it_up_and_rewrites_it() {
+        let project = tempfile::tempdir().unwrap();
+        let vql_dir = project.path().join("VQL");
+        fs::create_dir_all(&vql_dir).unwrap();
+        let mut old = serde_json::to_value(JsonStorage::new()).unwrap();
+        old["version"] = Value::String("2.0.0".to_string());
+        fs::write(vql_dir.join("vql_storage.json"), old.to_string()).unwrap();
+
+        let storage = JsonStorage::load_or_create(&vql_dir).unwrap();
+        assert_eq!(storage.version, CURRENT_VERSION);
+        drop(storage);
+
+        let backup: Value = serde_json::from_str(&fs::read_to_string(vql_dir.join("vql_storage.json.v2.0.0.bak")).unwrap()).unwrap();
+        assert_eq!(backup["version"], "2.0.0");
+        let rewritten: Value = serde_json::from_str(&fs::read_to_string(vql_dir.join("vql_storage.json")).unwrap()).unwrap();
+        assert_eq!(rewritten["version"], CURRENT_VERSION);
+    }
+}
+
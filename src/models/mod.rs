@@ -1,3 +1,19 @@
 pub mod asset;
+pub mod asset_paths;
+pub mod attribution;
+pub mod backend;
+pub mod check;
 pub mod config;
-pub mod json_storage;
\ No newline at end of file
+pub mod discovery;
+pub mod doctor;
+pub mod filter;
+pub mod findings;
+pub mod journal;
+pub mod json_storage;
+pub mod layout;
+pub mod merge;
+pub mod migration;
+pub mod rating;
+pub mod score;
+pub mod tags;
+pub mod waiver;
\ No newline at end of file
@@ -0,0 +1,165 @@
+use std::sync::OnceLock;
+use anyhow::{Result, anyhow};
+use serde::{Serialize, Deserialize};
+
+/// Name of the built-in High/Medium/Low scale, used when nothing else is configured
+pub const DEFAULT_SCALE_NAME: &str = "hml";
+
+/// One level of a rating scale
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RatingLevel {
+    /// Value stored in reviews and typed in commands, e.g. "H", "4" or "pass"
+    pub value: String,
+
+    /// Name shown to people, e.g. "High"
+    pub label: String,
+
+    /// Colour the label is shown in (a terminal colour name such as "green" or "bright_red")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+/// An ordered set of ratings a principle can be given, best first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RatingScale {
+    pub levels: Vec<RatingLevel>,
+}
+
+impl RatingScale {
+    /// The built-in High/Medium/Low scale
+    pub fn high_medium_low() -> &'static RatingScale {
+        static SCALE: OnceLock<RatingScale> = OnceLock::new();
+        SCALE.get_or_init(|| RatingScale {
+            levels: vec![
+                RatingLevel { value: "H".to_string(), label: "High".to_string(), color: Some("green".to_string()) },
+                RatingLevel { value: "M".to_string(), label: "Medium".to_string(), color: Some("yellow".to_string()) },
+                RatingLevel { value: "L".to_string(), label: "Low".to_string(), color: Some("red".to_string()) },
+            ],
+        })
+    }
+
+    /// Build a scale from level specs written best first, each "value[:label[:colour]]"
+    ///
+    /// e.g. `["5:Excellent:green", "4:Good", "3", "2:Poor:red", "1:Failing:red"]`
+    pub fn from_specs(specs: &[&str]) -> Result<Self> {
+        if specs.len() < 2 {
+            return Err(anyhow!("A rating scale needs at least two levels, best first, e.g. pass:Pass:green fail:Fail:red"));
+        }
+
+        let mut levels: Vec<RatingLevel> = Vec::new();
+        for spec in specs {
+            let mut fields = spec.splitn(3, ':').map(str::trim);
+            let value = fields.next().unwrap_or_default();
+            let label = fields.next().filter(|l| !l.is_empty()).unwrap_or(value);
+            let color = fields.next().filter(|c| !c.is_empty());
+
+            if value.is_empty() || !value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '+') {
+                return Err(anyhow!("Invalid rating value '{}': use letters, digits, '_', '-' or '+'", value));
+            }
+            if let Some(color) = color {
+                if parse_color(color).is_none() {
+                    return Err(anyhow!("Unknown colour '{}' for rating {}", color, value));
+                }
+            }
+
+            let level = RatingLevel {
+                value: value.to_string(),
+                label: label.to_string(),
+                color: color.map(str::to_string),
+            };
+            if levels.iter().any(|l| l.matches(&level.value) || l.matches(&level.label)) {
+                return Err(anyhow!("Rating '{}' appears more than once in the scale", value));
+            }
+            levels.push(level);
+        }
+
+        Ok(RatingScale { levels })
+    }
+
+    /// Find the level a rating refers to by value or label, ignoring case
+    pub fn level(&self, rating: &str) -> Option<&RatingLevel> {
+        self.levels.iter().find(|level| level.matches(rating))
+    }
+
+    /// Turn a rating as typed (e.g. "h" or "high") into the value stored in reviews
+    pub fn parse(&self, rating: &str) -> Result<&str> {
+        self.level(rating.trim())
+            .map(|level| level.value.as_str())
+            .ok_or_else(|| anyhow!("Invalid rating: {}. Must be one of {}", rating, self.describe()))
+    }
+
+    /// Where a rating sits on the scale: 1 for the best level, 0 for the worst
+    pub fn fraction(&self, rating: &str) -> Option<f64> {
+        let position = self.levels.iter().position(|level| level.matches(rating))?;
+        if self.levels.len() < 2 {
+            return Some(1.0);
+        }
+        Some((self.levels.len() - 1 - position) as f64 / (self.levels.len() - 1) as f64)
+    }
+
+    /// Find a rating stated in review text, e.g. "HIGH compliance" or "Compliance: 4"
+    ///
+    /// Explicit "<label> compliance" / "compliance: <rating>" phrases win over a bare
+    /// mention of a label such as "rated as high.".
+    pub fn extract_from_text(&self, text: &str) -> Option<String> {
+        let text_lower = text.to_lowercase();
+
+        for level in &self.levels {
+            let label = level.label.to_lowercase();
+            let value = level.value.to_lowercase();
+            if mentions(&text_lower, &format!("{} compliance", label))
+                || mentions(&text_lower, &format!("compliance: {}", label))
+                || mentions(&text_lower, &format!("compliance: {}", value)) {
+                return Some(level.value.clone());
+            }
+        }
+
+        // Fallback for simple mentions like "rated as HIGH"
+        self.levels.iter()
+            .find(|level| mentions(&text_lower, &format!(" {}", level.label.to_lowercase())))
+            .map(|level| level.value.clone())
+    }
+
+    /// The levels for messages, e.g. "H (High), M (Medium), L (Low)"
+    pub fn describe(&self) -> String {
+        self.levels.iter()
+            .map(|level| if level.label == level.value {
+                level.value.clone()
+            } else {
+                format!("{} ({})", level.value, level.label)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The values alone, best first, e.g. "H/M/L"
+    pub fn choices(&self) -> String {
+        self.levels.iter().map(|level| level.value.as_str()).collect::<Vec<_>>().join("/")
+    }
+}
+
+impl RatingLevel {
+    fn matches(&self, rating: &str) -> bool {
+        self.value.eq_ignore_ascii_case(rating) || self.label.eq_ignore_ascii_case(rating)
+    }
+}
+
+/// Parse a colour name, accepting "bright_red" and "bright-red" for "bright red"
+pub fn parse_color(name: &str) -> Option<colored::Color> {
+    name.replace(['_', '-'], " ").parse().ok()
+}
+
+/// Check the name of a rating scale (letters, digits and underscores)
+pub fn validate_scale_name(name: &str) -> Result<()> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(anyhow!("Invalid rating scale name '{}': use letters, digits and underscores only", name));
+    }
+    Ok(())
+}
+
+/// Whether `needle` occurs in `text` as whole words (not followed by a letter, digit, '-' or '_')
+fn mentions(text: &str, needle: &str) -> bool {
+    text.match_indices(needle).any(|(start, _)| {
+        !text[start + needle.len()..].chars().next().is_some_and(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    })
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use crate::models::json_storage::{AssetReference, JsonStorage};
+use crate::models::filter::{AssetFilter, ReviewFilter};
+
+/// Weighted points earned out of the points possible
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Score {
+    pub earned: f64,
+    pub possible: f64,
+}
+
+impl Score {
+    /// Score as a percentage, or None when nothing counted towards it
+    pub fn percent(&self) -> Option<f64> {
+        if self.possible > 0.0 {
+            Some(self.earned / self.possible * 100.0)
+        } else {
+            None
+        }
+    }
+
+    fn add(&mut self, other: Score) {
+        self.earned += other.earned;
+        self.possible += other.possible;
+    }
+}
+
+/// How one asset scores against each top-level principle
+#[derive(Debug, Clone)]
+pub struct AssetScore<'a> {
+    pub asset: &'a str,
+    pub total: Score,
+
+    /// Weighted score per top-level principle that has a rating for the asset
+    pub principles: BTreeMap<&'a str, Score>,
+}
+
+/// Scores for every asset, with totals by principle, entity and asset type
+#[derive(Debug, Clone, Default)]
+pub struct ProjectScore<'a> {
+    pub total: Score,
+    pub assets: Vec<AssetScore<'a>>,
+    pub by_principle: BTreeMap<&'a str, Score>,
+    pub by_entity: BTreeMap<&'a str, Score>,
+    pub by_asset_type: BTreeMap<&'a str, Score>,
+}
+
+/// Score every asset in storage that matches the filter, counting only the ratings of reviews that match `reviews`
+pub fn score_project<'a>(storage: &'a JsonStorage, filter: &AssetFilter, reviews: &ReviewFilter) -> ProjectScore<'a> {
+    let mut project = ProjectScore::default();
+
+    for (asset_name, asset) in storage.asset_references.iter().filter(|(_, asset)| filter.matches(storage, asset)) {
+        let score = score_asset(storage, asset_name, asset, reviews);
+
+        project.total.add(score.total);
+        project.by_entity.entry(asset.entity.as_str()).or_default().add(score.total);
+        project.by_asset_type.entry(asset.asset_type.as_str()).or_default().add(score.total);
+        for (principle, principle_score) in &score.principles {
+            project.by_principle.entry(principle).or_default().add(*principle_score);
+        }
+
+        project.assets.push(score);
+    }
+
+    project
+}
+
+/// Score one asset against the top-level principles
+///
+/// Each rated principle earns its weight times where the rating sits on the principle's
+/// scale, from all of it for the best level to none for the worst. A principle
+/// without a rating of its own is scored from its sub-principles, weighted among themselves.
+/// This differs from the rating `uc?` rolls up (`JsonStorage::rolled_up_rating`), which is
+/// the lowest sub-principle rating, since a shown rating has to be a level on the scale.
+pub fn score_asset<'a>(storage: &'a JsonStorage, asset_name: &'a str, asset: &'a AssetReference, reviews: &ReviewFilter) -> AssetScore<'a> {
+    let mut total = Score::default();
+    let mut principles = BTreeMap::new();
+
+    for principle in storage.principles.values().filter(|p| p.parent().is_none()) {
+        let Some(value) = principle_value(storage, asset, &principle.short_name, reviews) else {
+            continue;
+        };
+
+        let weight = principle.effective_weight();
+        let score = Score { earned: value * weight, possible: weight };
+        total.add(score);
+        principles.insert(principle.short_name.as_str(), score);
+    }
+
+    AssetScore { asset: asset_name, total, principles }
+}
+
+/// An asset's rating for a principle as a share of the best level (0 to 1), if it has been rated and is not waived
+fn principle_value(storage: &JsonStorage, asset: &AssetReference, principle: &str, reviews: &ReviewFilter) -> Option<f64> {
+    // An accepted risk is left out while its waiver is active
+    if asset.active_waiver(principle).is_some() {
+        return None;
+    }
+
+    let own_rating = asset.principle_reviews.get(principle)
+        .filter(|review| reviews.matches(review))
+        .and_then(|review| review.rating.as_deref())
+        .and_then(|rating| storage.rating_scale_for(principle).fraction(rating));
+    if own_rating.is_some() {
+        return own_rating;
+    }
+
+    let mut rolled_up = Score::default();
+    for sub in storage.sub_principles(principle) {
+        if let Some(value) = principle_value(storage, asset, sub, reviews) {
+            let weight = storage.principles[sub].effective_weight();
+            rolled_up.add(Score { earned: value * weight, possible: weight });
+        }
+    }
+
+    rolled_up.percent().map(|percent| percent / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::attribution::Attribution;
+
+    #[test]
+    fn sub_principles_roll_up_as_lowest_rating_but_score_as_weighted_average() {
+        let mut storage = JsonStorage::new();
+        storage.add_principle("a", "Architecture", None).unwrap();
+        storage.add_principle("a.dry", "Don't repeat yourself", None).unwrap();
+        storage.add_principle("a.kiss", "Keep it simple", None).unwrap();
+        storage.add_entity("u", "User").unwrap();
+        storage.add_asset_type("c", "Controller").unwrap();
+        storage.add_asset_reference("uc", "u", "c", "uc.js").unwrap();
+        storage.store_asset_review("uc", "a.dry", Some("H"), "No duplication", &Attribution::default(), Vec::new()).unwrap();
+        storage.store_asset_review("uc", "a.kiss", Some("L"), "Deeply nested", &Attribution::default(), Vec::new()).unwrap();
+
+        assert_eq!(storage.rolled_up_rating("uc", "a"), Some(("a.kiss", "L")));
+
+        let asset = &storage.asset_references["uc"];
+        let score = score_asset(&storage, "uc", asset, &ReviewFilter::default());
+        assert_eq!(score.principles["a"].percent(), Some(50.0));
+
+        // Weights shift the score, never the rolled-up rating
+        storage.set_principle_weight("a.dry", Some(3.0)).unwrap();
+        let asset = &storage.asset_references["uc"];
+        let score = score_asset(&storage, "uc", asset, &ReviewFilter::default());
+        assert_eq!(score.principles["a"].percent(), Some(75.0));
+        assert_eq!(storage.rolled_up_rating("uc", "a"), Some(("a.kiss", "L")));
+    }
+}
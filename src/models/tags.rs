@@ -0,0 +1,18 @@
+use anyhow::{Result, anyhow};
+
+/// Turn a tag as typed into its stored form (lowercase letters, digits, '_', '-' and '.')
+pub fn normalize_tag(tag: &str) -> Result<String> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    if tag.is_empty() || !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.') {
+        return Err(anyhow!("Invalid tag '{}': use letters, digits, '_', '-' or '.'", tag));
+    }
+    Ok(tag)
+}
+
+/// Check a metadata key (letters, digits, '_', '-' and '.')
+pub fn validate_metadata_key(key: &str) -> Result<()> {
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.') {
+        return Err(anyhow!("Invalid metadata key '{}': use letters, digits, '_', '-' or '.'", key));
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use anyhow::{Result, anyhow};
+use chrono::{NaiveDate, Utc};
+use serde::{Serialize, Deserialize};
+
+/// Date format waiver expiry dates are written in
+pub const EXPIRY_FORMAT: &str = "%Y-%m-%d";
+
+/// An accepted risk: the rating of an (asset, principle) pair is a conscious trade-off until a date
+///
+/// While a waiver is active the pair is shown apart from other reviews and left out of scores
+/// and checks. Once it expires the rating counts again and the waiver is flagged until it is
+/// renewed or removed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Waiver {
+    /// Why the rating is accepted
+    pub justification: String,
+
+    /// Who accepted it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+
+    /// When the waiver was recorded
+    pub created: String,
+
+    /// Last day the waiver applies (YYYY-MM-DD)
+    pub expires: String,
+}
+
+impl Waiver {
+    /// A waiver recorded now, checking that the expiry date is a valid date that has not passed
+    pub fn new(justification: &str, author: Option<&str>, expires: &str) -> Result<Self> {
+        let justification = justification.trim().trim_matches('"').trim();
+        if justification.is_empty() {
+            return Err(anyhow!("A waiver needs a justification"));
+        }
+
+        let expiry = parse_expiry(expires)?;
+        if expiry < today() {
+            return Err(anyhow!("Expiry date {} has already passed", expires.trim()));
+        }
+
+        Ok(Waiver {
+            justification: justification.to_string(),
+            author: author.map(str::to_string),
+            created: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            expires: expiry.format(EXPIRY_FORMAT).to_string(),
+        })
+    }
+
+    /// Whether the waiver still applies today (it applies through its expiry date)
+    pub fn is_active(&self) -> bool {
+        parse_expiry(&self.expires).is_ok_and(|expiry| expiry >= today())
+    }
+}
+
+/// Parse an expiry date written as YYYY-MM-DD
+pub fn parse_expiry(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date.trim(), EXPIRY_FORMAT)
+        .map_err(|_| anyhow!("Invalid expiry date '{}': use YYYY-MM-DD", date.trim()))
+}
+
+fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use super::*;
+    use crate::models::attribution::Attribution;
+    use crate::models::filter::ReviewFilter;
+    use crate::models::json_storage::JsonStorage;
+    use crate::models::score;
+
+    fn day(offset: i64) -> String {
+        (today() + Duration::days(offset)).format(EXPIRY_FORMAT).to_string()
+    }
+
+    #[test]
+    fn waivers_apply_through_their_expiry_date() {
+        assert!(Waiver::new("Legacy adapter", None, &day(0)).unwrap().is_active());
+        assert!(Waiver::new("Legacy adapter", None, &day(-1)).is_err());
+        assert!(Waiver::new("  ", None, &day(1)).is_err());
+        assert!(Waiver::new("Legacy adapter", None, "next year").is_err());
+
+        // A waiver recorded earlier stops applying the day after it expires
+        let mut waiver = Waiver::new("Legacy adapter", Some("dom"), &day(1)).unwrap();
+        waiver.expires = day(-1);
+        assert!(!waiver.is_active());
+    }
+
+    #[test]
+    fn expired_waivers_count_in_scores_again() {
+        let mut storage = JsonStorage::new();
+        storage.add_principle("a", "Architecture", None).unwrap();
+        storage.add_principle("a.dry", "Don't repeat yourself", None).unwrap();
+        storage.add_entity("u", "User").unwrap();
+        storage.add_asset_type("c", "Controller").unwrap();
+        storage.add_asset_reference("uc", "u", "c", "uc.js").unwrap();
+        storage.store_asset_review("uc", "a", Some("L"), "Tangled", &Attribution::default(), Vec::new()).unwrap();
+        storage.waive("uc", "a", Waiver::new("Rewrite planned", None, &day(30)).unwrap()).unwrap();
+
+        // A waiver on a principle covers its sub-principles
+        let asset = &storage.asset_references["uc"];
+        assert!(asset.active_waiver("a.dry").is_some());
+        let scored = score::score_asset(&storage, "uc", asset, &ReviewFilter::default());
+        assert!(scored.total.percent().is_none());
+
+        storage.asset_references.get_mut("uc").unwrap().waivers.get_mut("a").unwrap().expires = day(-1);
+        let asset = &storage.asset_references["uc"];
+        assert!(asset.active_waiver("a").is_none());
+        let scored = score::score_asset(&storage, "uc", asset, &ReviewFilter::default());
+        assert_eq!(scored.total.percent(), Some(0.0));
+    }
+}
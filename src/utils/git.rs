@@ -0,0 +1,83 @@
+use std::path::Path;
+use std::process::Command;
+use anyhow::{Result, Context, anyhow};
+
+/// Run a git command in a directory and return its trimmed output
+pub fn run(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git - is it installed?")?;
+
+    if !output.status.success() {
+        return Err(anyhow!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether a directory is inside a git working tree (false when git is not installed)
+pub fn is_repository(dir: &Path) -> bool {
+    run(dir, &["rev-parse", "--is-inside-work-tree"]).is_ok_and(|out| out == "true")
+}
+
+/// Commit checked out in a directory's working tree, or None outside a repository or before the first commit
+pub fn head_commit(dir: &Path) -> Option<String> {
+    run(dir, &["rev-parse", "--verify", "--quiet", "HEAD"]).ok().filter(|commit| !commit.is_empty())
+}
+
+/// Whether a commit exists in the repository (it may not after a rebase or a shallow clone)
+pub fn has_commit(dir: &Path, commit: &str) -> bool {
+    run(dir, &["cat-file", "-e", &format!("{}^{{commit}}", commit)]).is_ok()
+}
+
+/// How much a set of paths has changed since a commit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// Commits since the given one that touch the paths
+    pub commits: usize,
+
+    /// Lines added and removed between the commit and the working tree, uncommitted changes included
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl Changes {
+    /// Lines added and removed together
+    pub fn lines(&self) -> usize {
+        self.added + self.removed
+    }
+
+    /// Whether anything changed at all
+    pub fn is_empty(&self) -> bool {
+        self.commits == 0 && self.lines() == 0
+    }
+}
+
+/// Changes to paths (relative to `dir`, glob patterns allowed) since a commit
+pub fn changes_since(dir: &Path, commit: &str, paths: &[&str]) -> Result<Changes> {
+    let range = format!("{}..HEAD", commit);
+    let mut args = vec!["rev-list", "--count", range.as_str(), "--"];
+    args.extend(paths);
+    let commits = run(dir, &args)?.parse::<usize>()
+        .context("Unexpected output from git rev-list")?;
+
+    let mut args = vec!["diff", "--numstat", commit, "--"];
+    args.extend(paths);
+    let mut changes = Changes { commits, ..Changes::default() };
+    for line in run(dir, &args)?.lines() {
+        // Lines look like "added<TAB>removed<TAB>path"; binary files show "-" for both counts
+        let mut counts = line.split('\t').map(|count| count.parse::<usize>().unwrap_or(0));
+        changes.added += counts.next().unwrap_or(0);
+        changes.removed += counts.next().unwrap_or(0);
+    }
+
+    Ok(changes)
+}
+
+/// A commit shortened for display
+pub fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(8)]
+}
@@ -0,0 +1,109 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+use anyhow::{Result, Context};
+use fs2::FileExt;
+
+/// Name of the lock file created inside the VQL directory
+pub const LOCK_FILE_NAME: &str = ".vql_storage.lock";
+
+/// Default number of seconds to wait for another process to release the lock
+const DEFAULT_LOCK_TIMEOUT_SECS: u64 = 10;
+
+/// Delay between attempts to acquire the lock
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Advisory lock held around a load-modify-save cycle of VQL storage.
+///
+/// The lock is released when this value is dropped (or when the process exits).
+#[derive(Debug)]
+pub struct StorageLock {
+    file: File,
+}
+
+impl StorageLock {
+    /// Acquire an exclusive lock on the VQL directory, waiting up to the configured timeout
+    ///
+    /// The timeout defaults to 10 seconds and can be overridden with the
+    /// `VQL_LOCK_TIMEOUT` environment variable (in seconds, 0 = fail immediately).
+    pub fn acquire(vql_dir: &Path) -> Result<Self> {
+        let path = vql_dir.join(LOCK_FILE_NAME);
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .context(format!("Failed to open VQL lock file at {}", path.display()))?;
+
+        let timeout = lock_timeout();
+        let started = Instant::now();
+
+        loop {
+            match file.try_lock_exclusive() {
+                Ok(()) => return Ok(StorageLock { file }),
+                Err(e) if e.kind() == ErrorKind::WouldBlock || e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => {
+                    if started.elapsed() >= timeout {
+                        return Err(anyhow::anyhow!(
+                            "VQL storage is locked by another process (lock file: {}). Waited {}s; try again once the other VQL command has finished, or raise VQL_LOCK_TIMEOUT",
+                            path.display(),
+                            timeout.as_secs()
+                        ));
+                    }
+                    thread::sleep(LOCK_RETRY_INTERVAL);
+                },
+                Err(e) => {
+                    return Err(anyhow::Error::new(e)
+                        .context(format!("Failed to lock VQL storage at {}", path.display())));
+                },
+            }
+        }
+    }
+}
+
+impl Drop for StorageLock {
+    fn drop(&mut self) {
+        // Ignore errors - the OS releases the lock when the handle closes anyway
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// Read the lock timeout from the environment, falling back to the default
+fn lock_timeout() -> Duration {
+    let secs = std::env::var("VQL_LOCK_TIMEOUT")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_LOCK_TIMEOUT_SECS);
+
+    Duration::from_secs(secs)
+}
+
+/// Write a file atomically: write to a temporary sibling, flush it to disk, then rename over the target.
+///
+/// A crash part-way through leaves either the old file or the new file, never a truncated one.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let file_name = path.file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid file path: {}", path.display()))?;
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name));
+
+    {
+        let mut tmp_file = File::create(&tmp_path)
+            .context(format!("Failed to create temporary file {}", tmp_path.display()))?;
+        tmp_file.write_all(contents)
+            .context(format!("Failed to write temporary file {}", tmp_path.display()))?;
+        tmp_file.sync_all()
+            .context(format!("Failed to flush temporary file {}", tmp_path.display()))?;
+    }
+
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(anyhow::Error::new(e)
+            .context(format!("Failed to replace {}", path.display())));
+    }
+
+    Ok(())
+}
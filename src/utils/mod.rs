@@ -1,3 +1,5 @@
 pub mod filesystem;
+pub mod git;
+pub mod lock;
 pub mod parser;
 pub mod platform;
\ No newline at end of file
@@ -39,7 +39,7 @@ pub fn parse_asset(content: &str, asset_ref: &str) -> Result<AssetData> {
         let text = analysis_text.as_str();
         
         // Architecture analysis
-        if let Some(arch_match) = Regex::new(r"ARCH_ANALYSIS:(.*?)(?:SEC_ANALYSIS|PERF_ANALYSIS|$)")
+        if let Some(arch_match) = Regex::new(r"(?s)ARCH_ANALYSIS:(.*?)(?:SEC_ANALYSIS|PERF_ANALYSIS|$)")
             .ok()
             .and_then(|re| re.captures(text))
             .and_then(|cap| cap.get(1)) {
@@ -47,7 +47,7 @@ pub fn parse_asset(content: &str, asset_ref: &str) -> Result<AssetData> {
         }
         
         // Security analysis
-        if let Some(sec_match) = Regex::new(r"SEC_ANALYSIS:(.*?)(?:ARCH_ANALYSIS|PERF_ANALYSIS|$)")
+        if let Some(sec_match) = Regex::new(r"(?s)SEC_ANALYSIS:(.*?)(?:ARCH_ANALYSIS|PERF_ANALYSIS|$)")
             .ok()
             .and_then(|re| re.captures(text))
             .and_then(|cap| cap.get(1)) {
@@ -55,7 +55,7 @@ pub fn parse_asset(content: &str, asset_ref: &str) -> Result<AssetData> {
         }
         
         // Performance analysis
-        if let Some(perf_match) = Regex::new(r"PERF_ANALYSIS:(.*?)(?:ARCH_ANALYSIS|SEC_ANALYSIS|$)")
+        if let Some(perf_match) = Regex::new(r"(?s)PERF_ANALYSIS:(.*?)(?:ARCH_ANALYSIS|SEC_ANALYSIS|$)")
             .ok()
             .and_then(|re| re.captures(text))
             .and_then(|cap| cap.get(1)) {